@@ -0,0 +1,56 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::ShellResult;
+use crate::files::open_file;
+use crate::json::push_string;
+
+/// Environment variable naming the file that receives the audit log.
+pub const AUDIT_LOG_VAR: &str = "SHELL_AUDIT_LOG";
+
+/// Appends one JSON object per executed command, recording its fully
+/// expanded argv and a Unix timestamp.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Builds an audit log from `$SHELL_AUDIT_LOG`, if set.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(AUDIT_LOG_VAR)
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|path| Self::new(Path::new(&path)))
+    }
+
+    pub fn record(&self, argv: &[String]) -> ShellResult<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut line = format!(
+            "{{\"timestamp\":{}.{:03},\"pid\":{},\"argv\":[",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            std::process::id()
+        );
+        for (i, arg) in argv.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            push_string(&mut line, arg);
+        }
+        line.push_str("]}\n");
+
+        let mut file = open_file(&self.path, true, false)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
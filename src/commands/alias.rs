@@ -0,0 +1,105 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+/// Quotes an alias value the way `alias` prints it, so it can be re-entered.
+fn quote_value(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+pub struct AliasCommand;
+
+impl Command for AliasCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.is_empty() {
+            for (name, value) in registry.get_aliases() {
+                writeln!(ctx.output, "alias {}={}", name, quote_value(&value))?;
+            }
+            return Ok(ShellStatus::Continue);
+        }
+
+        for arg in args {
+            match arg.split_once('=') {
+                Some((name, value)) => registry.set_alias(name, value),
+                None => match registry.get_alias(arg) {
+                    Some(value) => writeln!(ctx.output, "alias {}={}", arg, quote_value(&value))?,
+                    None => {
+                        return Err(ShellError::AliasNotFound {
+                            command: self.get_name().to_string(),
+                            name: arg.clone(),
+                        });
+                    }
+                },
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "alias"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "alias [name[=value] ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Define or display aliases.\n\n\
+         Without arguments, print the list of aliases in the reusable form\n\
+         `alias NAME=VALUE'. Otherwise, define an alias for each NAME whose\n\
+         VALUE is given, and print the alias for each NAME without one."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
+
+pub struct UnaliasCommand;
+
+impl Command for UnaliasCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.first().is_some_and(|arg| arg == "-a") {
+            registry.clear_aliases();
+            return Ok(ShellStatus::Continue);
+        }
+
+        for name in args {
+            if !registry.remove_alias(name) {
+                return Err(ShellError::AliasNotFound {
+                    command: self.get_name().to_string(),
+                    name: name.clone(),
+                });
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "unalias"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "unalias [-a] name [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Remove each NAME from the list of defined aliases.\n\n\
+         With -a, remove all alias definitions."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
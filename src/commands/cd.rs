@@ -1,37 +1,161 @@
 use std::env;
-use std::io::Write;
-use std::path::Path;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 
-use super::{Command, CommandRegistry, ShellStatus};
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
 use crate::error::{ShellError, ShellResult};
+use crate::expand::cdpath_dirs;
+use crate::options::ShellOption;
+
+/// The largest `spelling_distance` that `cdspell` still corrects.
+const MAX_SPELLING_DISTANCE: usize = 2;
+
+/// Makes `dir` the working directory, then exports the new `PWD` and
+/// `OLDPWD` so spawned children inherit them. `command` names the builtin
+/// in the error reported when `dir` cannot be entered.
+pub(super) fn change_directory(command: &str, dir: &Path) -> ShellResult<()> {
+    let old_dir = env::current_dir().ok();
+
+    env::set_current_dir(dir).map_err(|_| ShellError::DirectoryNotFound {
+        command: command.to_string(),
+        path: dir.display().to_string(),
+    })?;
+
+    // SAFETY: the shell is single-threaded, so nothing reads the
+    // environment concurrently.
+    unsafe {
+        if let Some(old_dir) = old_dir {
+            env::set_var("OLDPWD", old_dir);
+        }
+        if let Ok(cwd) = env::current_dir() {
+            env::set_var("PWD", cwd);
+        }
+    }
+
+    Ok(())
+}
 
 pub struct CdCommand;
 
+impl CdCommand {
+    /// Looks a relative `dir` up in each `$CDPATH` entry. Paths that start
+    /// with `/`, `.` or `..` are used as given, like in bash.
+    fn search_cdpath(dir: &str) -> Option<PathBuf> {
+        let is_explicit = dir.starts_with('/')
+            || dir == "."
+            || dir == ".."
+            || dir.starts_with("./")
+            || dir.starts_with("../");
+        if is_explicit {
+            return None;
+        }
+
+        cdpath_dirs()
+            .into_iter()
+            .map(|base| Path::new(&base).join(dir))
+            .find(|candidate| candidate.is_dir())
+    }
+
+    /// Corrects each component of `dir` that names no directory to the
+    /// closest-spelled directory next to it, as `shopt -s cdspell` does.
+    /// Returns `None` if some component has no close enough match.
+    fn correct_spelling(dir: &str) -> Option<PathBuf> {
+        let mut corrected = PathBuf::new();
+        for component in Path::new(dir).components() {
+            let Component::Normal(name) = component else {
+                corrected.push(component);
+                continue;
+            };
+            let candidate = corrected.join(name);
+            if candidate.is_dir() {
+                corrected = candidate;
+                continue;
+            }
+
+            let parent = if corrected.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                corrected.as_path()
+            };
+            let name = name.to_str()?;
+            let mut entries: Vec<String> = fs::read_dir(parent)
+                .ok()?
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect();
+            entries.sort();
+
+            let best = entries
+                .into_iter()
+                .map(|entry| (spelling_distance(name, &entry), entry))
+                .filter(|(distance, _)| *distance <= MAX_SPELLING_DISTANCE)
+                .min_by_key(|(distance, _)| *distance)?;
+            corrected.push(best.1);
+        }
+        Some(corrected)
+    }
+}
+
+/// How far `typed` is from `name`, following bash's `cdspell`: 0 when they
+/// match, 1 for two swapped characters, 2 for one character wrong, missing
+/// or extra, and 3 for anything else.
+fn spelling_distance(typed: &str, name: &str) -> usize {
+    let typed: Vec<char> = typed.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let common = typed.iter().zip(&name).take_while(|(a, b)| a == b).count();
+    let (typed_rest, name_rest) = (&typed[common..], &name[common..]);
+
+    match (typed_rest, name_rest) {
+        ([], []) => 0,
+        ([a, b, typed_rest @ ..], [c, d, name_rest @ ..])
+            if a == d && b == c && typed_rest == name_rest =>
+        {
+            1
+        }
+        ([_, typed_rest @ ..], [_, name_rest @ ..]) if typed_rest == name_rest => 2,
+        ([_, typed_rest @ ..], _) if typed_rest == name_rest => 2,
+        (_, [_, name_rest @ ..]) if typed_rest == name_rest => 2,
+        _ => 3,
+    }
+}
+
 impl Command for CdCommand {
     fn execute(
         &self,
         args: &[String],
-        _: &CommandRegistry,
-        _: &mut dyn Write,
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
         if args.is_empty() {
             return Ok(ShellStatus::Continue);
         }
 
-        if args[0] == "~" {
-            let home_path: String = env::var("HOME").unwrap_or_default();
-            env::set_current_dir(&home_path).map_err(|_| ShellError::DirectoryNotFound {
-                path: home_path.clone(),
-            })?;
-            return Ok(ShellStatus::Continue);
-        }
-
+        // A tilde was already expanded with the rest of the word
         let new_dir = &args[0];
-        let root = Path::new(new_dir);
+        let cdpath_match = Self::search_cdpath(new_dir);
+        let spelling_match = if cdpath_match.is_none()
+            && registry.get_option(ShellOption::Cdspell)
+            && !Path::new(new_dir).is_dir()
+        {
+            Self::correct_spelling(new_dir)
+        } else {
+            None
+        };
+        let root = cdpath_match
+            .as_deref()
+            .or(spelling_match.as_deref())
+            .unwrap_or(Path::new(new_dir));
+        change_directory(self.get_name(), root)?;
 
-        env::set_current_dir(root).map_err(|_| ShellError::DirectoryNotFound {
-            path: new_dir.clone(),
-        })?;
+        // Like bash, show where a `$CDPATH` lookup or spelling fix led.
+        if let Some(corrected) = &spelling_match {
+            writeln!(ctx.output, "{}", corrected.display())?;
+        } else if cdpath_match.is_some()
+            && let Ok(cwd) = env::current_dir()
+        {
+            writeln!(ctx.output, "{}", cwd.display())?;
+        }
 
         Ok(ShellStatus::Continue)
     }
@@ -39,4 +163,68 @@ impl Command for CdCommand {
     fn get_name(&self) -> &str {
         "cd"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "cd [dir]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Change the shell working directory.\n\n\
+         Change the current directory to DIR. A leading `~` is expanded\n\
+         to the home directory. Relative directories are also searched for\n\
+         in each entry of $CDPATH. With `shopt -s cdspell', small typos in\n\
+         directory names are corrected. PWD and OLDPWD are exported afterwards."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
+
+pub struct MkcdCommand;
+
+impl Command for MkcdCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        _: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let dir = match args {
+            [dir] => dir.clone(),
+            [] => {
+                return Err(ShellError::MissingOperand {
+                    command: self.get_name().to_string(),
+                });
+            }
+            [_, extra, ..] => {
+                return Err(ShellError::ExtraOperand {
+                    command: self.get_name().to_string(),
+                    operand: extra.clone(),
+                });
+            }
+        };
+
+        fs::create_dir_all(&dir).map_err(|source| ShellError::CreateDirectory {
+            command: self.get_name().to_string(),
+            path: dir.clone(),
+            source,
+        })?;
+        change_directory(self.get_name(), Path::new(&dir))?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "mkcd"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "mkcd dir"
+    }
+
+    fn get_help(&self) -> &str {
+        "Create a directory and change into it.\n\n\
+         Missing parent directories are created too, and an existing DIR\n\
+         is simply entered. PWD and OLDPWD are exported afterwards."
+    }
 }
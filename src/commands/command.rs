@@ -1,12 +1,69 @@
-use std::io::Write;
+use std::fs::File;
+use std::io::{self, Read, Write};
 
 use crate::commands::CommandRegistry;
 use crate::error::ShellResult;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ShellStatus {
     Continue,
-    Exit,
+    /// The shell should exit with this status.
+    Exit(i32),
+}
+
+/// The streams a builtin reads from and writes to: the shell's own standard
+/// streams, or whatever redirects and pipes the executor set up instead.
+pub struct IoContext<'a> {
+    pub input: &'a mut dyn Read,
+    pub output: &'a mut dyn Write,
+    pub error: &'a mut dyn Write,
+    /// The files behind the streams, given by the executor to builtins that
+    /// run other commands. `None` when the builtin is called directly.
+    pub files: Option<StdioFiles>,
+}
+
+/// Files for a builtin's stdin, stdout and stderr, which the commands it
+/// runs are handed in place of the shell's own.
+#[derive(Debug)]
+pub struct StdioFiles {
+    pub input: File,
+    pub output: File,
+    pub error: File,
+}
+
+impl StdioFiles {
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            input: self.input.try_clone()?,
+            output: self.output.try_clone()?,
+            error: self.error.try_clone()?,
+        })
+    }
+}
+
+impl<'a> IoContext<'a> {
+    pub fn new(
+        input: &'a mut dyn Read,
+        output: &'a mut dyn Write,
+        error: &'a mut dyn Write,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            error,
+            files: None,
+        }
+    }
+}
+
+/// A builtin's description of itself, for tools that document or inspect
+/// the builtin set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandInfo {
+    pub name: String,
+    pub synopsis: String,
+    /// Whether POSIX specifies the command, as opposed to a bash extension.
+    pub posix: bool,
 }
 
 pub trait Command {
@@ -14,10 +71,38 @@ pub trait Command {
         &self,
         args: &[String],
         registry: &CommandRegistry,
-        output: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus>;
     fn get_name(&self) -> &str;
+    /// One-line usage summary shown by `help -s`.
+    fn get_synopsis(&self) -> &str;
+    /// Longer description shown by `help` below the synopsis.
+    fn get_help(&self) -> &str {
+        ""
+    }
     fn get_type(&self) -> &str {
         "shell builtin"
     }
+    /// Whether POSIX specifies this command; bash extensions leave this false.
+    fn is_posix(&self) -> bool {
+        false
+    }
+    /// Whether the command needs `$?` as the previous command left it,
+    /// instead of reset to 0 before it runs.
+    fn keeps_status(&self) -> bool {
+        false
+    }
+    /// Whether the command runs other commands, and so needs its streams
+    /// as files in `IoContext::files` to hand on to them.
+    fn runs_commands(&self) -> bool {
+        false
+    }
+    /// The name, synopsis and POSIX status together, for introspection.
+    fn describe(&self) -> CommandInfo {
+        CommandInfo {
+            name: self.get_name().to_string(),
+            synopsis: self.get_synopsis().to_string(),
+            posix: self.is_posix(),
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use std::env;
+use std::io::Write;
+use std::path::Path;
+
+use super::cd::change_directory;
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::prompt::abbreviate_home;
+
+/// Prints the working directory followed by the saved directories, on one
+/// line with `$HOME` shown as `~`, as `dirs`, `pushd` and `popd` do.
+fn print_stack(registry: &CommandRegistry, output: &mut dyn Write) -> ShellResult<()> {
+    let mut entries = vec![abbreviate_home(&env::current_dir()?.display().to_string())];
+    entries.extend(
+        registry
+            .get_dir_stack()
+            .iter()
+            .map(|dir| abbreviate_home(&dir.display().to_string())),
+    );
+    writeln!(output, "{}", entries.join(" "))?;
+    Ok(())
+}
+
+pub struct PushdCommand;
+
+impl Command for PushdCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let mut stack = registry.get_dir_stack();
+        let old_dir = env::current_dir()?;
+
+        match args.first() {
+            Some(dir) => {
+                change_directory(self.get_name(), Path::new(dir))?;
+                stack.insert(0, old_dir);
+            }
+            // With no DIR, swap the working directory with the top entry
+            None => {
+                let top = stack.first_mut().ok_or(ShellError::NoOtherDirectory)?;
+                change_directory(self.get_name(), top)?;
+                *top = old_dir;
+            }
+        }
+
+        registry.set_dir_stack(stack);
+        print_stack(registry, ctx.output)?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "pushd"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "pushd [dir]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Add a directory to the directory stack.\n\n\
+         Save the current directory on the stack and change to DIR. With no\n\
+         DIR, exchange the current directory with the top of the stack.\n\
+         The stack is printed afterwards, as by `dirs'."
+    }
+}
+
+pub struct PopdCommand;
+
+impl Command for PopdCommand {
+    fn execute(
+        &self,
+        _: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let mut stack = registry.get_dir_stack();
+        if stack.is_empty() {
+            return Err(ShellError::DirectoryStackEmpty {
+                command: self.get_name().to_string(),
+            });
+        }
+
+        let top = stack.remove(0);
+        change_directory(self.get_name(), &top)?;
+
+        registry.set_dir_stack(stack);
+        print_stack(registry, ctx.output)?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "popd"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "popd"
+    }
+
+    fn get_help(&self) -> &str {
+        "Remove the top directory from the directory stack and change to it.\n\n\
+         The stack is printed afterwards, as by `dirs'."
+    }
+}
+
+pub struct DirsCommand;
+
+impl Command for DirsCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.first().is_some_and(|arg| arg == "-c") {
+            registry.set_dir_stack(Vec::new());
+            return Ok(ShellStatus::Continue);
+        }
+
+        print_stack(registry, ctx.output)?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "dirs"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "dirs [-c]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Display the directory stack.\n\n\
+         Print the current directory followed by the directories saved by\n\
+         `pushd', most recent first.\n\n\
+         -c\tclear the directory stack"
+    }
+}
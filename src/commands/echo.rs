@@ -1,6 +1,4 @@
-use std::io::Write;
-
-use super::{Command, CommandRegistry, ShellStatus};
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
 use crate::error::ShellResult;
 
 pub struct EchoCommand;
@@ -10,13 +8,27 @@ impl Command for EchoCommand {
         &self,
         args: &[String],
         _: &CommandRegistry,
-        output: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
-        writeln!(output, "{}", args.join(" "))?;
+        writeln!(ctx.output, "{}", args.join(" "))?;
         Ok(ShellStatus::Continue)
     }
 
     fn get_name(&self) -> &str {
         "echo"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "echo [arg ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Write arguments to the standard output.\n\n\
+         Display the ARGs, separated by a single space character and\n\
+         followed by a newline."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
 }
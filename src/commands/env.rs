@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Command as ProcessCommand, Stdio};
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::exit_code;
+use crate::parser::parse_assignment;
+
+pub struct EnvCommand;
+
+impl EnvCommand {
+    fn print_environment(
+        assignments: &[(String, String)],
+        output: &mut dyn Write,
+    ) -> ShellResult<()> {
+        let mut vars: BTreeMap<String, String> = env::vars().collect();
+        vars.extend(assignments.iter().cloned());
+
+        for (name, value) in vars {
+            writeln!(output, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+
+    /// Runs `command` on the builtin's own streams and sets `$?` to its
+    /// exit status.
+    fn run_command(
+        assignments: &[(String, String)],
+        command: &str,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<()> {
+        let Some(full_path) = registry.get_executable_path(command) else {
+            return Err(ShellError::CommandNotFound(command.to_string()));
+        };
+
+        let mut process = ProcessCommand::new(&full_path);
+        process
+            .arg0(command)
+            .args(args)
+            .envs(assignments.iter().cloned());
+        // Called outside the executor there are no files, so output is
+        // copied to the writer instead
+        match ctx.files.take() {
+            Some(files) => process
+                .stdin(files.input)
+                .stdout(files.output)
+                .stderr(files.error),
+            None => process.stdout(Stdio::piped()),
+        };
+
+        let mut child = process.spawn().map_err(|e| ShellError::ProcessStart {
+            command: command.to_string(),
+            source: e,
+        })?;
+
+        if let Some(mut stdout) = child.stdout.take() {
+            io::copy(&mut stdout, ctx.output)?;
+        }
+        let status = child.wait()?;
+        registry.set_last_status(exit_code(status));
+        Ok(())
+    }
+}
+
+impl Command for EnvCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let split = args
+            .iter()
+            .position(|arg| parse_assignment(arg).is_none())
+            .unwrap_or(args.len());
+        let assignments: Vec<(String, String)> = args[..split]
+            .iter()
+            .filter_map(|arg| parse_assignment(arg))
+            .collect();
+
+        match args[split..].split_first() {
+            Some((command, args)) => Self::run_command(&assignments, command, args, registry, ctx)?,
+            None => Self::print_environment(&assignments, ctx.output)?,
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "env"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "env [name=value ...] [command [arg ...]]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Run a command in a modified environment.\n\n\
+         Set each NAME to VALUE and run COMMAND with those variables added\n\
+         to its environment. With no COMMAND, print the resulting\n\
+         environment as sorted NAME=VALUE lines."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+}
@@ -1,40 +1,584 @@
+use std::cell::{Cell, RefCell};
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::{self, Cursor, PipeReader, PipeWriter, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd, RawFd};
 use std::os::unix::process::CommandExt;
+use std::path::Path;
 use std::process::{Child, Command as ProcessCommand, Stdio};
+use std::thread::{self, JoinHandle};
 
-use super::{CommandRegistry, ShellStatus};
-use crate::error::ShellResult;
-use crate::files::open_file;
-use crate::parser::ParsedCommand;
+use super::set::quote_value;
+use super::trap::EXIT_TRAP;
+use super::{CommandRegistry, IoContext, ShellStatus, StdioFiles};
+use crate::audit::AuditLog;
+use crate::error::{ShellError, ShellResult};
+use crate::files::{DiskFileSystem, FileSystem, MemoryFile, OutputFile, open_input_file};
+use crate::jobs::{JobInfo, JobState, WaitOutcome, exit_code, wait_foreground};
+use crate::options::ShellOption;
+use crate::parser::{
+    Connector, ParsedCommand, Pipeline, Redirect, RedirectMode, VariableLookup, parse_input,
+    parse_simple_command,
+};
+use crate::terminal;
+
+/// Alias consulted, with the original argv, when a command is not found.
+const COMMAND_NOT_FOUND_HANDLER: &str = "command_not_found_handle";
+/// The variable holding commands to run before each prompt.
+const PROMPT_COMMAND: &str = "PROMPT_COMMAND";
 
 enum PipeState {
     None,
-    Process(Child),
+    Process(Child, Option<PipeReader>),
     Buffer(Vec<u8>),
+    /// Output of the pending `Feeder`, which runs once the command reading
+    /// it has started.
+    Feed,
+}
+
+/// A builtin whose output streams into the external command after it. The
+/// shell runs builtins itself, one at a time, so it holds the builtin back
+/// until the rest of the pipeline has started and can keep up with it.
+struct Feeder {
+    cmd: ParsedCommand,
+    /// What the builtin reads from, as `handle_builtin` takes it.
+    input: PipeState,
+    /// The reading command's stdin, once it has been spawned.
+    stream: Option<File>,
+}
+
+/// A thread collecting everything written to a pipe.
+type PipeReading = JoinHandle<io::Result<Vec<u8>>>;
+
+/// The files a builtin that runs other commands hands on to them, with the
+/// threads standing in for streams that have no file of their own.
+struct Relay {
+    files: StdioFiles,
+    /// Copies piped input into the pipe behind `files.input`.
+    input_copy: Option<JoinHandle<io::Result<u64>>>,
+    /// Collect what is written to the pipes behind `files.output` and
+    /// `files.error`.
+    output_read: Option<PipeReading>,
+    error_read: Option<PipeReading>,
+}
+
+impl Relay {
+    /// Closes the files and returns what came through the output and error
+    /// pipes, which is empty for a stream that had a file.
+    fn finish(self) -> io::Result<(Vec<u8>, Vec<u8>)> {
+        drop(self.files);
+        // Input a command left unread is dropped, as a pipe would drop it
+        if let Some(copy) = self.input_copy {
+            let _ = copy.join();
+        }
+
+        let collect = |reading: Option<PipeReading>| {
+            reading.map_or(Ok(Vec::new()), |reading| {
+                reading.join().expect("relay read panicked")
+            })
+        };
+        Ok((collect(self.output_read)?, collect(self.error_read)?))
+    }
+}
+
+/// A file for a builtin's output `target`. A target without one gets a
+/// pipe, with a thread collecting what is written to it.
+fn relay_output(
+    target: &OutputTarget,
+    stream: Option<&File>,
+) -> io::Result<(File, Option<PipeReading>)> {
+    let file = match (target, stream) {
+        (OutputTarget::Pipe, Some(stream)) => Some(stream.try_clone()?),
+        _ => target.to_file()?,
+    };
+    if let Some(file) = file {
+        return Ok((file, None));
+    }
+
+    let (mut reader, writer) = io::pipe()?;
+    let reading = thread::spawn(move || {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map(|_| data)
+    });
+    Ok((File::from(OwnedFd::from(writer)), Some(reading)))
+}
+
+/// Where one of a command's output descriptors points once redirects are applied.
+enum OutputTarget {
+    Stdout,
+    Stderr,
+    File(File),
+    /// A file of an in-memory `FileSystem`.
+    Memory(MemoryFile),
+    Pipe,
+    /// Collected by `run_capture` instead of reaching the terminal.
+    Capture,
+}
+
+impl OutputTarget {
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(match self {
+            OutputTarget::Stdout => OutputTarget::Stdout,
+            OutputTarget::Stderr => OutputTarget::Stderr,
+            OutputTarget::File(file) => OutputTarget::File(file.try_clone()?),
+            OutputTarget::Memory(file) => OutputTarget::Memory(file.clone()),
+            OutputTarget::Pipe => OutputTarget::Pipe,
+            OutputTarget::Capture => OutputTarget::Capture,
+        })
+    }
+
+    /// A file for this target, so a descriptor other than stdout and stderr
+    /// can point at it. Pipes, captures and memory files have none to share.
+    fn to_file(&self) -> io::Result<Option<File>> {
+        Ok(match self {
+            OutputTarget::Stdout => Some(File::from(io::stdout().as_fd().try_clone_to_owned()?)),
+            OutputTarget::Stderr => Some(File::from(io::stderr().as_fd().try_clone_to_owned()?)),
+            OutputTarget::File(file) => Some(file.try_clone()?),
+            OutputTarget::Memory(_) | OutputTarget::Pipe | OutputTarget::Capture => None,
+        })
+    }
+
+    /// Opens this target for a builtin. Pipe output goes to `stream` when
+    /// the next stage is already running to read it; otherwise it collects
+    /// in `buffer`, as capture output does, for the executor to pass on
+    /// afterwards.
+    fn writer<'a>(
+        &self,
+        buffer: &'a mut Vec<u8>,
+        stream: Option<&File>,
+    ) -> io::Result<Box<dyn Write + 'a>> {
+        Ok(match (self, stream) {
+            (OutputTarget::Stdout, _) => Box::new(io::stdout()),
+            (OutputTarget::Stderr, _) => Box::new(io::stderr()),
+            (OutputTarget::File(file), _) => Box::new(file.try_clone()?),
+            (OutputTarget::Memory(file), _) => Box::new(file.clone()),
+            (OutputTarget::Pipe, Some(stream)) => Box::new(stream.try_clone()?),
+            (OutputTarget::Pipe | OutputTarget::Capture, _) => Box::new(buffer),
+        })
+    }
+
+    /// Opens this target for a child process. A memory file cannot be handed
+    /// to a child, so it gets a pipe whose contents a thread copies into the
+    /// file; the thread is pushed onto `copies` to be joined later.
+    fn to_stdio(
+        &self,
+        pipe: Option<&PipeWriter>,
+        copies: &RefCell<Vec<JoinHandle<io::Result<u64>>>>,
+    ) -> io::Result<Stdio> {
+        Ok(match self {
+            OutputTarget::Stdout => Stdio::from(io::stdout()),
+            OutputTarget::Stderr => Stdio::from(io::stderr()),
+            OutputTarget::File(file) => Stdio::from(file.try_clone()?),
+            OutputTarget::Memory(file) => {
+                let (mut reader, writer) = io::pipe()?;
+                let mut file = file.clone();
+                copies
+                    .borrow_mut()
+                    .push(thread::spawn(move || io::copy(&mut reader, &mut file)));
+                Stdio::from(writer)
+            }
+            OutputTarget::Pipe | OutputTarget::Capture => match pipe {
+                Some(writer) => Stdio::from(writer.try_clone()?),
+                None => Stdio::null(),
+            },
+        })
+    }
 }
 
-fn setup_file_redirect(
-    redirect: &Option<std::path::PathBuf>,
-    append: bool,
-) -> ShellResult<Option<File>> {
-    if let Some(path) = redirect {
-        Ok(Some(open_file(path, append)?))
-    } else {
-        Ok(None)
+/// The descriptors a command runs with after its redirects are applied in order.
+struct Redirections {
+    stdin: Option<File>,
+    stdout: OutputTarget,
+    stderr: OutputTarget,
+    /// Files for any other descriptor, as in `3> log`. Only child
+    /// processes see these; builtins just have the files opened.
+    others: Vec<(i32, File)>,
+}
+
+impl Redirections {
+    fn resolve(
+        redirects: &[Redirect],
+        is_last: bool,
+        capture: bool,
+        stdio: Option<&StdioFiles>,
+        noclobber: bool,
+        file_system: &dyn FileSystem,
+    ) -> ShellResult<Self> {
+        let mut resolved = Redirections {
+            stdin: None,
+            stdout: match (is_last, capture, stdio) {
+                (false, _, _) => OutputTarget::Pipe,
+                (true, true, _) => OutputTarget::Capture,
+                (true, false, Some(files)) => OutputTarget::File(files.output.try_clone()?),
+                (true, false, None) => OutputTarget::Stdout,
+            },
+            stderr: match stdio {
+                Some(files) => OutputTarget::File(files.error.try_clone()?),
+                None => OutputTarget::Stderr,
+            },
+            others: Vec::new(),
+        };
+
+        for redirect in redirects {
+            match redirect {
+                Redirect::File {
+                    fd: 0,
+                    path,
+                    mode: RedirectMode::Read,
+                } => {
+                    resolved.stdin = Some(open_input_file(path)?);
+                }
+                Redirect::File {
+                    fd,
+                    path,
+                    mode: RedirectMode::Read,
+                } => {
+                    resolved.set_other(*fd, open_input_file(path)?);
+                }
+                Redirect::File { fd, path, mode } => {
+                    let append = *mode == RedirectMode::Append;
+                    // `>|` overwrites regardless of noclobber
+                    let noclobber = noclobber && *mode != RedirectMode::Clobber;
+                    let target = match file_system.open_file(path, append, noclobber)? {
+                        OutputFile::Disk(file) => OutputTarget::File(file),
+                        OutputFile::Memory(file) => OutputTarget::Memory(file),
+                    };
+                    resolved.set_output(*fd, target)?;
+                }
+                Redirect::Duplicate { fd, target } => {
+                    if let Some(target) = resolved.get_output(*target)? {
+                        resolved.set_output(*fd, target)?;
+                    }
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// A copy of where `fd` currently points, if it is an output.
+    fn get_output(&self, fd: i32) -> io::Result<Option<OutputTarget>> {
+        match fd {
+            1 => self.stdout.try_clone().map(Some),
+            2 => self.stderr.try_clone().map(Some),
+            _ => self
+                .others
+                .iter()
+                .find(|(other, _)| *other == fd)
+                .map(|(_, file)| file.try_clone().map(OutputTarget::File))
+                .transpose(),
+        }
+    }
+
+    fn set_output(&mut self, fd: i32, target: OutputTarget) -> io::Result<()> {
+        match fd {
+            1 => self.stdout = target,
+            2 => self.stderr = target,
+            _ => {
+                if let Some(file) = target.to_file()? {
+                    self.set_other(fd, file);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_other(&mut self, fd: i32, file: File) {
+        self.others.retain(|(other, _)| *other != fd);
+        self.others.push((fd, file));
+    }
+
+    fn uses_pipe(&self) -> bool {
+        matches!(self.stdout, OutputTarget::Pipe) || matches!(self.stderr, OutputTarget::Pipe)
+    }
+
+    fn uses_capture(&self) -> bool {
+        matches!(self.stdout, OutputTarget::Capture) || matches!(self.stderr, OutputTarget::Capture)
     }
 }
 
+/// Reconstructs a command's text for display in the jobs table.
+fn command_line(cmd: &ParsedCommand) -> String {
+    let mut line = cmd.command.clone();
+    for arg in &cmd.args {
+        line.push(' ');
+        line.push_str(arg);
+    }
+    line
+}
+
 pub struct ShellExecutor<'a> {
     registry: &'a CommandRegistry,
+    audit_log: Option<AuditLog>,
+    file_system: Box<dyn FileSystem>,
+    /// Threads copying external commands' output into memory files.
+    copies: RefCell<Vec<JoinHandle<io::Result<u64>>>>,
+    /// Output collected by `run_capture`; `None` when output goes to stdout.
+    capture: RefCell<Option<Vec<u8>>>,
+    /// Where `set -x` traces commands; stderr unless a test replaces it.
+    trace: RefCell<Box<dyn Write>>,
+    /// Whether external commands get their own process group, which owns
+    /// the terminal while it runs in the foreground.
+    job_control: bool,
+    /// The process group of the pipeline being run, once it has one.
+    pipeline_group: Cell<Option<libc::pid_t>>,
+    /// A builtin waiting to stream its output into the next stage.
+    feeder: RefCell<Option<Feeder>>,
+    /// What commands read and write by default, when not the shell's own
+    /// standard streams.
+    stdio: Option<StdioFiles>,
 }
 
 impl<'a> ShellExecutor<'a> {
     pub fn new(registry: &'a CommandRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            audit_log: None,
+            file_system: Box::new(DiskFileSystem),
+            copies: RefCell::new(Vec::new()),
+            capture: RefCell::new(None),
+            trace: RefCell::new(Box::new(io::stderr())),
+            job_control: false,
+            pipeline_group: Cell::new(None),
+            feeder: RefCell::new(None),
+            stdio: None,
+        }
+    }
+
+    /// Runs commands against `stdio`, if given, instead of the shell's own
+    /// standard streams, as a builtin running commands passes on its own.
+    pub fn with_stdio(mut self, stdio: Option<StdioFiles>) -> Self {
+        self.stdio = stdio;
+        self
+    }
+
+    /// Runs each pipeline in its own process group and hands it the
+    /// terminal while it is in the foreground, as an interactive shell does,
+    /// so Ctrl-C and Ctrl-Z reach the pipeline and not the shell.
+    pub fn with_job_control(mut self, job_control: bool) -> Self {
+        self.job_control = job_control;
+        self
+    }
+
+    /// Records the expanded argv of every command run to `audit_log`.
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Writes `set -x` traces to `output` instead of stderr.
+    pub fn with_trace_output(mut self, output: impl Write + 'static) -> Self {
+        self.trace = RefCell::new(Box::new(output));
+        self
+    }
+
+    /// Opens output redirect targets through `file_system` instead of the disk.
+    pub fn with_file_system(mut self, file_system: impl FileSystem + 'static) -> Self {
+        self.file_system = Box::new(file_system);
+        self
+    }
+
+    /// The `$?` value left by the last command run: its exit code, or 128
+    /// plus the signal number if a signal killed it.
+    pub fn last_status(&self) -> i32 {
+        self.registry.get_last_status()
+    }
+
+    /// Runs each pipeline in order, skipping those whose `&&`/`||` connector
+    /// is not satisfied by the last status. Errors from all but the last
+    /// pipeline are reported immediately so later pipelines still run,
+    /// unless `set -e` makes a failure exit the shell.
+    pub fn run(&self, pipelines: &[Pipeline]) -> ShellResult<ShellStatus> {
+        let mut iter = pipelines.iter().peekable();
+
+        while let Some(pipeline) = iter.next() {
+            let succeeded = self.registry.get_last_status() == 0;
+            let should_run = match pipeline.connector {
+                Connector::Always => true,
+                Connector::And => succeeded,
+                Connector::Or => !succeeded,
+            };
+            if !should_run {
+                continue;
+            }
+
+            let result = self.run_and_record(pipeline);
+
+            // `set -e` ignores a failure that `&&` or `||` goes on to test
+            let status = self.registry.get_last_status();
+            let tested = iter
+                .peek()
+                .is_some_and(|next| next.connector != Connector::Always);
+            if status != 0
+                && !tested
+                && self.registry.get_option(ShellOption::Errexit)
+                && !matches!(result, Ok(ShellStatus::Exit(_)))
+            {
+                if let Err(e) = result {
+                    eprintln!("{}", e);
+                }
+                return Ok(ShellStatus::Exit(status));
+            }
+
+            if iter.peek().is_none() {
+                return result;
+            }
+
+            match result {
+                Ok(status @ ShellStatus::Exit(_)) => return Ok(status),
+                Ok(ShellStatus::Continue) => {}
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    /// Runs `$PROMPT_COMMAND`, if set, before the shell shows its prompt.
+    /// Errors are only reported, an `exit` inside it does not end the
+    /// shell, and `$?` keeps the status of the last command the user ran.
+    pub fn run_prompt_command(&self) {
+        let Some(command) = self
+            .registry
+            .get_variable(PROMPT_COMMAND)
+            .filter(|command| !command.trim().is_empty())
+        else {
+            return;
+        };
+
+        let status = self.registry.get_last_status();
+        let result = parse_input(&command).and_then(|pipelines| self.run(&pipelines));
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+        self.registry.set_last_status(status);
+    }
+
+    /// Runs the `EXIT` trap, if any, and returns the status the shell should
+    /// exit with: `pending`, unless the trap itself calls `exit`.
+    pub fn shutdown(&self, pending: i32) -> i32 {
+        let Some(action) = self.registry.get_trap(EXIT_TRAP) else {
+            return pending;
+        };
+        // Reset first so an `exit` inside the trap cannot run it again
+        self.registry.set_trap(EXIT_TRAP, None);
+
+        let result = parse_input(&action).and_then(|pipelines| self.run(&pipelines));
+        match result {
+            Ok(ShellStatus::Exit(code)) => code,
+            Ok(ShellStatus::Continue) => pending,
+            Err(e) => {
+                eprintln!("{}", e);
+                pending
+            }
+        }
+    }
+
+    /// Runs `pipelines` like `run`, but collects what their last stages write
+    /// to stdout instead of printing it, as command substitution needs.
+    pub fn run_capture(&self, pipelines: &[Pipeline]) -> ShellResult<(ShellStatus, String)> {
+        let outer = self.capture.replace(Some(Vec::new()));
+        let result = self.run(pipelines);
+        let captured = self.capture.replace(outer).unwrap_or_default();
+
+        result.map(|status| (status, String::from_utf8_lossy(&captured).into_owned()))
+    }
+
+    fn is_capturing(&self) -> bool {
+        self.capture.borrow().is_some()
+    }
+
+    fn append_capture(&self, data: &[u8]) {
+        if let Some(captured) = self.capture.borrow_mut().as_mut() {
+            captured.extend_from_slice(data);
+        }
+    }
+
+    /// Replaces a command word that names an alias with the alias's words.
+    /// Expansion is not recursive: the replacement is never looked up again.
+    fn expand_alias(&self, cmd: &ParsedCommand) -> ParsedCommand {
+        let Some(value) = self.registry.get_alias(&cmd.command) else {
+            return cmd.clone();
+        };
+
+        let Ok(mut expanded) = parse_simple_command(&value, self) else {
+            return cmd.clone();
+        };
+        if expanded.command.is_empty() && expanded.env_assignments.is_empty() {
+            return cmd.clone();
+        }
+
+        expanded.args.extend(cmd.args.iter().cloned());
+        expanded.redirects.extend(cmd.redirects.iter().cloned());
+        let mut env_assignments = cmd.env_assignments.clone();
+        env_assignments.append(&mut expanded.env_assignments);
+        expanded.env_assignments = env_assignments;
+        expanded.background = cmd.background;
+        expanded.heredoc = cmd.heredoc.clone().or(expanded.heredoc);
+        expanded
+    }
+
+    /// Expands `pipeline` now that the pipelines before it have run, then
+    /// traces, audits and runs it. A failure sets `$?`.
+    fn run_and_record(&self, pipeline: &Pipeline) -> ShellResult<ShellStatus> {
+        let pipeline: Vec<ParsedCommand> = match pipeline.expand(self) {
+            Ok(commands) => commands.iter().map(|cmd| self.expand_alias(cmd)).collect(),
+            Err(e) => {
+                self.registry.set_last_status(e.exit_code());
+                return Err(e);
+            }
+        };
+
+        if self.registry.get_option(ShellOption::Xtrace) {
+            let mut trace = self.trace.borrow_mut();
+            for cmd in pipeline.iter().filter(|cmd| !cmd.command.is_empty()) {
+                let mut line = format!("+ {}", quote_value(&cmd.command));
+                for arg in &cmd.args {
+                    line.push(' ');
+                    line.push_str(&quote_value(arg));
+                }
+                writeln!(trace, "{}", line)?;
+            }
+        }
+
+        if let Some(audit_log) = &self.audit_log {
+            for cmd in &pipeline {
+                let mut argv = vec![cmd.command.clone()];
+                argv.extend(cmd.args.iter().cloned());
+                if let Err(e) = audit_log.record(&argv) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+
+        let result = self.run_pipeline(&pipeline);
+        let copies = std::mem::take(&mut *self.copies.borrow_mut());
+
+        match &result {
+            Ok(_) => {
+                for copy in copies {
+                    copy.join().expect("memory file copy panicked")?;
+                }
+            }
+            // A stage may still be running; leave its copy to finish alone
+            Err(e) => self.registry.set_last_status(e.exit_code()),
+        }
+
+        result
+    }
+
+    fn run_pipeline(&self, pipeline: &[ParsedCommand]) -> ShellResult<ShellStatus> {
+        self.pipeline_group.set(None);
+        let result = self.run_stages(pipeline);
+        // Only left behind when a later stage failed to start
+        self.feeder.take();
+        if self.pipeline_group.take().is_some() {
+            // Not being able to take the terminal back is no reason to fail
+            let _ = terminal::reclaim_terminal();
+        }
+        result
     }
 
-    pub fn run(&self, pipeline: &[ParsedCommand]) -> ShellResult<ShellStatus> {
+    fn run_stages(&self, pipeline: &[ParsedCommand]) -> ShellResult<ShellStatus> {
         if pipeline.is_empty() {
             return Ok(ShellStatus::Continue);
         }
@@ -45,145 +589,567 @@ impl<'a> ShellExecutor<'a> {
         while let Some(cmd) = iter.next() {
             let is_last = iter.peek().is_none();
 
-            let is_builtin = self.registry.get_builtin(&cmd.command).is_some();
+            // `x=1`, `> file` or both: the redirects are opened, then dropped
+            if cmd.command.is_empty() {
+                Redirections::resolve(
+                    &cmd.redirects,
+                    is_last,
+                    false,
+                    None,
+                    self.registry.get_option(ShellOption::Noclobber),
+                    &*self.file_system,
+                )?;
+                self.assign_variables(&cmd.env_assignments, cmd.substitution_status);
+                previous_output = PipeState::None;
+                continue;
+            }
 
-            let (new_state, status) = if is_builtin {
-                self.handle_builtin(cmd, &mut previous_output, is_last)?
+            let handled;
+            let cmd = match self.not_found_handler(cmd) {
+                Some(handler) => {
+                    handled = handler;
+                    &handled
+                }
+                None => cmd,
+            };
+
+            // A here-document replaces whatever the previous stage would feed in
+            if let Some(body) = &cmd.heredoc {
+                let upstream = std::mem::replace(
+                    &mut previous_output,
+                    PipeState::Buffer(body.clone().into_bytes()),
+                );
+                if let PipeState::Process(mut child, reader) = upstream {
+                    drop(reader);
+                    child.wait()?;
+                }
+            }
+
+            let (new_state, status) = if self.runs_as_builtin(cmd) {
+                if let Some(next) = iter.peek()
+                    && self.streams_into(next)
+                {
+                    let input = std::mem::replace(&mut previous_output, PipeState::Feed);
+                    *self.feeder.borrow_mut() = Some(Feeder {
+                        cmd: cmd.clone(),
+                        input,
+                        stream: None,
+                    });
+                    continue;
+                }
+                self.run_feeder();
+                self.handle_builtin(cmd, &mut previous_output, is_last, None)?
             } else {
                 self.handle_external(cmd, &mut previous_output, is_last)?
             };
 
-            if let ShellStatus::Exit = status {
-                return Ok(ShellStatus::Exit);
+            if let ShellStatus::Exit(_) = status {
+                return Ok(status);
             }
 
             previous_output = new_state;
         }
 
-        if let PipeState::Process(mut child) = previous_output {
-            child.wait()?;
+        self.run_feeder();
+        if let PipeState::Process(mut child, _) = previous_output {
+            let status = child.wait()?;
+            self.registry.set_last_status(exit_code(status));
         }
 
         Ok(ShellStatus::Continue)
     }
 
+    /// Records the group of a just-spawned `child`, which leads it when it
+    /// is the pipeline's first process, and gives a foreground pipeline the
+    /// terminal.
+    fn join_pipeline_group(&self, child: &Child, background: bool) {
+        let pid = child.id() as libc::pid_t;
+        let group = self.pipeline_group.get().unwrap_or(pid);
+
+        // The child sets its group too; doing it here as well means it is in
+        // place before the terminal is handed over. It fails harmlessly once
+        // the child has exec'd.
+        // SAFETY: setpgid takes no pointers.
+        unsafe {
+            libc::setpgid(pid, group);
+        }
+
+        if self.pipeline_group.replace(Some(group)).is_none() && !background {
+            let _ = terminal::set_foreground_group(group);
+        }
+    }
+
+    /// Whether a builtin's output can stream into `next` as it runs: `next`
+    /// must be an external command that reads its stdin from the pipe.
+    fn streams_into(&self, next: &ParsedCommand) -> bool {
+        let reads_pipe = next.heredoc.is_none()
+            && !next.redirects.iter().any(|redirect| {
+                matches!(
+                    redirect,
+                    Redirect::File {
+                        fd: 0,
+                        mode: RedirectMode::Read,
+                        ..
+                    }
+                )
+            });
+        reads_pipe
+            && !self.runs_as_builtin(next)
+            && self.registry.get_executable_path(&next.command).is_some()
+    }
+
+    /// Runs the pending feeder builtin, if any, streaming its output into
+    /// the command that was started to read it. That command exiting early
+    /// just ends the builtin's output. The rest of the pipeline is already
+    /// running, so an error is reported here rather than ending it, and as
+    /// if in its own process, the builtin cannot exit the shell.
+    fn run_feeder(&self) {
+        let Some(mut feeder) = self.feeder.take() else {
+            return;
+        };
+        if let Err(e) = self.handle_builtin(&feeder.cmd, &mut feeder.input, false, feeder.stream) {
+            self.registry.set_last_status(e.exit_code());
+            eprintln!("{}", e);
+        }
+    }
+
+    /// Whether `cmd` runs as a builtin. The shell cannot fork a copy of
+    /// itself, so a backgrounded command uses the executable on `PATH`
+    /// when there is one, leaving the builtin for the foreground.
+    fn runs_as_builtin(&self, cmd: &ParsedCommand) -> bool {
+        self.registry.get_builtin(&cmd.command).is_some()
+            && !(cmd.background && self.registry.get_executable_path(&cmd.command).is_some())
+    }
+
+    /// Rewrites a command that is neither a builtin nor on `PATH` into a call
+    /// to the `command_not_found_handle` alias, passing the original argv as
+    /// its arguments. Returns `None` when the command exists or no handler is
+    /// defined, so the usual "command not found" error is reported.
+    fn not_found_handler(&self, cmd: &ParsedCommand) -> Option<ParsedCommand> {
+        if self.registry.get_builtin(&cmd.command).is_some()
+            || self.registry.get_executable_path(&cmd.command).is_some()
+            || self.registry.get_alias(COMMAND_NOT_FOUND_HANDLER).is_none()
+        {
+            return None;
+        }
+
+        let mut args = vec![cmd.command.clone()];
+        args.extend(cmd.args.iter().cloned());
+        let handler = ParsedCommand {
+            command: COMMAND_NOT_FOUND_HANDLER.to_string(),
+            args,
+            ..cmd.clone()
+        };
+        Some(self.expand_alias(&handler))
+    }
+
+    /// Applies a bare `NAME=value` command to the shell's own environment.
+    /// The status is that of the last command substitution, or 0 if none.
+    fn assign_variables(&self, assignments: &[(String, String)], substitution_status: Option<i32>) {
+        for (name, value) in assignments {
+            // SAFETY: the shell is single-threaded, so nothing reads the
+            // environment concurrently.
+            unsafe { std::env::set_var(name, value) };
+        }
+        self.registry
+            .set_last_status(substitution_status.unwrap_or(0));
+    }
+
+    /// Runs a builtin stage. Its piped output is buffered for the next
+    /// stage, unless `stream` is that stage's already-running input.
     fn handle_builtin(
         &self,
         cmd: &ParsedCommand,
-        _input: &mut PipeState,
+        input: &mut PipeState,
         is_last: bool,
+        stream: Option<File>,
     ) -> ShellResult<(PipeState, ShellStatus)> {
         let builtin = self
             .registry
             .get_builtin(&cmd.command)
             .expect("handle_builtin called but builtin not found - this is a bug");
 
-        let mut output_buffer = Vec::new();
-        let mut writer: Box<dyn Write> = if let Some(file) =
-            setup_file_redirect(&cmd.stdout_redirect, cmd.stdout_redirect_append)?
-        {
-            Box::new(file)
-        } else if !is_last {
-            Box::new(&mut output_buffer)
+        let redirections = Redirections::resolve(
+            &cmd.redirects,
+            is_last,
+            self.is_capturing(),
+            self.stdio.as_ref(),
+            self.registry.get_option(ShellOption::Noclobber),
+            &*self.file_system,
+        )?;
+
+        // A previous stage feeds the builtin the way it would feed a process:
+        // buffered builtin output is replayed, and a process's pipe is read
+        // directly. The process itself is reaped once the builtin is done.
+        let (mut piped, mut upstream): (Option<Box<dyn Read + Send>>, Option<Child>) =
+            match std::mem::replace(input, PipeState::None) {
+                PipeState::None | PipeState::Feed => (None, None),
+                PipeState::Buffer(data) => (Some(Box::new(Cursor::new(data))), None),
+                PipeState::Process(child, pipe) => {
+                    let reader: Box<dyn Read + Send> = match pipe {
+                        Some(pipe) => Box::new(pipe),
+                        None => Box::new(io::empty()),
+                    };
+                    (Some(reader), Some(child))
+                }
+            };
+
+        // A builtin that runs other commands uses files it can hand on
+        let relay = if builtin.runs_commands() {
+            Some(self.start_relay(&redirections, piped.take(), stream.as_ref())?)
         } else {
-            Box::new(io::stdout())
+            None
         };
 
-        let _stderr_file = setup_file_redirect(&cmd.stderr_redirect, cmd.stderr_redirect_append)?;
+        let mut reader: Box<dyn Read> = match (&relay, &redirections.stdin, piped) {
+            (Some(relay), _, _) => Box::new(relay.files.input.try_clone()?),
+            (None, Some(file), _) => Box::new(file.try_clone()?),
+            (None, None, Some(piped)) => piped,
+            (None, None, None) => match &self.stdio {
+                Some(files) => Box::new(files.input.try_clone()?),
+                None => Box::new(io::stdin()),
+            },
+        };
 
-        let result = builtin.execute(&cmd.args, self.registry, &mut *writer);
+        // Unless streamed, pipe and capture output collect in buffers first.
+        // Anything the builtin writes to a piped stderr follows its regular
+        // output.
+        let mut output_buffer = Vec::new();
+        let mut error_buffer = Vec::new();
+        let (mut writer, mut error_writer): (Box<dyn Write>, Box<dyn Write>) = match &relay {
+            Some(relay) => (
+                Box::new(relay.files.output.try_clone()?),
+                Box::new(relay.files.error.try_clone()?),
+            ),
+            None => (
+                redirections
+                    .stdout
+                    .writer(&mut output_buffer, stream.as_ref())?,
+                redirections
+                    .stderr
+                    .writer(&mut error_buffer, stream.as_ref())?,
+            ),
+        };
+
+        // Builtins succeed unless they report another status themselves.
+        if !builtin.keeps_status() {
+            self.registry.set_last_status(0);
+        }
+        let mut ctx = IoContext::new(&mut *reader, &mut *writer, &mut *error_writer);
+        if let Some(relay) = &relay {
+            ctx.files = Some(relay.files.try_clone()?);
+        }
+        let result = builtin.execute(&cmd.args, self.registry, &mut ctx);
+        drop(ctx);
 
+        // Closing our end first lets a writer the builtin left unread exit.
+        drop(reader);
         drop(writer);
+        drop(error_writer);
+        let relayed = relay.map(Relay::finish).transpose()?;
+        if let Some(child) = upstream.as_mut() {
+            child.wait()?;
+        }
 
-        match result {
-            Ok(status) => {
-                if !is_last && cmd.stdout_redirect.is_none() {
-                    Ok((PipeState::Buffer(output_buffer), status))
-                } else {
-                    Ok((PipeState::None, status))
-                }
+        if let Some((output, error)) = relayed {
+            redirections
+                .stdout
+                .writer(&mut output_buffer, stream.as_ref())?
+                .write_all(&output)?;
+            redirections
+                .stderr
+                .writer(&mut error_buffer, stream.as_ref())?
+                .write_all(&error)?;
+        }
+        output_buffer.append(&mut error_buffer);
+
+        let status = match result {
+            Ok(status) => status,
+            // The reader went away, which ends a streaming builtin quietly
+            Err(ShellError::Io(e)) if stream.is_some() && e.kind() == io::ErrorKind::BrokenPipe => {
+                ShellStatus::Continue
             }
             Err(e) => {
-                if let Some(mut file) = setup_file_redirect(&cmd.stderr_redirect, true)? {
-                    writeln!(file, "{}", e)?;
-                    Ok((PipeState::None, ShellStatus::Continue))
-                } else {
-                    Err(e)
+                match (&redirections.stderr, &stream) {
+                    (OutputTarget::Stderr, _) => return Err(e),
+                    (OutputTarget::Stdout, _) => writeln!(io::stdout(), "{}", e)?,
+                    (OutputTarget::File(file), _) => writeln!(&*file, "{}", e)?,
+                    (OutputTarget::Memory(file), _) => writeln!(file.clone(), "{}", e)?,
+                    (OutputTarget::Pipe, Some(stream)) => {
+                        let _ = writeln!(&*stream, "{}", e);
+                    }
+                    (OutputTarget::Pipe | OutputTarget::Capture, _) => {
+                        writeln!(output_buffer, "{}", e)?
+                    }
                 }
+                self.registry.set_last_status(e.exit_code());
+                ShellStatus::Continue
             }
+        };
+
+        if redirections.uses_capture() {
+            self.append_capture(&output_buffer);
+            Ok((PipeState::None, status))
+        } else if redirections.uses_pipe() && stream.is_none() {
+            Ok((PipeState::Buffer(output_buffer), status))
+        } else {
+            Ok((PipeState::None, status))
         }
     }
 
+    /// Gives a builtin that runs other commands files for its streams.
+    /// Piped input is copied into a pipe on a thread, and output without a
+    /// file of its own is collected from one.
+    fn start_relay(
+        &self,
+        redirections: &Redirections,
+        piped: Option<Box<dyn Read + Send>>,
+        stream: Option<&File>,
+    ) -> io::Result<Relay> {
+        let (input, input_copy) = match (&redirections.stdin, piped) {
+            (Some(file), _) => (file.try_clone()?, None),
+            (None, Some(mut piped)) => {
+                let (reader, mut writer) = io::pipe()?;
+                let copy = thread::spawn(move || io::copy(&mut piped, &mut writer));
+                (File::from(OwnedFd::from(reader)), Some(copy))
+            }
+            (None, None) => match &self.stdio {
+                Some(files) => (files.input.try_clone()?, None),
+                None => (File::from(io::stdin().as_fd().try_clone_to_owned()?), None),
+            },
+        };
+        let (output, output_read) = relay_output(&redirections.stdout, stream)?;
+        let (error, error_read) = relay_output(&redirections.stderr, stream)?;
+
+        Ok(Relay {
+            files: StdioFiles {
+                input,
+                output,
+                error,
+            },
+            input_copy,
+            output_read,
+            error_read,
+        })
+    }
+
     fn handle_external(
         &self,
         cmd: &ParsedCommand,
         input: &mut PipeState,
         is_last: bool,
     ) -> ShellResult<(PipeState, ShellStatus)> {
-        let Some(full_path) = self.registry.get_executable_path(&cmd.command) else {
-            return Err(crate::error::ShellError::CommandNotFound(
-                cmd.command.clone(),
-            ));
-        };
-
-        let stdin = match input {
-            PipeState::Process(child) => {
-                if let Some(out) = child.stdout.take() {
-                    Stdio::from(out)
-                } else {
-                    Stdio::null()
-                }
+        // A path that exists but cannot run is left for `spawn` to explain
+        let full_path = match self.registry.get_executable_path(&cmd.command) {
+            Some(full_path) => full_path,
+            None if cmd.command.contains('/') && Path::new(&cmd.command).exists() => {
+                cmd.command.clone()
             }
-            PipeState::Buffer(_) => Stdio::piped(),
-            PipeState::None => Stdio::inherit(),
+            None => return Err(ShellError::CommandNotFound(cmd.command.clone())),
         };
 
-        let (stdout, creates_pipe) = if let Some(file) =
-            setup_file_redirect(&cmd.stdout_redirect, cmd.stdout_redirect_append)?
-        {
-            (Stdio::from(file), false)
-        } else if !is_last {
-            (Stdio::piped(), true)
+        let redirections = Redirections::resolve(
+            &cmd.redirects,
+            is_last,
+            self.is_capturing(),
+            self.stdio.as_ref(),
+            self.registry.get_option(ShellOption::Noclobber),
+            &*self.file_system,
+        )?;
+
+        let stdin = if let Some(file) = redirections.stdin.as_ref() {
+            Stdio::from(file.try_clone()?)
         } else {
-            (Stdio::inherit(), false)
+            match input {
+                PipeState::Process(_, reader) => match reader.take() {
+                    Some(reader) => Stdio::from(reader),
+                    None => Stdio::null(),
+                },
+                PipeState::Buffer(_) | PipeState::Feed => Stdio::piped(),
+                PipeState::None => match &self.stdio {
+                    Some(files) => Stdio::from(files.input.try_clone()?),
+                    None => Stdio::inherit(),
+                },
+            }
         };
 
-        let stderr = if let Some(file) =
-            setup_file_redirect(&cmd.stderr_redirect, cmd.stderr_redirect_append)?
+        let (pipe_reader, pipe_writer) = if redirections.uses_pipe() || redirections.uses_capture()
         {
-            Stdio::from(file)
+            let (reader, writer) = io::pipe()?;
+            (Some(reader), Some(writer))
         } else {
-            Stdio::inherit()
+            (None, None)
         };
 
+        let stdout = redirections
+            .stdout
+            .to_stdio(pipe_writer.as_ref(), &self.copies)?;
+        let stderr = redirections
+            .stderr
+            .to_stdio(pipe_writer.as_ref(), &self.copies)?;
+
         let mut command_builder = ProcessCommand::new(&full_path);
 
+        // Descriptors beyond stderr are moved into place in the child. Each
+        // file is first copied above every target so that no `dup2` closes
+        // a file another redirect still needs.
+        let mut others: Vec<(RawFd, i32)> = redirections
+            .others
+            .iter()
+            .map(|(fd, file)| (file.as_raw_fd(), *fd))
+            .collect();
+        let floor = others.iter().map(|&(_, fd)| fd + 1).max().unwrap_or(0);
+
+        // SAFETY: `signal`, `fcntl` and `dup2` are async-signal-safe, so they
+        // may run between fork and exec, and the files behind `others` stay
+        // open until the child is spawned. The shell ignores SIGTSTP; restore
+        // it so Ctrl-Z stops the child rather than being inherited as ignored
+        // across exec, and likewise the terminal signals.
+        unsafe {
+            command_builder.pre_exec(move || {
+                for signal in [libc::SIGTSTP, libc::SIGTTIN, libc::SIGTTOU] {
+                    libc::signal(signal, libc::SIG_DFL);
+                }
+                for (source, _) in others.iter_mut() {
+                    *source = libc::fcntl(*source, libc::F_DUPFD_CLOEXEC, floor);
+                    if *source == -1 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                for &(source, fd) in &others {
+                    if libc::dup2(source, fd) == -1 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+
+        // Command substitutions stay in the shell's group, as in bash
+        let job_control = self.job_control && !self.is_capturing();
+        if job_control {
+            command_builder.process_group(self.pipeline_group.get().unwrap_or(0));
+        }
+
         command_builder
             .arg0(&cmd.command)
             .args(&cmd.args)
+            .envs(cmd.env_assignments.iter().cloned())
             .stdin(stdin)
             .stdout(stdout)
             .stderr(stderr);
 
-        let mut child =
-            command_builder
-                .spawn()
-                .map_err(|e| crate::error::ShellError::ProcessStart {
-                    command: cmd.command.clone(),
-                    source: e,
-                })?;
+        let mut child = command_builder.spawn().map_err(|e| {
+            let command = cmd.command.clone();
+            match e.kind() {
+                // exec reports a directory as EACCES too
+                _ if Path::new(&full_path).is_dir() => ShellError::IsADirectory(command),
+                io::ErrorKind::PermissionDenied => ShellError::PermissionDenied(command),
+                _ => ShellError::ProcessStart { command, source: e },
+            }
+        })?;
+
+        // Close our copies of the pipe's write end so the reader sees EOF.
+        drop(command_builder);
+        drop(pipe_writer);
 
+        if job_control {
+            self.join_pipeline_group(&child, cmd.background);
+        }
+
+        // A child that exits without reading its input is not an error
         if let PipeState::Buffer(data) = input
             && let Some(mut stdin) = child.stdin.take()
+            && let Err(e) = stdin.write_all(data)
+            && e.kind() != io::ErrorKind::BrokenPipe
+        {
+            return Err(e.into());
+        }
+        if let PipeState::Feed = input
+            && let Some(feeder) = self.feeder.borrow_mut().as_mut()
         {
-            stdin.write_all(data)?;
+            feeder.stream = child
+                .stdin
+                .take()
+                .map(|stdin| File::from(OwnedFd::from(stdin)));
         }
 
-        if creates_pipe {
-            Ok((PipeState::Process(child), ShellStatus::Continue))
+        if redirections.uses_capture()
+            && let Some(mut reader) = pipe_reader
+        {
+            // Read on a thread so a feeder can write while output comes back
+            let reading = thread::spawn(move || {
+                let mut captured = Vec::new();
+                reader.read_to_end(&mut captured).map(|_| captured)
+            });
+            self.run_feeder();
+            let captured = reading.join().expect("capture read panicked")?;
+            self.append_capture(&captured);
+
+            let status = child.wait()?;
+            self.registry.set_last_status(exit_code(status));
+            Ok((PipeState::None, ShellStatus::Continue))
+        } else if pipe_reader.is_some() {
+            Ok((
+                PipeState::Process(child, pipe_reader),
+                ShellStatus::Continue,
+            ))
+        } else if cmd.background {
+            self.run_feeder();
+            let pid = child.id();
+            let job_id = self.registry.add_job(child, &command_line(cmd));
+            // The job outlives this pipeline, so its copies finish on their own
+            self.copies.borrow_mut().clear();
+            writeln!(io::stdout(), "[{}] {}", job_id, pid)?;
+            self.registry.set_last_status(0);
+            Ok((PipeState::None, ShellStatus::Continue))
         } else {
-            child.wait()?;
+            self.run_feeder();
+            let outcome = wait_foreground(&child)?;
+            self.registry.set_last_status(outcome.exit_code());
+            if let WaitOutcome::Stopped(_) = outcome {
+                let pid = child.id();
+                let command = command_line(cmd);
+                let id = self.registry.add_stopped_job(child, &command);
+                let job = JobInfo {
+                    id,
+                    pid,
+                    command,
+                    state: JobState::Stopped,
+                    marker: '+',
+                };
+                writeln!(io::stdout(), "\n{}", job)?;
+            }
             Ok((PipeState::None, ShellStatus::Continue))
         }
     }
 }
+
+/// Resolves `$NAME` through the registry and runs `$(...)` with this executor,
+/// so parsing with an executor supports command substitution.
+impl VariableLookup for ShellExecutor<'_> {
+    fn get_variable(&self, name: &str) -> Option<String> {
+        self.registry.get_variable(name)
+    }
+
+    fn get_positional_args(&self) -> Vec<String> {
+        self.registry.get_positional_args()
+    }
+
+    fn get_option(&self, option: ShellOption) -> bool {
+        self.registry.get_option(option)
+    }
+
+    fn substitute_command(&self, command: &str) -> Option<(String, i32)> {
+        let result = parse_input(command).and_then(|pipelines| self.run_capture(&pipelines));
+        let output = match result {
+            Ok((_, output)) => output,
+            Err(e) => {
+                self.registry.set_last_status(e.exit_code());
+                eprintln!("{}", e);
+                String::new()
+            }
+        };
+        Some((output, self.registry.get_last_status()))
+    }
+}
@@ -1,24 +1,58 @@
-use std::io::Write;
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
 
-use super::{Command, CommandRegistry, ShellStatus};
-use crate::error::ShellResult;
+/// Status used when `exit` is given something other than a number.
+const EXIT_BAD_ARGUMENT: i32 = 2;
 
 pub struct ExitCommand;
 
 impl Command for ExitCommand {
     fn execute(
         &self,
-        _: &[String],
+        args: &[String],
         registry: &CommandRegistry,
-        _: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
+        // Like bash, a bad argument is reported but the shell still exits
+        let code = match args.first().map(|arg| (arg, arg.parse::<i32>())) {
+            Some((_, Ok(code))) => code.rem_euclid(256),
+            Some((arg, Err(_))) => {
+                let error = ShellError::NumericArgRequired {
+                    command: self.get_name().to_string(),
+                    arg: arg.clone(),
+                };
+                writeln!(ctx.error, "{}", error)?;
+                EXIT_BAD_ARGUMENT
+            }
+            None => registry.get_last_status(),
+        };
+        registry.set_last_status(code);
+
         if let Some(histfile) = CommandRegistry::get_histfile_path() {
             let _ = registry.write_history_to_file(&histfile, false, false);
         }
-        Ok(ShellStatus::Exit)
+        Ok(ShellStatus::Exit(code))
     }
 
     fn get_name(&self) -> &str {
         "exit"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "exit [n]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Exit the shell.\n\n\
+         Exits with status N, or with the status of the last command.\n\
+         Saves the history list to $HISTFILE, if set, before exiting."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+
+    fn keeps_status(&self) -> bool {
+        true
+    }
 }
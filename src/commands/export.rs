@@ -0,0 +1,108 @@
+use std::env;
+use std::io::Write;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::parser::is_valid_name;
+
+pub struct ExportCommand;
+
+impl ExportCommand {
+    /// Lists the environment, along with names exported while unset, which
+    /// have no value to show.
+    fn list_exports(registry: &CommandRegistry, output: &mut dyn Write) -> ShellResult<()> {
+        let mut vars: Vec<(String, Option<String>)> = env::vars()
+            .map(|(name, value)| (name, Some(value)))
+            .chain(
+                registry
+                    .get_exported_unset()
+                    .into_iter()
+                    .map(|name| (name, None)),
+            )
+            .collect();
+        vars.sort();
+
+        for (name, value) in vars {
+            match value {
+                Some(value) => {
+                    writeln!(output, "declare -x {}=\"{}\"", name, escape_value(&value))?
+                }
+                None => writeln!(output, "declare -x {}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes a value for display inside double quotes.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl Command for ExportCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.iter().all(|arg| arg == "-p") {
+            Self::list_exports(registry, ctx.output)?;
+            return Ok(ShellStatus::Continue);
+        }
+
+        for arg in args {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg.as_str(), None),
+            };
+
+            if !is_valid_name(name) {
+                return Err(ShellError::InvalidIdentifier {
+                    command: self.get_name().to_string(),
+                    name: arg.clone(),
+                });
+            }
+
+            // Every variable in the process environment is already exported,
+            // so a bare NAME only needs remembering while it is unset.
+            match value {
+                Some(value) => {
+                    // SAFETY: the shell is single-threaded, so nothing reads
+                    // the environment concurrently.
+                    unsafe { env::set_var(name, value) };
+                }
+                None if env::var_os(name).is_none() => registry.set_exported_unset(name, true),
+                None => {}
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "export"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "export [-p] [name[=value] ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Set export attribute for shell variables.\n\n\
+         Each NAME is exported to the environment of subsequently executed\n\
+         commands. If VALUE is supplied, assign VALUE before exporting.\n\
+         With no arguments or with -p, print all exported variables."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Command, CommandRegistry, IoContext, ShellExecutor, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::parser::parse_input;
+
+pub struct FcCommand;
+
+impl FcCommand {
+    /// Re-executes a history entry, as `fc -s [old=new] [prefix]` does.
+    ///
+    /// When the newest entry is the `fc` invocation itself, it is skipped
+    /// when searching and then replaced by the command that actually runs.
+    /// The command runs against `fc`'s own streams.
+    fn reexecute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let (substitutions, prefix): (Vec<&String>, Vec<&String>) =
+            args.iter().partition(|arg| arg.contains('='));
+
+        let history = registry.get_history();
+        let invoked_from_history = history
+            .last()
+            .is_some_and(|entry| entry.split_whitespace().next() == Some(self.get_name()));
+        let searchable = if invoked_from_history {
+            &history[..history.len() - 1]
+        } else {
+            &history[..]
+        };
+
+        let mut command = searchable
+            .iter()
+            .rev()
+            .find(|entry| {
+                prefix
+                    .first()
+                    .is_none_or(|prefix| entry.starts_with(prefix.as_str()))
+            })
+            .cloned()
+            .ok_or(ShellError::HistoryEventNotFound {
+                command: self.get_name().to_string(),
+            })?;
+
+        for substitution in substitutions {
+            if let Some((old, new)) = substitution.split_once('=') {
+                command = command.replace(old, new);
+            }
+        }
+
+        if invoked_from_history {
+            registry.replace_last_history_entry(&command);
+        }
+
+        writeln!(ctx.output, "{}", command)?;
+        ctx.output.flush()?;
+
+        let executor = ShellExecutor::new(registry).with_stdio(ctx.files.take());
+        executor.run(&parse_input(&command)?)
+    }
+}
+
+impl Command for FcCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        match args {
+            [flag, rest @ ..] if flag == "-s" => self.reexecute(rest, registry, ctx),
+            [flag, editor, rest @ ..] if flag == "-e" && editor == "-" => {
+                self.reexecute(rest, registry, ctx)
+            }
+            _ => Err(ShellError::FcUsage),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "fc"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "fc -s [pat=rep] [command] or fc -e - [pat=rep] [command]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Re-execute a command from the history list.\n\n\
+         Runs the most recent command, or the most recent one starting\n\
+         with COMMAND, after replacing every PAT with REP. The command is\n\
+         printed before it runs and takes the place of the fc invocation\n\
+         in the history list."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+}
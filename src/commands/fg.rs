@@ -0,0 +1,119 @@
+use super::kill::send;
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::{JobInfo, JobState, WaitOutcome, signal_target};
+use crate::terminal;
+
+/// Finds the job named by the spec given to `fg` or `bg`: `%n`, `%%` or
+/// `%+`, or the current job when there is none.
+fn resolve_job(command: &str, args: &[String], registry: &CommandRegistry) -> ShellResult<JobInfo> {
+    let id = match args
+        .first()
+        .map(|spec| spec.strip_prefix('%').unwrap_or(spec))
+    {
+        None | Some("%" | "+") => registry.current_job_id(),
+        Some(number) => number.parse().ok(),
+    };
+
+    id.and_then(|id| registry.find_job(id))
+        .ok_or_else(|| ShellError::NoSuchJob {
+            command: command.to_string(),
+            spec: args.first().map_or("current", String::as_str).to_string(),
+        })
+}
+
+pub struct FgCommand;
+
+impl Command for FgCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let job = resolve_job(self.get_name(), args, registry)?;
+        writeln!(ctx.output, "{}", job.command)?;
+
+        // A job in its own process group takes the terminal while it runs,
+        // as it would have if started in the foreground
+        let target = signal_target(job.pid);
+        if target < 0 {
+            let _ = terminal::set_foreground_group(-target);
+        }
+        let outcome = send(target, libc::SIGCONT)
+            .map_err(ShellError::from)
+            .and_then(|()| {
+                registry.set_job_state(job.id, JobState::Running);
+                registry.wait_for_job_in_foreground(job.id)
+            });
+        if target < 0 {
+            let _ = terminal::reclaim_terminal();
+        }
+
+        if let Some(outcome) = outcome? {
+            registry.set_last_status(outcome.exit_code());
+            if let WaitOutcome::Stopped(_) = outcome
+                && let Some(job) = registry.find_job(job.id)
+            {
+                writeln!(ctx.output, "\n{}", job)?;
+            }
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "fg"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "fg [job_spec]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Move a job to the foreground.\n\n\
+         Resumes the job identified by JOB_SPEC, or the current job, and\n\
+         waits for it as the foreground command. The exit status is that\n\
+         of the job."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
+
+pub struct BgCommand;
+
+impl Command for BgCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let job = resolve_job(self.get_name(), args, registry)?;
+
+        send(signal_target(job.pid), libc::SIGCONT)?;
+        registry.set_job_state(job.id, JobState::Running);
+
+        writeln!(ctx.output, "[{}]{} {} &", job.id, job.marker, job.command)?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "bg"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "bg [job_spec]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Move a job to the background.\n\n\
+         Resumes the stopped job identified by JOB_SPEC, or the current\n\
+         job, as if it had been started with `&'."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,64 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+pub struct HashCommand;
+
+impl Command for HashCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let rebuild = args.first().is_some_and(|arg| arg == "-r");
+        let names = if rebuild { &args[1..] } else { args };
+
+        if rebuild {
+            registry.rehash();
+        }
+
+        if names.is_empty() {
+            if !rebuild {
+                for (name, path) in registry.get_hashed_executables() {
+                    writeln!(ctx.output, "{}\t{}", name, path)?;
+                }
+            }
+            return Ok(ShellStatus::Continue);
+        }
+
+        let mut missing = None;
+        for name in names {
+            if registry.get_builtin(name).is_some() {
+                continue;
+            }
+            if registry.hash_executable(name).is_none() {
+                missing.get_or_insert_with(|| name.clone());
+            }
+        }
+
+        match missing {
+            Some(name) => Err(ShellError::HashNotFound(name)),
+            None => Ok(ShellStatus::Continue),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "hash"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "hash [-r] [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Remember or display program locations.\n\n\
+         Looks up each NAME on $PATH and remembers where it was found, so\n\
+         later runs skip the search. Without arguments, lists every\n\
+         remembered location.\n\n\
+         -r\trescan $PATH, forgetting all remembered locations"
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::Write;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+const HELP_TEXT_INDENT: &str = "    ";
+
+pub struct HelpCommand;
+
+impl Command for HelpCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let (short, names) = match args.first().map(|s| s.as_str()) {
+            Some("-s") => (true, &args[1..]),
+            _ => (false, args),
+        };
+
+        if names.is_empty() {
+            return self.list_builtins(registry, ctx.output);
+        }
+
+        for name in names {
+            let command = registry
+                .get_builtin(name)
+                .ok_or_else(|| ShellError::HelpNotFound {
+                    pattern: name.clone(),
+                })?;
+
+            writeln!(ctx.output, "{}: {}", name, command.get_synopsis())?;
+
+            if !short {
+                for line in command.get_help().lines() {
+                    if line.is_empty() {
+                        writeln!(ctx.output)?;
+                    } else {
+                        writeln!(ctx.output, "{}{}", HELP_TEXT_INDENT, line)?;
+                    }
+                }
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "help"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "help [-s] [pattern ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Display information about builtin commands.\n\n\
+         With no PATTERN, list the synopsis of every builtin.\n\n\
+         -s\toutput only a short usage synopsis for each PATTERN"
+    }
+}
+
+impl HelpCommand {
+    fn list_builtins(
+        &self,
+        registry: &CommandRegistry,
+        output: &mut dyn Write,
+    ) -> ShellResult<ShellStatus> {
+        let mut names: Vec<&String> = registry.builtins.keys().collect();
+        names.sort();
+
+        for name in names {
+            if let Some(command) = registry.get_builtin(name) {
+                writeln!(output, "{}", command.get_synopsis())?;
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+}
@@ -1,10 +1,13 @@
 use std::io::Write;
 use std::path::Path;
 
-use super::{Command, CommandRegistry, ShellStatus};
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
 use crate::error::{ShellError, ShellResult};
+use crate::terminal::{terminal_width, truncate};
 
 const HISTORY_LINE_NUMBER_WIDTH: usize = 5;
+/// The line number column plus the two spaces after it.
+const HISTORY_ENTRY_OFFSET: usize = HISTORY_LINE_NUMBER_WIDTH + 2;
 
 pub struct HistoryCommand;
 
@@ -13,9 +16,14 @@ impl Command for HistoryCommand {
         &self,
         args: &[String],
         registry: &CommandRegistry,
-        output: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
         match args.first().map(|s| s.as_str()) {
+            Some("-c") => {
+                registry.clear_history();
+                Ok(ShellStatus::Continue)
+            }
+
             Some("-w") => {
                 let path = args.get(1).ok_or_else(|| ShellError::HistoryArgRequired {
                     flag: "-w".to_string(),
@@ -40,13 +48,44 @@ impl Command for HistoryCommand {
                 Ok(ShellStatus::Continue)
             }
 
-            _ => self.list_history(args, registry, output),
+            _ => {
+                let mut width = None;
+                let mut reverse = false;
+                let mut rest = args;
+                while let Some((flag, tail)) = rest.split_first() {
+                    match flag.as_str() {
+                        "--truncate" => {
+                            width =
+                                terminal_width().map(|w| w.saturating_sub(HISTORY_ENTRY_OFFSET));
+                        }
+                        "--reverse" | "-R" => reverse = true,
+                        _ => break,
+                    }
+                    rest = tail;
+                }
+                self.list_history(rest, registry, width, reverse, ctx.output)
+            }
         }
     }
 
     fn get_name(&self) -> &str {
         "history"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "history [-c] [--truncate] [-R] [n] or history -awr [filename]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Display or manipulate the history list.\n\n\
+         With N, list only the last N entries. With --truncate, entries\n\
+         too long for the terminal are cut short with an ellipsis.\n\n\
+         -c\tclear the history list by deleting all of the entries\n\
+         -R\tlist the newest entries first; also --reverse\n\
+         -a\tappend new history lines to FILENAME\n\
+         -r\tread FILENAME and append it to the history list\n\
+         -w\twrite the current history to FILENAME"
+    }
 }
 
 impl HistoryCommand {
@@ -54,6 +93,8 @@ impl HistoryCommand {
         &self,
         args: &[String],
         registry: &CommandRegistry,
+        width: Option<usize>,
+        reverse: bool,
         output: &mut dyn Write,
     ) -> ShellResult<ShellStatus> {
         let history = registry.get_history();
@@ -68,8 +109,16 @@ impl HistoryCommand {
         };
 
         let start_index = history.len().saturating_sub(limit);
+        let mut entries: Vec<_> = history.iter().enumerate().skip(start_index).collect();
+        if reverse {
+            entries.reverse();
+        }
 
-        for (i, entry) in history.iter().enumerate().skip(start_index) {
+        for (i, entry) in entries {
+            let entry = match width {
+                Some(width) => truncate(entry, width),
+                None => entry.clone(),
+            };
             writeln!(
                 output,
                 "{:>width$}  {}",
@@ -0,0 +1,51 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::ShellResult;
+use crate::json::JsonObject;
+
+pub struct JobsCommand;
+
+impl Command for JobsCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let json = args.first().is_some_and(|arg| arg == "--json");
+
+        for job in registry.list_jobs()? {
+            if json {
+                let object = JsonObject::new()
+                    .number("id", job.id)
+                    .number("pid", job.pid)
+                    .string("state", &job.state.to_string())
+                    .string("command", &job.command)
+                    .string("marker", &job.marker.to_string())
+                    .finish();
+                writeln!(ctx.output, "{}", object)?;
+            } else {
+                writeln!(ctx.output, "{}", job)?;
+            }
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "jobs"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "jobs [--json]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Display status of jobs.\n\n\
+         Lists the active background jobs. Jobs that have finished are\n\
+         reported once as Done and then removed from the table. With\n\
+         --json, each job is printed as a JSON object instead."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,106 @@
+use std::io;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::{JobState, parse_signal, signal_target};
+
+pub struct KillCommand;
+
+impl KillCommand {
+    /// Splits off a leading `-s SIG`, `-n NUM` or `-SIG`, returning the
+    /// signal to send (SIGTERM by default) and the remaining arguments.
+    fn parse_signal_arg(args: &[String]) -> ShellResult<(libc::c_int, &[String])> {
+        let (spec, rest) = match args {
+            [flag, spec, rest @ ..] if flag == "-s" || flag == "-n" => (spec.as_str(), rest),
+            [flag, rest @ ..] if flag == "--" => return Ok((libc::SIGTERM, rest)),
+            [flag, rest @ ..] if flag.len() > 1 && flag.starts_with('-') => (&flag[1..], rest),
+            _ => return Ok((libc::SIGTERM, args)),
+        };
+
+        let signal = parse_signal(spec).ok_or_else(|| ShellError::InvalidSignal {
+            spec: spec.to_string(),
+        })?;
+        Ok((signal, rest))
+    }
+
+    /// Sends `signal` to a `%n` job spec or a process ID. A job in its own
+    /// process group is signalled as a whole, and a stopped job is continued
+    /// so it can act on the signal.
+    fn signal(arg: &str, signal: libc::c_int, registry: &CommandRegistry) -> ShellResult<()> {
+        let failed = |source| ShellError::KillFailed {
+            target: arg.to_string(),
+            source,
+        };
+
+        let Some(spec) = arg.strip_prefix('%') else {
+            let pid = arg.parse().map_err(|_| ShellError::KillInvalidArg {
+                arg: arg.to_string(),
+            })?;
+            return send(pid, signal).map_err(failed);
+        };
+
+        let Some(job) = spec.parse().ok().and_then(|id| registry.find_job(id)) else {
+            return Err(ShellError::NoSuchJob {
+                command: "kill".to_string(),
+                spec: arg.to_string(),
+            });
+        };
+        let target = signal_target(job.pid);
+
+        send(target, signal).map_err(failed)?;
+        if job.state == JobState::Stopped && signal != libc::SIGCONT {
+            send(target, libc::SIGCONT).map_err(failed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends `signal` to `target`, a process ID or a negated process group ID.
+pub(super) fn send(target: libc::pid_t, signal: libc::c_int) -> io::Result<()> {
+    // SAFETY: kill takes no pointers.
+    if unsafe { libc::kill(target, signal) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl Command for KillCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let (signal, targets) = Self::parse_signal_arg(args)?;
+        if targets.is_empty() {
+            return Err(ShellError::MissingOperand {
+                command: self.get_name().to_string(),
+            });
+        }
+
+        for target in targets {
+            Self::signal(target, signal, registry)?;
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "kill"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "kill [-s sigspec | -n signum | -sigspec] pid | jobspec ..."
+    }
+
+    fn get_help(&self) -> &str {
+        "Send a signal to jobs or processes.\n\n\
+         Sends the signal named by SIGSPEC or numbered SIGNUM, or SIGTERM\n\
+         if neither is given, to each process ID or job specification such\n\
+         as %1. SIGSPEC may be given with or without the `SIG' prefix."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -1,13 +1,37 @@
+mod alias;
 mod cd;
 mod command;
+mod dirs;
 mod echo;
+mod env;
 mod executor;
 mod exit;
+mod export;
+mod fc;
+mod fg;
+mod hash;
+mod help;
 mod history;
+mod jobs;
+mod kill;
+mod pathname;
+mod printf;
 mod pwd;
+mod read;
+mod realpath;
 mod registry;
+mod set;
+mod shopt;
+mod sleep;
+mod source;
+mod test;
+mod timeout;
+mod trap;
 mod type_cmd;
+mod unset;
+mod wait;
+mod which;
 
-pub use command::{Command, ShellStatus};
+pub use command::{Command, CommandInfo, IoContext, ShellStatus, StdioFiles};
 pub use executor::ShellExecutor;
 pub use registry::CommandRegistry;
@@ -0,0 +1,126 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+/// The last component of `path`, following POSIX: trailing slashes are
+/// ignored, a path of only slashes is `/`, and `suffix` is removed unless
+/// it is the whole name.
+fn basename<'a>(path: &'a str, suffix: Option<&str>) -> &'a str {
+    if path.is_empty() {
+        return path;
+    }
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return "/";
+    }
+
+    let name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    match suffix {
+        Some(suffix) if name != suffix => name.strip_suffix(suffix).unwrap_or(name),
+        _ => name,
+    }
+}
+
+/// Everything in `path` before its last component, following POSIX: a
+/// path without a slash is `.`, and one directly under the root is `/`.
+fn dirname(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return if path.is_empty() { "." } else { "/" };
+    }
+
+    match trimmed.rfind('/') {
+        Some(slash) => match trimmed[..slash].trim_end_matches('/') {
+            "" => "/",
+            parent => parent,
+        },
+        None => ".",
+    }
+}
+
+pub struct BasenameCommand;
+
+impl Command for BasenameCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        _: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let (path, suffix) = match args {
+            [path] => (path, None),
+            [path, suffix] => (path, Some(suffix.as_str())),
+            [] => {
+                return Err(ShellError::MissingOperand {
+                    command: self.get_name().to_string(),
+                });
+            }
+            [_, _, extra, ..] => {
+                return Err(ShellError::ExtraOperand {
+                    command: self.get_name().to_string(),
+                    operand: extra.clone(),
+                });
+            }
+        };
+
+        writeln!(ctx.output, "{}", basename(path, suffix))?;
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "basename"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "basename string [suffix]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Print STRING with any leading directory components removed.\n\n\
+         Trailing slashes are ignored. If SUFFIX is given and ends the\n\
+         name without being all of it, it is removed as well."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
+
+pub struct DirnameCommand;
+
+impl Command for DirnameCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        _: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.is_empty() {
+            return Err(ShellError::MissingOperand {
+                command: self.get_name().to_string(),
+            });
+        }
+
+        for path in args {
+            writeln!(ctx.output, "{}", dirname(path))?;
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "dirname"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "dirname string ..."
+    }
+
+    fn get_help(&self) -> &str {
+        "Print each STRING with its last component removed.\n\n\
+         A STRING without a slash prints `.', and one directly under the\n\
+         root directory prints `/'."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,282 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+/// Characters that never need quoting in `%q` output.
+const SHELL_SAFE_CHARS: &str = "_@%+=:,./-";
+/// Digits after the point for `%f` without an explicit precision.
+const DEFAULT_FLOAT_PRECISION: usize = 6;
+
+/// Quotes `arg` so the shell reads it back as the same single word.
+///
+/// Printable text is backslash-escaped, as in `a\ b`; text with control
+/// characters uses the `$'...'` form instead.
+fn shell_quote(arg: &str) -> String {
+    if arg.is_empty() {
+        return "''".to_string();
+    }
+
+    if arg.chars().any(|c| c.is_control()) {
+        let mut quoted = String::from("$'");
+        for c in arg.chars() {
+            match c {
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                '\'' => quoted.push_str("\\'"),
+                '\\' => quoted.push_str("\\\\"),
+                c if c.is_control() => quoted.push_str(&format!("\\{:03o}", c as u32)),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('\'');
+        return quoted;
+    }
+
+    let mut quoted = String::with_capacity(arg.len());
+    for c in arg.chars() {
+        if !(c.is_alphanumeric() || SHELL_SAFE_CHARS.contains(c)) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted
+}
+
+/// A `%` conversion with its optional `-` and `0` flags, field width and
+/// precision.
+struct Conversion {
+    left_align: bool,
+    zero_pad: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: char,
+}
+
+impl Conversion {
+    fn pad(&self, text: String) -> String {
+        if self.left_align {
+            format!("{:<width$}", text, width = self.width)
+        } else if self.zero_pad && self.kind != 's' && self.kind != 'c' {
+            // Zeros go between the sign and the digits, as in `-0042`
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", text.as_str()),
+            };
+            let width = self.width.saturating_sub(sign.len());
+            format!("{}{:0>width$}", sign, digits, width = width)
+        } else {
+            format!("{:>width$}", text, width = self.width)
+        }
+    }
+}
+
+/// Unwraps a parsed number, recording the error and using zero in its
+/// place when the argument was not one, as bash does.
+fn or_zero<T: Default>(parsed: ShellResult<T>, errors: &mut Vec<ShellError>) -> T {
+    parsed.unwrap_or_else(|error| {
+        errors.push(error);
+        T::default()
+    })
+}
+
+/// Decodes an octal escape whose first digit has been read: `\0NNN` takes
+/// up to three more digits and `\NNN` up to two.
+fn read_octal(chars: &mut std::iter::Peekable<std::str::Chars>, first: u32) -> char {
+    let mut value = first;
+    let remaining = if first == 0 { 3 } else { 2 };
+    for _ in 0..remaining {
+        match chars.peek().and_then(|c| c.to_digit(8)) {
+            Some(digit) => {
+                value = value * 8 + digit;
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    char::from_u32(value).unwrap_or('\0')
+}
+
+pub struct PrintfCommand;
+
+impl PrintfCommand {
+    /// Expands one pass of `format`, taking arguments from `args`, and
+    /// adds an error to `errors` for each argument that is not a number
+    /// where one was expected. Returns whether any conversion consumed an
+    /// argument.
+    fn format_once(
+        format: &str,
+        args: &mut std::slice::Iter<String>,
+        output: &mut String,
+        errors: &mut Vec<ShellError>,
+    ) -> ShellResult<bool> {
+        let mut consumed = false;
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => output.push('\n'),
+                    Some('t') => output.push('\t'),
+                    Some('r') => output.push('\r'),
+                    Some('a') => output.push('\x07'),
+                    Some('b') => output.push('\x08'),
+                    Some('f') => output.push('\x0c'),
+                    Some('v') => output.push('\x0b'),
+                    Some('\\') => output.push('\\'),
+                    Some('"') => output.push('"'),
+                    Some(digit @ '0'..='7') => {
+                        output.push(read_octal(&mut chars, digit.to_digit(8).unwrap_or(0)))
+                    }
+                    Some(other) => {
+                        output.push('\\');
+                        output.push(other);
+                    }
+                    None => output.push('\\'),
+                },
+                '%' => {
+                    if chars.peek() == Some(&'%') {
+                        chars.next();
+                        output.push('%');
+                        continue;
+                    }
+
+                    let mut left_align = false;
+                    let mut zero_pad = false;
+                    while let Some(flag) = chars.next_if(|&c| c == '-' || c == '0') {
+                        match flag {
+                            '-' => left_align = true,
+                            _ => zero_pad = true,
+                        }
+                    }
+                    let width = Self::read_number(&mut chars);
+                    let precision = chars
+                        .next_if_eq(&'.')
+                        .map(|_| Self::read_number(&mut chars));
+                    let Some(kind) = chars.next() else {
+                        output.push('%');
+                        break;
+                    };
+                    let conversion = Conversion {
+                        left_align,
+                        zero_pad,
+                        width,
+                        precision,
+                        kind,
+                    };
+
+                    let arg = args.next();
+                    consumed |= arg.is_some();
+                    let arg = arg.map(String::as_str).unwrap_or("");
+
+                    let text = match conversion.kind {
+                        's' => match conversion.precision {
+                            Some(precision) => arg.chars().take(precision).collect(),
+                            None => arg.to_string(),
+                        },
+                        'c' => arg.chars().next().map(String::from).unwrap_or_default(),
+                        'q' => shell_quote(arg),
+                        'd' | 'i' => or_zero(Self::parse_integer(arg), errors).to_string(),
+                        'x' => format!("{:x}", or_zero(Self::parse_integer(arg), errors)),
+                        'X' => format!("{:X}", or_zero(Self::parse_integer(arg), errors)),
+                        'o' => format!("{:o}", or_zero(Self::parse_integer(arg), errors)),
+                        'f' => format!(
+                            "{:.precision$}",
+                            or_zero(Self::parse_float(arg), errors),
+                            precision = conversion.precision.unwrap_or(DEFAULT_FLOAT_PRECISION)
+                        ),
+                        other => {
+                            return Err(ShellError::PrintfInvalidFormat {
+                                format: other.to_string(),
+                            });
+                        }
+                    };
+                    output.push_str(&conversion.pad(text));
+                }
+                c => output.push(c),
+            }
+        }
+
+        Ok(consumed)
+    }
+
+    fn read_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> usize {
+        let mut number = 0;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+            number = number * 10 + digit as usize;
+            chars.next();
+        }
+        number
+    }
+
+    fn parse_float(arg: &str) -> ShellResult<f64> {
+        if arg.is_empty() {
+            return Ok(0.0);
+        }
+        arg.trim()
+            .parse()
+            .map_err(|_| ShellError::PrintfInvalidNumber {
+                arg: arg.to_string(),
+            })
+    }
+
+    fn parse_integer(arg: &str) -> ShellResult<i64> {
+        if arg.is_empty() {
+            return Ok(0);
+        }
+        arg.trim()
+            .parse()
+            .map_err(|_| ShellError::PrintfInvalidNumber {
+                arg: arg.to_string(),
+            })
+    }
+}
+
+impl Command for PrintfCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let Some((format, args)) = args.split_first() else {
+            return Err(ShellError::PrintfUsage);
+        };
+
+        let mut text = String::new();
+        let mut errors = Vec::new();
+        let mut args = args.iter();
+        // The format is reused until every argument has been consumed.
+        while Self::format_once(format, &mut args, &mut text, &mut errors)? && args.len() > 0 {}
+
+        // A bad number still prints, as zero, but fails the command
+        ctx.output.write_all(text.as_bytes())?;
+        for error in &errors {
+            writeln!(ctx.error, "{}", error)?;
+        }
+        if let Some(error) = errors.last() {
+            registry.set_last_status(error.exit_code());
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "printf"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "printf format [arguments]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Formats and prints ARGUMENTS under control of the FORMAT.\n\n\
+         Supports the %s, %c, %d, %i, %x, %X, %o, %f, %q and %% conversions,\n\
+         the `-' and `0' flags, a field width and a precision such as %.2f.\n\
+         Backslash escapes like \\n, \\t, \\\\ and \\NNN (octal) are expanded.\n\
+         %q quotes its argument so it can be reused as shell input. The\n\
+         format is reused as needed to consume all of the arguments."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -1,6 +1,4 @@
-use std::io::Write;
-
-use super::{Command, CommandRegistry, ShellStatus};
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
 use crate::error::ShellResult;
 
 pub struct PwdCommand;
@@ -10,14 +8,26 @@ impl Command for PwdCommand {
         &self,
         _: &[String],
         _: &CommandRegistry,
-        output: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
         let current_dir = std::env::current_dir()?;
-        writeln!(output, "{}", current_dir.display())?;
+        writeln!(ctx.output, "{}", current_dir.display())?;
         Ok(ShellStatus::Continue)
     }
 
     fn get_name(&self) -> &str {
         "pwd"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "pwd"
+    }
+
+    fn get_help(&self) -> &str {
+        "Print the name of the current working directory."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
 }
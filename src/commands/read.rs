@@ -0,0 +1,198 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem::ManuallyDrop;
+use std::os::fd::{FromRawFd, RawFd};
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::parser::is_valid_name;
+
+/// Variable assigned when `read` is given no names.
+const DEFAULT_VARIABLE: &str = "REPLY";
+/// `$?` left by `read` when input ends before the delimiter.
+const EXIT_EOF: i32 = 1;
+/// Field separators when `$IFS` is unset.
+const DEFAULT_IFS: &str = " \t\n";
+
+pub struct ReadCommand;
+
+impl ReadCommand {
+    /// Reads one byte at a time up to `delimiter`, so nothing past it is
+    /// consumed. Unless `raw` is set, a backslash escapes the next byte and
+    /// a backslash-newline is dropped. Returns the record and whether the
+    /// delimiter was found before end of input.
+    fn read_record(input: &mut dyn Read, delimiter: u8, raw: bool) -> io::Result<(String, bool)> {
+        let mut record = Vec::new();
+        let mut escaped = false;
+        let mut byte = [0u8; 1];
+
+        loop {
+            if input.read(&mut byte)? == 0 {
+                return Ok((String::from_utf8_lossy(&record).into_owned(), false));
+            }
+            let b = byte[0];
+
+            if escaped {
+                escaped = false;
+                if b != b'\n' {
+                    record.push(b);
+                }
+            } else if b == delimiter {
+                return Ok((String::from_utf8_lossy(&record).into_owned(), true));
+            } else if b == b'\\' && !raw {
+                escaped = true;
+            } else {
+                record.push(b);
+            }
+        }
+    }
+
+    /// Splits `record` on the characters of `ifs`, one field per name; the
+    /// last name gets the rest of the record. Runs of `ifs` whitespace
+    /// count as one separator and are trimmed from both ends, while every
+    /// other `ifs` character ends a field on its own, as in bash.
+    fn split_fields(record: &str, count: usize, ifs: &str) -> Vec<String> {
+        let is_separator = |c: char| ifs.contains(c);
+        let is_blank = |c: char| ifs.contains(c) && c.is_whitespace();
+        let mut fields = Vec::with_capacity(count);
+        let mut rest = record.trim_start_matches(is_blank);
+
+        for _ in 1..count {
+            let end = rest.find(is_separator).unwrap_or(rest.len());
+            fields.push(rest[..end].to_string());
+            rest = rest[end..].trim_start_matches(is_blank);
+            if let Some(c) = rest.chars().next().filter(|&c| is_separator(c)) {
+                rest = rest[c.len_utf8()..].trim_start_matches(is_blank);
+            }
+        }
+        fields.push(rest.trim_end_matches(is_blank).to_string());
+
+        fields
+    }
+
+    /// Parses the argument of `-u`, which must name a descriptor the shell
+    /// already has open.
+    fn parse_fd(&self, arg: &str) -> ShellResult<RawFd> {
+        // SAFETY: F_GETFD only queries the descriptor's flags.
+        match arg.parse::<RawFd>() {
+            Ok(fd) if fd >= 0 && unsafe { libc::fcntl(fd, libc::F_GETFD) } != -1 => Ok(fd),
+            _ => Err(ShellError::InvalidFileDescriptor {
+                command: self.get_name().to_string(),
+                fd: arg.to_string(),
+            }),
+        }
+    }
+}
+
+impl Command for ReadCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let mut raw = false;
+        let mut delimiter = b'\n';
+        let mut fd = None;
+        let mut names = args;
+
+        while let Some((flag, rest)) = names.split_first() {
+            match flag.as_str() {
+                "-r" => {
+                    raw = true;
+                    names = rest;
+                }
+                "-d" => {
+                    let Some((value, rest)) = rest.split_first() else {
+                        return Err(ShellError::OptionArgRequired {
+                            command: self.get_name().to_string(),
+                            option: flag.clone(),
+                        });
+                    };
+                    // An empty delimiter means NUL, as with `find -print0`.
+                    delimiter = value.bytes().next().unwrap_or(0);
+                    names = rest;
+                }
+                "-u" => {
+                    let Some((value, rest)) = rest.split_first() else {
+                        return Err(ShellError::OptionArgRequired {
+                            command: self.get_name().to_string(),
+                            option: flag.clone(),
+                        });
+                    };
+                    fd = Some(self.parse_fd(value)?);
+                    names = rest;
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(invalid) = names.iter().find(|name| !is_valid_name(name)) {
+            return Err(ShellError::InvalidIdentifier {
+                command: self.get_name().to_string(),
+                name: invalid.clone(),
+            });
+        }
+
+        let (record, complete) = match fd {
+            Some(fd) => {
+                // SAFETY: `parse_fd` checked that `fd` is open. The file is
+                // never dropped, so the descriptor stays open for later reads.
+                let mut file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+                Self::read_record(&mut *file, delimiter, raw)?
+            }
+            None => Self::read_record(ctx.input, delimiter, raw)?,
+        };
+        let ifs = registry
+            .get_variable("IFS")
+            .unwrap_or_else(|| DEFAULT_IFS.to_string());
+
+        let assignments: Vec<(&str, String)> = if names.is_empty() {
+            vec![(DEFAULT_VARIABLE, record)]
+        } else {
+            names
+                .iter()
+                .map(String::as_str)
+                .zip(Self::split_fields(&record, names.len(), &ifs))
+                .collect()
+        };
+
+        for (name, value) in assignments {
+            // SAFETY: the shell is single-threaded, so nothing reads the
+            // environment concurrently.
+            unsafe { env::set_var(name, value) };
+        }
+
+        if !complete {
+            registry.set_last_status(EXIT_EOF);
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "read"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "read [-r] [-d delim] [-u fd] [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Read a line from the standard input and split it into fields.\n\n\
+         The line is split into words on the characters of IFS. The first\n\
+         word is assigned to the first NAME, the second to the second, and\n\
+         so on, with the remaining words assigned to the last NAME. Without\n\
+         NAMEs, the line is stored in REPLY.\n\n\
+         -d delim\tcontinue until the first character of DELIM is read,\n\
+         \trather than newline; an empty DELIM means NUL\n\
+         -r\tdo not allow backslashes to escape any characters\n\
+         -u fd\tread from file descriptor FD instead of the standard input\n\n\
+         Returns failure if end of input is reached before the delimiter."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,92 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+pub struct RealpathCommand;
+
+impl RealpathCommand {
+    /// Resolves `path` like `fs::canonicalize`, but lets any part of it be
+    /// missing: each prefix that exists has its symlinks resolved, and the
+    /// rest is appended with `.` and `..` handled lexically.
+    fn resolve_missing(path: &Path) -> io::Result<PathBuf> {
+        let mut resolved = if path.is_absolute() {
+            PathBuf::from("/")
+        } else {
+            fs::canonicalize(env::current_dir()?)?
+        };
+
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    if let Ok(real) = fs::canonicalize(&resolved) {
+                        resolved = real;
+                    }
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+impl Command for RealpathCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        _: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let allow_missing = args.first().is_some_and(|arg| arg == "-m");
+        let paths = if allow_missing { &args[1..] } else { args };
+
+        if paths.is_empty() {
+            return Err(ShellError::MissingOperand {
+                command: self.get_name().to_string(),
+            });
+        }
+
+        let mut missing = None;
+        for path in paths {
+            let resolved = if allow_missing {
+                Self::resolve_missing(Path::new(path))
+            } else {
+                fs::canonicalize(path)
+            };
+
+            match resolved {
+                Ok(resolved) => writeln!(ctx.output, "{}", resolved.display())?,
+                Err(_) => {
+                    missing.get_or_insert_with(|| path.clone());
+                }
+            }
+        }
+
+        match missing {
+            Some(path) => Err(ShellError::RealpathNotFound { path }),
+            None => Ok(ShellStatus::Continue),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "realpath"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "realpath [-m] path ..."
+    }
+
+    fn get_help(&self) -> &str {
+        "Print the resolved absolute form of each PATH.\n\n\
+         Symlinks, `.' and `..' are resolved, and every PATH must exist.\n\n\
+         -m\tallow missing components, resolving them lexically"
+    }
+}
@@ -1,34 +1,151 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
 use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::process::Child;
 use std::{env, fs};
 
-use super::Command;
+use super::{Command, CommandInfo};
 use super::{
-    cd::CdCommand, echo::EchoCommand, exit::ExitCommand, history::HistoryCommand, pwd::PwdCommand,
+    alias::{AliasCommand, UnaliasCommand},
+    cd::{CdCommand, MkcdCommand},
+    dirs::{DirsCommand, PopdCommand, PushdCommand},
+    echo::EchoCommand,
+    env::EnvCommand,
+    exit::ExitCommand,
+    export::ExportCommand,
+    fc::FcCommand,
+    fg::{BgCommand, FgCommand},
+    hash::HashCommand,
+    help::HelpCommand,
+    history::HistoryCommand,
+    jobs::JobsCommand,
+    kill::KillCommand,
+    pathname::{BasenameCommand, DirnameCommand},
+    printf::PrintfCommand,
+    pwd::PwdCommand,
+    read::ReadCommand,
+    realpath::RealpathCommand,
+    set::SetCommand,
+    shopt::ShoptCommand,
+    sleep::SleepCommand,
+    source::{DotCommand, SourceCommand},
+    test::{BracketCommand, TestCommand},
+    timeout::TimeoutCommand,
+    trap::TrapCommand,
     type_cmd::TypeCommand,
+    unset::UnsetCommand,
+    wait::WaitCommand,
+    which::WhichCommand,
 };
 use crate::error::ShellResult;
-use crate::files::open_file;
+use crate::files::open_locked_file;
+use crate::jobs::{JobInfo, JobState, JobTable, WaitOutcome, wait_for_untracked_pid};
+use crate::options::{ShellOption, ShellOptions};
+use crate::parser::VariableLookup;
 
 const EXECUTABLE_PERMISSION_BITS: u32 = 0o111;
+/// Colon-separated suffixes, such as `.sh:.py`, that a command name may be
+/// missing when searching `$PATH`. Unset means exact names only.
+pub const PATH_SUFFIXES_VAR: &str = "SHELL_PATH_SUFFIXES";
+const DEFAULT_HISTORY_SIZE: usize = 500;
+
+/// History filtering options parsed from a colon-separated `$HISTCONTROL`.
+#[derive(Debug, Default, Clone, Copy)]
+struct HistoryControl {
+    erase_dups: bool,
+    ignore_dups: bool,
+    ignore_space: bool,
+}
+
+impl HistoryControl {
+    fn from_env() -> Self {
+        let value = env::var("HISTCONTROL").unwrap_or_default();
+        let mut control = Self::default();
+        for option in value.split(':') {
+            match option {
+                "erasedups" => control.erase_dups = true,
+                "ignoredups" => control.ignore_dups = true,
+                "ignorespace" => control.ignore_space = true,
+                "ignoreboth" => {
+                    control.ignore_dups = true;
+                    control.ignore_space = true;
+                }
+                _ => {}
+            }
+        }
+        control
+    }
+}
+
+/// Maximum number of entries kept in memory, from `$HISTSIZE`. Unset or
+/// unparsable values fall back to the default; as in bash, a negative one
+/// means no limit.
+fn history_size() -> usize {
+    match env::var("HISTSIZE").map(|value| value.trim().parse::<i64>()) {
+        Ok(Ok(size)) if size < 0 => usize::MAX,
+        Ok(Ok(size)) => usize::try_from(size).unwrap_or(usize::MAX),
+        _ => DEFAULT_HISTORY_SIZE,
+    }
+}
 
 /// Manages command history with support for loading from and saving to files
 struct HistoryManager {
     entries: RefCell<Vec<String>>,
+    /// Index of the first entry not yet written by `history -a`.
+    append_cursor: Cell<usize>,
 }
 
 impl HistoryManager {
     fn new() -> Self {
         Self {
             entries: RefCell::new(Vec::new()),
+            append_cursor: Cell::new(0),
+        }
+    }
+
+    fn add_entry(&self, cmd: &str, control: HistoryControl) {
+        if control.ignore_space && cmd.starts_with(' ') {
+            return;
+        }
+
+        let mut entries = self.entries.borrow_mut();
+
+        if control.ignore_dups && entries.last().is_some_and(|last| last == cmd) {
+            return;
+        }
+
+        if control.erase_dups {
+            let cursor = self.append_cursor.get();
+            let erased_before_cursor = entries
+                .iter()
+                .take(cursor)
+                .filter(|entry| *entry == cmd)
+                .count();
+            self.append_cursor.set(cursor - erased_before_cursor);
+            entries.retain(|entry| entry != cmd);
+        }
+
+        entries.push(cmd.to_string());
+
+        let excess = entries.len().saturating_sub(history_size());
+        if excess > 0 {
+            entries.drain(..excess);
+            self.append_cursor
+                .set(self.append_cursor.get().saturating_sub(excess));
         }
     }
 
-    fn add_entry(&self, cmd: &str) {
-        self.entries.borrow_mut().push(cmd.to_string());
+    fn clear(&self) {
+        self.entries.borrow_mut().clear();
+        self.append_cursor.set(0);
+    }
+
+    fn replace_last(&self, cmd: &str) {
+        if let Some(last) = self.entries.borrow_mut().last_mut() {
+            *last = cmd.to_string();
+        }
     }
 
     fn get_entries(&self) -> Vec<String> {
@@ -43,30 +160,28 @@ impl HistoryManager {
     }
 
     fn write_to_file(&self, path: &Path, append: bool, is_exit: bool) -> ShellResult<()> {
-        let mut file = open_file(path, append)?;
         let entries = self.entries.borrow();
 
-        let start_index = Self::calculate_start_index(&entries, append, is_exit);
+        let start_index = if append && !is_exit {
+            self.append_cursor.get().min(entries.len())
+        } else {
+            0
+        };
 
+        let mut content = String::new();
         for entry in entries.iter().skip(start_index) {
-            writeln!(file, "{}", entry)?;
+            content.push_str(entry);
+            content.push('\n');
         }
 
-        Ok(())
-    }
+        let mut file = open_locked_file(path, append)?;
+        file.write_all(content.as_bytes())?;
 
-    fn calculate_start_index(entries: &[String], append: bool, is_exit: bool) -> usize {
         if append && !is_exit {
-            entries
-                .iter()
-                .rev()
-                .skip(1)
-                .position(|x| x.starts_with("history -a"))
-                .map(|rev_index| entries.len() - 1 - rev_index)
-                .unwrap_or(0)
-        } else {
-            0
+            self.append_cursor.set(entries.len());
         }
+
+        Ok(())
     }
 }
 
@@ -81,6 +196,34 @@ impl PathScanner {
             .collect()
     }
 
+    /// The suffixes tried after each name, starting with the empty one so
+    /// an exact match always wins within a directory.
+    fn get_suffixes() -> Vec<String> {
+        let mut suffixes = vec![String::new()];
+        if let Ok(value) = env::var(PATH_SUFFIXES_VAR) {
+            suffixes.extend(
+                value
+                    .split(':')
+                    .filter(|suffix| !suffix.is_empty())
+                    .map(|suffix| suffix.to_string()),
+            );
+        }
+        suffixes
+    }
+
+    /// Every path in search order that could hold `command`.
+    fn candidate_paths(command: &str) -> Vec<PathBuf> {
+        let suffixes = Self::get_suffixes();
+        Self::get_path_dirs()
+            .iter()
+            .flat_map(|path_dir| {
+                suffixes.iter().map(move |suffix| {
+                    PathBuf::from(path_dir).join(format!("{}{}", command, suffix))
+                })
+            })
+            .collect()
+    }
+
     fn is_executable(path: &PathBuf) -> bool {
         fs::metadata(path)
             .map(|m| m.permissions().mode() & EXECUTABLE_PERMISSION_BITS != 0)
@@ -88,34 +231,73 @@ impl PathScanner {
     }
 
     fn scan_executables() -> HashMap<String, String> {
-        let executables: Vec<(String, String)> = Self::get_path_dirs()
-            .iter()
-            .filter_map(|path_dir| fs::read_dir(path_dir).ok())
-            .flat_map(|entries| entries.flatten())
-            .filter_map(|entry| {
-                let file_name = entry.file_name().into_string().ok()?;
-                let full_path = entry.path();
-
-                if Self::is_executable(&full_path) {
-                    let path_str = full_path.to_str()?.to_string();
-                    Some((file_name, path_str))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
+        let suffixes = Self::get_suffixes();
         let mut map = HashMap::new();
-        for (name, path) in executables {
-            map.entry(name).or_insert(path);
+
+        for path_dir in Self::get_path_dirs() {
+            let Ok(entries) = fs::read_dir(&path_dir) else {
+                continue;
+            };
+
+            let executables: Vec<(String, String)> = entries
+                .flatten()
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().into_string().ok()?;
+                    let full_path = entry.path();
+
+                    if Self::is_executable(&full_path) {
+                        let path_str = full_path.to_str()?.to_string();
+                        Some((file_name, path_str))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            // A configured suffix also makes a file reachable without it,
+            // after exact names and in suffix order, as `find_executable` does
+            let mut stems: Vec<(usize, String, String)> = executables
+                .iter()
+                .filter_map(|(name, path)| {
+                    suffixes
+                        .iter()
+                        .enumerate()
+                        .skip(1)
+                        .find_map(|(index, suffix)| {
+                            let stem = name.strip_suffix(suffix.as_str())?;
+                            (!stem.is_empty()).then(|| (index, stem.to_string(), path.clone()))
+                        })
+                })
+                .collect();
+            stems.sort();
+
+            let stems = stems.into_iter().map(|(_, stem, path)| (stem, path));
+            for (name, path) in executables.into_iter().chain(stems) {
+                map.entry(name).or_insert(path);
+            }
         }
+
         map
     }
 
+    fn find_all_executables(command: &str) -> Vec<String> {
+        Self::candidate_paths(command)
+            .into_iter()
+            .filter(Self::is_executable)
+            .filter_map(|path| path.to_str().map(|s| s.to_string()))
+            .collect()
+    }
+
+    /// Checks a command typed as a path, such as `./script.sh`, which runs
+    /// from exactly there instead of being searched for on `$PATH`.
+    fn find_at_path(command: &str) -> Option<String> {
+        let path = PathBuf::from(command);
+        (Self::is_executable(&path) && !path.is_dir()).then(|| command.to_string())
+    }
+
     fn find_executable(command: &str) -> Option<String> {
-        Self::get_path_dirs()
-            .iter()
-            .map(|path_dir| PathBuf::from(path_dir).join(command))
+        Self::candidate_paths(command)
+            .into_iter()
             .find(Self::is_executable)
             .and_then(|path| path.to_str().map(|s| s.to_string()))
     }
@@ -123,16 +305,40 @@ impl PathScanner {
 
 pub struct CommandRegistry {
     pub builtins: HashMap<String, Box<dyn Command>>,
-    pub executables: HashMap<String, String>,
+    /// Command names mapped to where they were found on `$PATH`, like bash's
+    /// hash table. Rebuilt by `hash -r`.
+    pub executables: RefCell<HashMap<String, String>>,
     history: HistoryManager,
+    jobs: JobTable,
+    last_status: Cell<i32>,
+    positional_args: RefCell<Vec<String>>,
+    aliases: RefCell<HashMap<String, String>>,
+    /// Commands set with `trap`, keyed by condition name such as `EXIT`.
+    traps: RefCell<HashMap<String, String>>,
+    /// Directories saved by `pushd`, most recent first. The working
+    /// directory itself is not stored.
+    dir_stack: RefCell<Vec<PathBuf>>,
+    /// Names given to `export` without a value while unset. They have no
+    /// place in the process environment until assigned, but are still
+    /// listed as exported.
+    exported_unset: RefCell<BTreeSet<String>>,
+    options: ShellOptions,
 }
 
 impl CommandRegistry {
     pub fn new() -> Self {
         CommandRegistry {
             builtins: HashMap::new(),
-            executables: HashMap::new(),
+            executables: RefCell::new(HashMap::new()),
             history: HistoryManager::new(),
+            jobs: JobTable::new(),
+            last_status: Cell::new(0),
+            positional_args: RefCell::new(Vec::new()),
+            aliases: RefCell::new(HashMap::new()),
+            traps: RefCell::new(HashMap::new()),
+            dir_stack: RefCell::new(Vec::new()),
+            exported_unset: RefCell::new(BTreeSet::new()),
+            options: ShellOptions::new(),
         }
     }
 
@@ -140,9 +346,19 @@ impl CommandRegistry {
         self.builtins.get(name).map(|b| b.as_ref())
     }
 
+    /// Describes every builtin, sorted by name.
+    pub fn describe_builtins(&self) -> Vec<CommandInfo> {
+        let mut infos: Vec<CommandInfo> = self.builtins.values().map(|b| b.describe()).collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// Every builtin, alias and executable name, sorted. `$PATH` is scanned
+    /// afresh, so programs installed after startup are included.
     pub fn get_command_names(&self) -> Vec<String> {
         let mut names: Vec<String> = self.builtins.keys().cloned().collect();
-        names.extend(self.executables.keys().cloned());
+        names.extend(PathScanner::scan_executables().into_keys());
+        names.extend(self.aliases.borrow().keys().cloned());
 
         names.sort();
         names.dedup();
@@ -150,13 +366,27 @@ impl CommandRegistry {
         names
     }
 
-    fn register_builtin(&mut self, command: Box<dyn Command>) {
+    /// Adds a builtin, replacing any existing one with the same name. Embedders
+    /// can call this after construction to install their own commands.
+    pub fn register_builtin(&mut self, command: Box<dyn Command>) {
         self.builtins
             .insert(command.get_name().to_string(), command);
     }
 
+    /// Records `cmd` in the history list unless `set +o history` is in effect.
     pub fn add_history_entry(&self, cmd: &str) {
-        self.history.add_entry(cmd);
+        if self.get_option(ShellOption::History) {
+            self.history.add_entry(cmd, HistoryControl::from_env());
+        }
+    }
+
+    pub fn clear_history(&self) {
+        self.history.clear();
+    }
+
+    /// Overwrites the newest history entry, e.g. with the command `fc` ran.
+    pub fn replace_last_history_entry(&self, cmd: &str) {
+        self.history.replace_last(cmd);
     }
 
     pub fn get_history(&self) -> Vec<String> {
@@ -183,12 +413,258 @@ impl CommandRegistry {
         self.history.write_to_file(path, append, is_exit)
     }
 
-    fn scan_path_executables(&mut self) {
-        self.executables = PathScanner::scan_executables();
+    pub fn add_job(&self, child: Child, command: &str) -> usize {
+        self.jobs.add(child, command)
+    }
+
+    /// Records a foreground child that was stopped as a job.
+    pub fn add_stopped_job(&self, child: Child, command: &str) -> usize {
+        self.jobs.add_stopped(child, command)
+    }
+
+    pub fn get_job_count(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Reaps finished background children without blocking and returns them
+    /// so they can be reported as done.
+    pub fn reap_jobs(&self) -> ShellResult<Vec<JobInfo>> {
+        Ok(self.jobs.snapshot(true)?)
     }
 
+    /// Lists every background job, dropping the finished ones afterwards.
+    pub fn list_jobs(&self) -> ShellResult<Vec<JobInfo>> {
+        Ok(self.jobs.snapshot(false)?)
+    }
+
+    /// The job with `id` as last seen, or `None` if there is no such job.
+    pub fn find_job(&self, id: usize) -> Option<JobInfo> {
+        self.jobs.find(id)
+    }
+
+    /// The ID of the current job, which `fg` and `bg` use by default.
+    pub fn current_job_id(&self) -> Option<usize> {
+        self.jobs.current_id()
+    }
+
+    pub fn set_job_state(&self, id: usize, state: JobState) {
+        self.jobs.set_state(id, state);
+    }
+
+    /// Waits for the job with `id` in the foreground until it exits or is
+    /// stopped. Returns `None` for unknown jobs.
+    pub fn wait_for_job_in_foreground(&self, id: usize) -> ShellResult<Option<WaitOutcome>> {
+        Ok(self.jobs.wait_in_foreground(id)?)
+    }
+
+    /// Blocks until the job with `id` exits or is stopped, or Ctrl-C breaks
+    /// off the wait. Returns `None` for unknown jobs.
+    pub fn wait_for_job(&self, id: usize) -> ShellResult<Option<WaitOutcome>> {
+        Ok(self.jobs.wait_for_id(id)?)
+    }
+
+    /// Blocks until the child with `pid` exits, whether or not it is a job.
+    /// Returns `None` if `pid` is not a child of the shell.
+    pub fn wait_for_pid(&self, pid: u32) -> ShellResult<Option<WaitOutcome>> {
+        match self.jobs.wait_for_pid(pid)? {
+            Some(outcome) => Ok(Some(outcome)),
+            None => Ok(wait_for_untracked_pid(pid)?.map(WaitOutcome::Exited)),
+        }
+    }
+
+    /// Blocks until every background job that is not stopped exits.
+    pub fn wait_for_all_jobs(&self) -> ShellResult<Option<WaitOutcome>> {
+        Ok(self.jobs.wait_all()?)
+    }
+
+    pub fn get_option(&self, option: ShellOption) -> bool {
+        self.options.is_enabled(option)
+    }
+
+    pub fn set_option(&self, option: ShellOption, enabled: bool) {
+        self.options.set(option, enabled);
+    }
+
+    pub fn get_last_status(&self) -> i32 {
+        self.last_status.get()
+    }
+
+    pub fn set_last_status(&self, status: i32) {
+        self.last_status.set(status);
+    }
+
+    pub fn get_positional_args(&self) -> Vec<String> {
+        self.positional_args.borrow().clone()
+    }
+
+    pub fn set_positional_args(&self, args: Vec<String>) {
+        *self.positional_args.borrow_mut() = args;
+    }
+
+    pub fn get_alias(&self, name: &str) -> Option<String> {
+        self.aliases.borrow().get(name).cloned()
+    }
+
+    pub fn set_alias(&self, name: &str, value: &str) {
+        self.aliases
+            .borrow_mut()
+            .insert(name.to_string(), value.to_string());
+    }
+
+    /// Removes an alias, returning false if it was not defined.
+    pub fn remove_alias(&self, name: &str) -> bool {
+        self.aliases.borrow_mut().remove(name).is_some()
+    }
+
+    pub fn clear_aliases(&self) {
+        self.aliases.borrow_mut().clear();
+    }
+
+    /// Returns every alias as `(name, value)`, sorted by name.
+    pub fn get_aliases(&self) -> Vec<(String, String)> {
+        let mut aliases: Vec<(String, String)> = self
+            .aliases
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    pub fn get_trap(&self, condition: &str) -> Option<String> {
+        self.traps.borrow().get(condition).cloned()
+    }
+
+    /// Sets the command run for `condition`, or resets it with `None`.
+    pub fn set_trap(&self, condition: &str, command: Option<&str>) {
+        let mut traps = self.traps.borrow_mut();
+        match command {
+            Some(command) => traps.insert(condition.to_string(), command.to_string()),
+            None => traps.remove(condition),
+        };
+    }
+
+    /// Returns every trap as `(condition, command)`, sorted by condition.
+    pub fn get_traps(&self) -> Vec<(String, String)> {
+        let mut traps: Vec<(String, String)> = self
+            .traps
+            .borrow()
+            .iter()
+            .map(|(condition, command)| (condition.clone(), command.clone()))
+            .collect();
+        traps.sort();
+        traps
+    }
+
+    pub fn get_dir_stack(&self) -> Vec<PathBuf> {
+        self.dir_stack.borrow().clone()
+    }
+
+    pub fn set_dir_stack(&self, stack: Vec<PathBuf>) {
+        *self.dir_stack.borrow_mut() = stack;
+    }
+
+    /// Marks `name` as exported even though it has no value yet, or stops
+    /// doing so with `false`, as `unset` does.
+    pub fn set_exported_unset(&self, name: &str, exported: bool) {
+        let mut names = self.exported_unset.borrow_mut();
+        if exported {
+            names.insert(name.to_string());
+        } else {
+            names.remove(name);
+        }
+    }
+
+    /// Returns the names exported without a value that are still unset,
+    /// sorted.
+    pub fn get_exported_unset(&self) -> Vec<String> {
+        self.exported_unset
+            .borrow()
+            .iter()
+            .filter(|name| env::var_os(name).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Resolves a shell variable, falling back to the process environment.
+    pub fn get_variable(&self, name: &str) -> Option<String> {
+        match name {
+            "?" => Some(self.get_last_status().to_string()),
+            "#" => Some(self.positional_args.borrow().len().to_string()),
+            "0" => env::args().next(),
+            _ if name.chars().all(|c| c.is_ascii_digit()) => {
+                let index = name.parse::<usize>().ok()?.checked_sub(1)?;
+                self.positional_args.borrow().get(index).cloned()
+            }
+            _ => env::var(name).ok(),
+        }
+    }
+
+    /// Rescans `$PATH`, replacing every remembered executable location.
+    pub fn rehash(&self) {
+        *self.executables.borrow_mut() = PathScanner::scan_executables();
+    }
+
+    /// Returns every remembered `(name, path)` pair, sorted by name.
+    pub fn get_hashed_executables(&self) -> Vec<(String, String)> {
+        let mut executables: Vec<(String, String)> = self
+            .executables
+            .borrow()
+            .iter()
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+        executables.sort();
+        executables
+    }
+
+    /// Finds `command`, preferring the location remembered in the hash
+    /// table. A remembered path that is no longer executable is looked up
+    /// on `$PATH` again, and whatever is found is remembered. A `command`
+    /// containing a `/` is a path and is only checked where it points.
     pub fn get_executable_path(&self, command: &str) -> Option<String> {
-        PathScanner::find_executable(command)
+        if command.contains('/') {
+            return PathScanner::find_at_path(command);
+        }
+
+        let hashed = self.executables.borrow().get(command).cloned();
+        if let Some(path) = hashed.filter(|path| PathScanner::is_executable(&PathBuf::from(path))) {
+            return Some(path);
+        }
+
+        self.hash_executable(command)
+    }
+
+    /// Searches `$PATH` for `command` and remembers where it was found.
+    pub fn hash_executable(&self, command: &str) -> Option<String> {
+        let path = PathScanner::find_executable(command)?;
+        self.executables
+            .borrow_mut()
+            .insert(command.to_string(), path.clone());
+        Some(path)
+    }
+
+    /// Returns every executable named `name` on `$PATH`, in search order,
+    /// or just `name` itself when it is a path to an executable.
+    pub fn find_all_executables(&self, name: &str) -> Vec<String> {
+        if name.contains('/') {
+            return PathScanner::find_at_path(name).into_iter().collect();
+        }
+        PathScanner::find_all_executables(name)
+    }
+}
+
+impl VariableLookup for CommandRegistry {
+    fn get_variable(&self, name: &str) -> Option<String> {
+        CommandRegistry::get_variable(self, name)
+    }
+
+    fn get_positional_args(&self) -> Vec<String> {
+        CommandRegistry::get_positional_args(self)
+    }
+
+    fn get_option(&self, option: ShellOption) -> bool {
+        CommandRegistry::get_option(self, option)
     }
 }
 
@@ -201,8 +677,40 @@ impl Default for CommandRegistry {
         registry.register_builtin(Box::new(PwdCommand));
         registry.register_builtin(Box::new(CdCommand));
         registry.register_builtin(Box::new(HistoryCommand));
-
-        registry.scan_path_executables();
+        registry.register_builtin(Box::new(HelpCommand));
+        registry.register_builtin(Box::new(JobsCommand));
+        registry.register_builtin(Box::new(ExportCommand));
+        registry.register_builtin(Box::new(UnsetCommand));
+        registry.register_builtin(Box::new(EnvCommand));
+        registry.register_builtin(Box::new(AliasCommand));
+        registry.register_builtin(Box::new(UnaliasCommand));
+        registry.register_builtin(Box::new(PrintfCommand));
+        registry.register_builtin(Box::new(SourceCommand));
+        registry.register_builtin(Box::new(DotCommand));
+        registry.register_builtin(Box::new(WaitCommand));
+        registry.register_builtin(Box::new(WhichCommand));
+        registry.register_builtin(Box::new(SetCommand));
+        registry.register_builtin(Box::new(FcCommand));
+        registry.register_builtin(Box::new(ReadCommand));
+        registry.register_builtin(Box::new(ShoptCommand));
+        registry.register_builtin(Box::new(HashCommand));
+        registry.register_builtin(Box::new(TrapCommand));
+        registry.register_builtin(Box::new(TestCommand));
+        registry.register_builtin(Box::new(BracketCommand));
+        registry.register_builtin(Box::new(RealpathCommand));
+        registry.register_builtin(Box::new(PushdCommand));
+        registry.register_builtin(Box::new(PopdCommand));
+        registry.register_builtin(Box::new(DirsCommand));
+        registry.register_builtin(Box::new(BasenameCommand));
+        registry.register_builtin(Box::new(DirnameCommand));
+        registry.register_builtin(Box::new(SleepCommand));
+        registry.register_builtin(Box::new(MkcdCommand));
+        registry.register_builtin(Box::new(TimeoutCommand));
+        registry.register_builtin(Box::new(KillCommand));
+        registry.register_builtin(Box::new(FgCommand));
+        registry.register_builtin(Box::new(BgCommand));
+
+        registry.rehash();
 
         registry
     }
@@ -0,0 +1,139 @@
+use std::env;
+use std::io::Write;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::options::ShellOption;
+
+/// Characters a value can hold and still be listed without quotes.
+const SAFE_VALUE_CHARS: &str = "_@%+=:,./-";
+
+/// Quotes `value` so the shell reads it back as the same single word:
+/// plain text is left bare, anything else is single-quoted.
+pub(super) fn quote_value(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || SAFE_VALUE_CHARS.contains(c))
+    {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+pub struct SetCommand;
+
+impl SetCommand {
+    /// Prints every variable as `NAME=value`, sorted by name.
+    fn print_variables(output: &mut dyn Write) -> ShellResult<()> {
+        let mut variables: Vec<(String, String)> = env::vars().collect();
+        variables.sort();
+        for (name, value) in variables {
+            writeln!(output, "{}={}", name, quote_value(&value))?;
+        }
+        Ok(())
+    }
+
+    /// Applies a cluster of single-letter flags such as `-ex` or `+x`.
+    fn set_flags(&self, arg: &str, enable: bool, registry: &CommandRegistry) -> ShellResult<()> {
+        for flag in arg[1..].chars() {
+            let option = ShellOption::from_flag(flag).ok_or_else(|| ShellError::InvalidOption {
+                command: self.get_name().to_string(),
+                option: format!("{}{}", &arg[..1], flag),
+            })?;
+            registry.set_option(option, enable);
+        }
+        Ok(())
+    }
+
+    fn print_options(
+        registry: &CommandRegistry,
+        reusable: bool,
+        output: &mut dyn Write,
+    ) -> ShellResult<()> {
+        for &option in ShellOption::SET {
+            let enabled = registry.get_option(option);
+            if reusable {
+                let flag = if enabled { '-' } else { '+' };
+                writeln!(output, "set {}o {}", flag, option.name())?;
+            } else {
+                let state = if enabled { "on" } else { "off" };
+                writeln!(output, "{:<15}\t{}", option.name(), state)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Command for SetCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.is_empty() {
+            Self::print_variables(ctx.output)?;
+            return Ok(ShellStatus::Continue);
+        }
+
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let enable = match arg.as_str() {
+                "-o" => true,
+                "+o" => false,
+                _ if arg.len() > 1 && (arg.starts_with('-') || arg.starts_with('+')) => {
+                    self.set_flags(arg, arg.starts_with('-'), registry)?;
+                    continue;
+                }
+                _ => {
+                    return Err(ShellError::InvalidOption {
+                        command: self.get_name().to_string(),
+                        option: arg.clone(),
+                    });
+                }
+            };
+
+            let Some(name) = iter.next() else {
+                Self::print_options(registry, !enable, ctx.output)?;
+                continue;
+            };
+
+            let option = ShellOption::from_name(ShellOption::SET, name).ok_or_else(|| {
+                ShellError::InvalidOption {
+                    command: self.get_name().to_string(),
+                    option: name.clone(),
+                }
+            })?;
+            registry.set_option(option, enable);
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "set"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "set [-eCfx] [+eCfx] [-o option-name] [+o option-name]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Set or unset values of shell options.\n\n\
+         -o NAME enables the option NAME and +o NAME disables it. Without a\n\
+         NAME, -o lists every option with its state and +o prints them as\n\
+         commands that recreate the current settings. Without arguments,\n\
+         every variable is listed.\n\n\
+         Options:\n  errexit    exit when a command fails; also -e\n  \
+         history    record commands in the history list\n  \
+         noclobber  keep `>' from overwriting files; `>|' still does; also -C\n  \
+         noglob     leave wildcards unexpanded; also -f\n  \
+         xtrace     print each command to stderr before running it; also -x"
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::Write;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::options::ShellOption;
+
+pub struct ShoptCommand;
+
+impl ShoptCommand {
+    fn print_option(
+        option: ShellOption,
+        enabled: bool,
+        reusable: bool,
+        output: &mut dyn Write,
+    ) -> ShellResult<()> {
+        if reusable {
+            let flag = if enabled { 's' } else { 'u' };
+            writeln!(output, "shopt -{} {}", flag, option.name())?;
+        } else {
+            let state = if enabled { "on" } else { "off" };
+            writeln!(output, "{:<15}\t{}", option.name(), state)?;
+        }
+        Ok(())
+    }
+}
+
+impl Command for ShoptCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let mut set = None;
+        let mut reusable = false;
+        let mut names = args;
+
+        while let Some((flag, rest)) = names.split_first() {
+            match flag.as_str() {
+                "-s" => set = Some(true),
+                "-u" => set = Some(false),
+                "-p" => reusable = true,
+                _ if flag.starts_with('-') => {
+                    return Err(ShellError::InvalidOption {
+                        command: self.get_name().to_string(),
+                        option: flag.clone(),
+                    });
+                }
+                _ => break,
+            }
+            names = rest;
+        }
+
+        let options = names
+            .iter()
+            .map(|name| {
+                ShellOption::from_name(ShellOption::SHOPT, name).ok_or_else(|| {
+                    ShellError::InvalidOption {
+                        command: self.get_name().to_string(),
+                        option: name.clone(),
+                    }
+                })
+            })
+            .collect::<ShellResult<Vec<_>>>()?;
+
+        // `-s NAME` / `-u NAME` change options silently
+        if let Some(enable) = set
+            && !options.is_empty()
+        {
+            for option in options {
+                registry.set_option(option, enable);
+            }
+            return Ok(ShellStatus::Continue);
+        }
+
+        // Otherwise list the named options, or every option, optionally only
+        // those matching `-s` / `-u`. The status is 1 if any is off.
+        let listed = if options.is_empty() {
+            ShellOption::SHOPT.to_vec()
+        } else {
+            options
+        };
+
+        let mut all_enabled = true;
+        for option in listed {
+            let enabled = registry.get_option(option);
+            all_enabled &= enabled;
+            if set.is_none_or(|state| state == enabled) {
+                Self::print_option(option, enabled, reusable, ctx.output)?;
+            }
+        }
+
+        if !all_enabled && !names.is_empty() {
+            registry.set_last_status(1);
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "shopt"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "shopt [-psu] [optname ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Set and unset shell options.\n\n\
+         -s enables and -u disables each OPTNAME. Without -s or -u, prints\n\
+         the state of each OPTNAME, or of every option when none are given,\n\
+         and returns failure if any named option is off. -p prints options\n\
+         as commands that recreate the current settings.\n\n\
+         Options:\n  globstar   let ** match files and any number of directories"
+    }
+}
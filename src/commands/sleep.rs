@@ -0,0 +1,96 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::take_interrupt;
+
+/// How often a sleep wakes up to check whether Ctrl-C was pressed.
+const INTERRUPT_POLL: Duration = Duration::from_millis(10);
+const EXIT_INTERRUPTED: i32 = 128 + libc::SIGINT;
+
+pub struct SleepCommand;
+
+impl SleepCommand {
+    /// Parses a number of seconds, optionally fractional and followed by a
+    /// unit: `ms`, `s`, `m`, `h` or `d`.
+    pub(super) fn parse_duration(arg: &str) -> Option<Duration> {
+        let (number, scale) = if let Some(number) = arg.strip_suffix("ms") {
+            (number, 0.001)
+        } else {
+            match arg.char_indices().last()? {
+                (end, 's') => (&arg[..end], 1.0),
+                (end, 'm') => (&arg[..end], 60.0),
+                (end, 'h') => (&arg[..end], 3600.0),
+                (end, 'd') => (&arg[..end], 86400.0),
+                _ => (arg, 1.0),
+            }
+        };
+
+        // Rules out `inf`, `nan` and signs, which `f64` would accept
+        if !number.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let seconds: f64 = number.parse().ok()?;
+        Duration::try_from_secs_f64(seconds * scale).ok()
+    }
+}
+
+impl Command for SleepCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.is_empty() {
+            return Err(ShellError::MissingOperand {
+                command: self.get_name().to_string(),
+            });
+        }
+
+        // A duration too long to add up counts as invalid rather than
+        // overflowing
+        let mut deadline = Instant::now();
+        for arg in args {
+            deadline = Self::parse_duration(arg)
+                .and_then(|duration| deadline.checked_add(duration))
+                .ok_or_else(|| ShellError::InvalidDuration {
+                    command: self.get_name().to_string(),
+                    arg: arg.clone(),
+                })?;
+        }
+
+        // A Ctrl-C from before the sleep started should not cut it short
+        take_interrupt();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(INTERRUPT_POLL));
+
+            if take_interrupt() {
+                registry.set_last_status(EXIT_INTERRUPTED);
+                break;
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "sleep"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "sleep number[suffix] ..."
+    }
+
+    fn get_help(&self) -> &str {
+        "Pause for the sum of the given durations.\n\n\
+         Each NUMBER may be fractional and is in seconds, unless followed\n\
+         by a suffix: `ms' for milliseconds, `s' for seconds, `m' for\n\
+         minutes, `h' for hours or `d' for days. Ctrl-C ends the pause early."
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+
+use super::{Command, CommandRegistry, IoContext, ShellExecutor, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::parser::parse_input;
+
+/// Runs each line of the file named by `args[0]` in the current shell.
+///
+/// Builtins have no handle on the running executor, but an executor only
+/// borrows the registry, so a fresh one over the same registry shares all
+/// shell state (cwd, variables, aliases, jobs) with the caller. It runs
+/// the lines against the builtin's own streams, so redirecting, piping or
+/// capturing `source` covers everything the file prints.
+fn source_file(
+    name: &str,
+    args: &[String],
+    registry: &CommandRegistry,
+    ctx: &mut IoContext,
+) -> ShellResult<ShellStatus> {
+    let Some((path, script_args)) = args.split_first() else {
+        return Err(ShellError::SourceArgRequired {
+            command: name.to_string(),
+        });
+    };
+
+    let script = fs::read_to_string(path).map_err(|e| ShellError::FileOpen {
+        path: path.clone(),
+        source: e,
+    })?;
+
+    let saved_args = (!script_args.is_empty()).then(|| {
+        let saved = registry.get_positional_args();
+        registry.set_positional_args(script_args.to_vec());
+        saved
+    });
+
+    let executor = ShellExecutor::new(registry).with_stdio(ctx.files.take());
+    let mut status = ShellStatus::Continue;
+
+    for line in script.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let result = parse_input(line).and_then(|pipelines| executor.run(&pipelines));
+        match result {
+            Ok(exit @ ShellStatus::Exit(_)) => {
+                status = exit;
+                break;
+            }
+            Ok(ShellStatus::Continue) => {}
+            Err(e) => {
+                registry.set_last_status(e.exit_code());
+                writeln!(ctx.error, "{}", e)?;
+            }
+        }
+    }
+
+    if let Some(saved_args) = saved_args {
+        registry.set_positional_args(saved_args);
+    }
+
+    Ok(status)
+}
+
+const SOURCE_HELP: &str = "Execute commands from a file in the current shell.\n\n\
+     Read and execute commands from FILENAME line by line. Blank lines and\n\
+     lines starting with `#' are skipped, and a failing command does not\n\
+     stop the rest of the file. ARGUMENTS become the positional parameters\n\
+     while FILENAME runs.";
+
+pub struct SourceCommand;
+
+impl Command for SourceCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        source_file(self.get_name(), args, registry, ctx)
+    }
+
+    fn get_name(&self) -> &str {
+        "source"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "source filename [arguments]"
+    }
+
+    fn get_help(&self) -> &str {
+        SOURCE_HELP
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+}
+
+/// `.` is the POSIX spelling of `source`.
+pub struct DotCommand;
+
+impl Command for DotCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        source_file(self.get_name(), args, registry, ctx)
+    }
+
+    fn get_name(&self) -> &str {
+        "."
+    }
+
+    fn get_synopsis(&self) -> &str {
+        ". filename [arguments]"
+    }
+
+    fn get_help(&self) -> &str {
+        SOURCE_HELP
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+}
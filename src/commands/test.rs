@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+const BINARY_OPERATORS: &[&str] = &["=", "==", "!=", "-eq", "-ne", "-lt", "-le", "-gt", "-ge"];
+
+/// Evaluates a `test` expression, following the POSIX rules that pick the
+/// meaning of each argument from how many arguments there are.
+struct Expression<'a> {
+    command: &'a str,
+}
+
+impl Expression<'_> {
+    fn evaluate(&self, args: &[&str]) -> ShellResult<bool> {
+        match args {
+            [] => Ok(false),
+            [arg] => Ok(!arg.is_empty()),
+            // A binary operator in the middle wins over a leading `!`
+            [left, op, right] if BINARY_OPERATORS.contains(op) => self.binary(left, op, right),
+            ["!", rest @ ..] if rest.len() <= 3 => Ok(!self.evaluate(rest)?),
+            [op, operand] => self.unary(op, operand),
+            [_, op, _] => Err(ShellError::TestBinaryExpected {
+                command: self.command.to_string(),
+                arg: op.to_string(),
+            }),
+            _ => Err(ShellError::TestTooManyArguments {
+                command: self.command.to_string(),
+            }),
+        }
+    }
+
+    fn unary(&self, op: &str, operand: &str) -> ShellResult<bool> {
+        let path = Path::new(operand);
+        Ok(match op {
+            "-e" => path.exists(),
+            "-f" => path.is_file(),
+            "-d" => path.is_dir(),
+            "-z" => operand.is_empty(),
+            "-n" => !operand.is_empty(),
+            _ => {
+                return Err(ShellError::TestUnaryExpected {
+                    command: self.command.to_string(),
+                    arg: op.to_string(),
+                });
+            }
+        })
+    }
+
+    fn binary(&self, left: &str, op: &str, right: &str) -> ShellResult<bool> {
+        let compare: fn(&i64, &i64) -> bool = match op {
+            "=" | "==" => return Ok(left == right),
+            "!=" => return Ok(left != right),
+            "-eq" => i64::eq,
+            "-ne" => i64::ne,
+            "-lt" => i64::lt,
+            "-le" => i64::le,
+            "-gt" => i64::gt,
+            "-ge" => i64::ge,
+            _ => {
+                return Err(ShellError::TestBinaryExpected {
+                    command: self.command.to_string(),
+                    arg: op.to_string(),
+                });
+            }
+        };
+        Ok(compare(&self.integer(left)?, &self.integer(right)?))
+    }
+
+    fn integer(&self, arg: &str) -> ShellResult<i64> {
+        arg.trim()
+            .parse()
+            .map_err(|_| ShellError::TestIntegerExpected {
+                command: self.command.to_string(),
+                arg: arg.to_string(),
+            })
+    }
+}
+
+/// Sets `$?` to 0 if `args` form a true expression and to 1 if not.
+fn run_test(
+    command: &str,
+    args: &[String],
+    registry: &CommandRegistry,
+) -> ShellResult<ShellStatus> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = Expression { command }.evaluate(&args)?;
+    registry.set_last_status(if result { 0 } else { 1 });
+    Ok(ShellStatus::Continue)
+}
+
+const TEST_HELP: &str = "Evaluate conditional expression.\n\n\
+     Exits with a status of 0 (true) or 1 (false) depending on the\n\
+     evaluation of EXPR, and with 2 if EXPR is malformed.\n\n\
+     File operators:\n  -e FILE        True if file exists.\n\
+       -f FILE        True if file exists and is a regular file.\n\
+       -d FILE        True if file is a directory.\n\n\
+     String operators:\n  -z STRING      True if string is empty.\n\
+       -n STRING      True if string is not empty.\n\
+       STRING         True if string is not empty.\n\
+       S1 = S2        True if the strings are equal.\n\
+       S1 != S2       True if the strings are not equal.\n\n\
+     Other operators:\n  ! EXPR         True if expr is false.\n\
+       N1 OP N2       OP is one of -eq, -ne, -lt, -le, -gt or -ge.";
+
+pub struct TestCommand;
+
+impl Command for TestCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        run_test(self.get_name(), args, registry)
+    }
+
+    fn get_name(&self) -> &str {
+        "test"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "test [expr]"
+    }
+
+    fn get_help(&self) -> &str {
+        TEST_HELP
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
+
+/// `[` is `test` with a required closing `]`.
+pub struct BracketCommand;
+
+impl Command for BracketCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let Some((_, args)) = args.split_last().filter(|(last, _)| *last == "]") else {
+            return Err(ShellError::TestMissingBracket);
+        };
+        run_test(self.get_name(), args, registry)
+    }
+
+    fn get_name(&self) -> &str {
+        "["
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "[ arg... ]"
+    }
+
+    fn get_help(&self) -> &str {
+        TEST_HELP
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command as ProcessCommand, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::sleep::SleepCommand;
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::{exit_code, take_interrupt};
+
+/// How often the timer wakes up to pass on a Ctrl-C.
+const INTERRUPT_POLL: Duration = Duration::from_millis(10);
+/// How long a timed-out command has to exit after SIGTERM before SIGKILL.
+const KILL_GRACE: Duration = Duration::from_secs(1);
+const EXIT_TIMED_OUT: i32 = 124;
+
+pub struct TimeoutCommand;
+
+impl TimeoutCommand {
+    /// Waits until `deadline` for `done`, then sends SIGTERM to the process
+    /// group `group`, followed by SIGKILL if it is still running after
+    /// `KILL_GRACE`. The command has its own group, so Ctrl-C only reaches
+    /// it by being passed on here. Returns whether the deadline was reached.
+    fn watch(group: libc::pid_t, deadline: Instant, done: Receiver<()>) -> bool {
+        let signal = |signal| {
+            // SAFETY: kill takes no pointers.
+            unsafe { libc::kill(-group, signal) };
+        };
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            if !matches!(
+                done.recv_timeout(remaining.min(INTERRUPT_POLL)),
+                Err(RecvTimeoutError::Timeout)
+            ) {
+                return false;
+            }
+            if take_interrupt() {
+                signal(libc::SIGINT);
+            }
+        }
+
+        signal(libc::SIGTERM);
+        if let Err(RecvTimeoutError::Timeout) = done.recv_timeout(KILL_GRACE) {
+            signal(libc::SIGKILL);
+        }
+        true
+    }
+
+    /// Blocks until `child` exits, leaving it unreaped so its process group
+    /// cannot be reused by another process while the timer may signal it.
+    fn wait_unreaped(child: &Child) -> io::Result<()> {
+        loop {
+            // SAFETY: `info` is a valid, writable siginfo_t for waitid to fill in.
+            let status = unsafe {
+                let mut info: libc::siginfo_t = std::mem::zeroed();
+                libc::waitid(
+                    libc::P_PID,
+                    child.id(),
+                    &mut info,
+                    libc::WEXITED | libc::WNOWAIT,
+                )
+            };
+            if status == 0 {
+                return Ok(());
+            }
+
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::EINTR) {
+                return Err(error);
+            }
+        }
+    }
+}
+
+impl Command for TimeoutCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let [duration, command, args @ ..] = args else {
+            return Err(ShellError::MissingOperand {
+                command: self.get_name().to_string(),
+            });
+        };
+        // A limit too far off to represent is as invalid as a malformed one
+        let deadline = SleepCommand::parse_duration(duration)
+            .and_then(|limit| Instant::now().checked_add(limit))
+            .ok_or_else(|| ShellError::InvalidDuration {
+                command: self.get_name().to_string(),
+                arg: duration.clone(),
+            })?;
+        let Some(full_path) = registry.get_executable_path(command) else {
+            return Err(ShellError::CommandNotFound(command.to_string()));
+        };
+
+        let mut child = ProcessCommand::new(&full_path)
+            .arg0(command)
+            .args(args)
+            .process_group(0)
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| ShellError::ProcessStart {
+                command: command.to_string(),
+                source: e,
+            })?;
+
+        let group = child.id() as libc::pid_t;
+        let (done, finished) = mpsc::channel();
+        take_interrupt();
+        let timer = thread::spawn(move || Self::watch(group, deadline, finished));
+
+        let copied = match child.stdout.take() {
+            Some(mut stdout) => io::copy(&mut stdout, ctx.output).map(drop),
+            None => Ok(()),
+        };
+        let waited = Self::wait_unreaped(&child);
+        drop(done);
+        let timed_out = timer.join().unwrap_or(false);
+        let status = child.wait()?;
+        copied?;
+        waited?;
+
+        registry.set_last_status(if timed_out {
+            EXIT_TIMED_OUT
+        } else {
+            exit_code(status)
+        });
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "timeout"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "timeout duration command [arg ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Run COMMAND, stopping it if it runs longer than DURATION.\n\n\
+         DURATION takes the same suffixes as `sleep'. When it runs out,\n\
+         COMMAND and anything it started are sent SIGTERM, then SIGKILL a\n\
+         second later if still running, and the exit status is 124.\n\
+         Otherwise the exit status is that of COMMAND."
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+/// The condition whose trap runs when the shell exits.
+pub const EXIT_TRAP: &str = "EXIT";
+
+/// Quotes a trap command the way `trap` prints it, so it can be re-entered.
+fn quote_command(command: &str) -> String {
+    format!("'{}'", command.replace('\'', "'\\''"))
+}
+
+/// Maps a condition as written on the command line to its canonical name.
+/// Only `EXIT` (or `0`) is supported.
+fn condition_name(spec: &str) -> ShellResult<&'static str> {
+    match spec {
+        "EXIT" | "0" => Ok(EXIT_TRAP),
+        _ => Err(ShellError::InvalidSignalSpec {
+            spec: spec.to_string(),
+        }),
+    }
+}
+
+pub struct TrapCommand;
+
+impl Command for TrapCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let args = match args.first().map(String::as_str) {
+            Some("--") => &args[1..],
+            _ => args,
+        };
+
+        let (action, conditions) = match args {
+            [] => {
+                for (condition, command) in registry.get_traps() {
+                    writeln!(
+                        ctx.output,
+                        "trap -- {} {}",
+                        quote_command(&command),
+                        condition
+                    )?;
+                }
+                return Ok(ShellStatus::Continue);
+            }
+            // `trap CONDITION` resets it, like `trap - CONDITION`
+            [condition] => (None, std::slice::from_ref(condition)),
+            [action, conditions @ ..] if action == "-" => (None, conditions),
+            [action, conditions @ ..] => (Some(action.as_str()), conditions),
+        };
+
+        for spec in conditions {
+            registry.set_trap(condition_name(spec)?, action);
+        }
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "trap"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "trap [-] [action condition ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Run commands when the shell exits.\n\n\
+         ACTION is run when each CONDITION occurs. The only supported\n\
+         condition is EXIT (or 0), which fires as the shell shuts down; the\n\
+         shell still exits with its pending status unless ACTION runs `exit'.\n\
+         An ACTION of `-', or no ACTION at all, resets the condition. Without\n\
+         arguments, prints every trap in a reusable form."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
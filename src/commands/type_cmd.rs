@@ -1,29 +1,126 @@
-use std::io::Write;
-
 use super::CommandRegistry;
-use super::{Command, ShellStatus};
+use super::{Command, IoContext, ShellStatus};
 use crate::error::{ShellError, ShellResult};
+use crate::json::JsonObject;
+
+/// `$?` left by `type -t` when a name is not found; nothing is printed.
+const EXIT_NOT_FOUND: i32 = 1;
+
+/// One way a name can be interpreted as a command.
+enum Resolution {
+    Alias(String),
+    Builtin(String),
+    File(String),
+}
+
+impl Resolution {
+    /// The single word printed by `-t`.
+    fn kind(&self) -> &'static str {
+        match self {
+            Resolution::Alias(_) => "alias",
+            Resolution::Builtin(_) => "builtin",
+            Resolution::File(_) => "file",
+        }
+    }
+
+    fn describe(&self, name: &str) -> String {
+        match self {
+            Resolution::Alias(value) => format!("{} is aliased to `{}'", name, value),
+            Resolution::Builtin(kind) => format!("{} is a {}", name, kind),
+            Resolution::File(path) => format!("{} is {}", name, path),
+        }
+    }
+
+    fn to_json(&self, name: &str) -> String {
+        let object = JsonObject::new()
+            .string("name", name)
+            .string("kind", self.kind());
+        match self {
+            Resolution::Alias(value) => object.string("value", value),
+            Resolution::Builtin(_) => object,
+            Resolution::File(path) => object.string("path", path),
+        }
+        .finish()
+    }
+}
 
 pub struct TypeCommand;
 
+impl TypeCommand {
+    /// Returns each way `name` could be interpreted, in lookup order. Only
+    /// the first match is returned unless `all` is set.
+    fn classify(name: &str, registry: &CommandRegistry, all: bool) -> Vec<Resolution> {
+        let mut matches = Vec::new();
+
+        if let Some(value) = registry.get_alias(name) {
+            matches.push(Resolution::Alias(value));
+        }
+        if let Some(command) = registry.get_builtin(name) {
+            matches.push(Resolution::Builtin(command.get_type().to_string()));
+        }
+        if all {
+            for path in registry.find_all_executables(name) {
+                matches.push(Resolution::File(path));
+            }
+        } else if matches.is_empty()
+            && let Some(path) = registry.get_executable_path(name)
+        {
+            matches.push(Resolution::File(path));
+        }
+
+        if !all {
+            matches.truncate(1);
+        }
+        matches
+    }
+}
+
 impl Command for TypeCommand {
     fn execute(
         &self,
         args: &[String],
         registry: &CommandRegistry,
-        output: &mut dyn Write,
+        ctx: &mut IoContext,
     ) -> ShellResult<ShellStatus> {
-        if args.is_empty() {
-            return Ok(ShellStatus::Continue);
+        let mut terse = false;
+        let mut all = false;
+        let mut json = false;
+        let mut names = args;
+        while let Some((flag, rest)) = names.split_first() {
+            if flag == "--json" {
+                json = true;
+                names = rest;
+                continue;
+            }
+            let Some(letters) = flag.strip_prefix('-') else {
+                break;
+            };
+            if letters.is_empty() || !letters.chars().all(|c| matches!(c, 'a' | 't')) {
+                break;
+            }
+            terse |= letters.contains('t');
+            all |= letters.contains('a');
+            names = rest;
         }
 
-        for arg in args {
-            if let Some(command) = registry.get_builtin(arg) {
-                writeln!(output, "{} is a {}", arg, command.get_type())?;
-            } else if let Some(executable_path) = registry.get_executable_path(arg) {
-                writeln!(output, "{} is {}", arg, executable_path)?;
-            } else {
-                return Err(ShellError::TypeNotFound(arg.clone()));
+        for name in names {
+            let matches = Self::classify(name, registry, all);
+            if matches.is_empty() {
+                if terse {
+                    registry.set_last_status(EXIT_NOT_FOUND);
+                    continue;
+                }
+                return Err(ShellError::TypeNotFound(name.clone()));
+            }
+
+            for resolution in matches {
+                if json {
+                    writeln!(ctx.output, "{}", resolution.to_json(name))?;
+                } else if terse {
+                    writeln!(ctx.output, "{}", resolution.kind())?;
+                } else {
+                    writeln!(ctx.output, "{}", resolution.describe(name))?;
+                }
             }
         }
 
@@ -33,4 +130,23 @@ impl Command for TypeCommand {
     fn get_name(&self) -> &str {
         "type"
     }
+
+    fn get_synopsis(&self) -> &str {
+        "type [-at] [--json] name [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Display information about command type.\n\n\
+         For each NAME, indicate how it would be interpreted if used as a\n\
+         command name.\n\n\
+         -a\tdisplay all locations containing an executable named NAME,\n\
+         \tafter any alias and builtin of that name\n\
+         -t\tprint a single word, one of `alias', `builtin' or `file'\n\
+         --json\tprint one JSON object per match, with its name, kind\n\
+         \tand the alias value or file path"
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,50 @@
+use std::env;
+
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::parser::is_valid_name;
+
+pub struct UnsetCommand;
+
+impl Command for UnsetCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if let Some(invalid) = args.iter().find(|arg| !is_valid_name(arg)) {
+            return Err(ShellError::InvalidIdentifier {
+                command: self.get_name().to_string(),
+                name: invalid.clone(),
+            });
+        }
+
+        for name in args {
+            // SAFETY: the shell is single-threaded, so nothing reads the
+            // environment concurrently.
+            unsafe { env::remove_var(name) };
+            registry.set_exported_unset(name, false);
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "unset"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "unset [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Unset values of shell variables.\n\n\
+         Remove each NAME from the environment. Names that are not set\n\
+         are ignored."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
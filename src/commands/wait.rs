@@ -0,0 +1,81 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+use crate::jobs::WaitOutcome;
+
+pub struct WaitCommand;
+
+impl WaitCommand {
+    /// Waits for a `%n` job spec or a process ID and returns how it ended.
+    fn wait_for(arg: &str, registry: &CommandRegistry) -> ShellResult<WaitOutcome> {
+        if let Some(spec) = arg.strip_prefix('%') {
+            let outcome = match spec.parse() {
+                Ok(id) => registry.wait_for_job(id)?,
+                Err(_) => None,
+            };
+            return outcome.ok_or_else(|| ShellError::NoSuchJob {
+                command: "wait".to_string(),
+                spec: arg.to_string(),
+            });
+        }
+
+        let pid: u32 = arg.parse().map_err(|_| ShellError::WaitInvalidArg {
+            arg: arg.to_string(),
+        })?;
+
+        registry
+            .wait_for_pid(pid)?
+            .ok_or_else(|| ShellError::WaitNotChild {
+                pid: arg.to_string(),
+            })
+    }
+}
+
+impl Command for WaitCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        _: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        if args.is_empty() {
+            let status = match registry.wait_for_all_jobs()? {
+                Some(WaitOutcome::Interrupted) => WaitOutcome::Interrupted.exit_code(),
+                _ => 0,
+            };
+            registry.set_last_status(status);
+            return Ok(ShellStatus::Continue);
+        }
+
+        for arg in args {
+            let outcome = Self::wait_for(arg, registry)?;
+            registry.set_last_status(outcome.exit_code());
+            if outcome == WaitOutcome::Interrupted {
+                break;
+            }
+        }
+
+        Ok(ShellStatus::Continue)
+    }
+
+    fn get_name(&self) -> &str {
+        "wait"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "wait [id ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Wait for job completion and return exit status.\n\n\
+         Waits for each process identified by ID, which may be a process ID\n\
+         or a job specification such as %1, and reports its status. Process\n\
+         IDs that are children of the shell but not jobs are waited for too.\n\
+         Without arguments, waits for all running background jobs and\n\
+         returns zero; stopped jobs are left alone. Ctrl-C stops the wait\n\
+         and leaves the jobs running."
+    }
+
+    fn is_posix(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,52 @@
+use super::{Command, CommandRegistry, IoContext, ShellStatus};
+use crate::error::{ShellError, ShellResult};
+
+pub struct WhichCommand;
+
+impl Command for WhichCommand {
+    fn execute(
+        &self,
+        args: &[String],
+        registry: &CommandRegistry,
+        ctx: &mut IoContext,
+    ) -> ShellResult<ShellStatus> {
+        let show_builtins = args.first().is_some_and(|arg| arg == "-a");
+        let names = if show_builtins { &args[1..] } else { args };
+
+        let mut missing = None;
+        for name in names {
+            let report_builtin = show_builtins && registry.get_builtin(name).is_some();
+            if report_builtin {
+                writeln!(ctx.output, "{}: shell builtin", name)?;
+            }
+
+            match registry.get_executable_path(name) {
+                Some(path) => writeln!(ctx.output, "{}", path)?,
+                None if report_builtin => {}
+                None => {
+                    missing.get_or_insert_with(|| name.clone());
+                }
+            }
+        }
+
+        match missing {
+            Some(name) => Err(ShellError::WhichNotFound(name)),
+            None => Ok(ShellStatus::Continue),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "which"
+    }
+
+    fn get_synopsis(&self) -> &str {
+        "which [-a] name [name ...]"
+    }
+
+    fn get_help(&self) -> &str {
+        "Locate commands on PATH.\n\n\
+         Print the full path of the executable each NAME would run. Unlike\n\
+         `type', builtins are ignored unless -a is given, in which case they\n\
+         are reported before any executable of the same name."
+    }
+}
@@ -2,26 +2,166 @@ use std::io;
 
 use thiserror::Error;
 
+const EXIT_FAILURE: i32 = 1;
+const EXIT_SYNTAX_ERROR: i32 = 2;
+const EXIT_CANNOT_EXECUTE: i32 = 126;
+const EXIT_COMMAND_NOT_FOUND: i32 = 127;
+
 #[derive(Error, Debug)]
 pub enum ShellError {
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
+    #[error("syntax error near unexpected token `{0}'")]
+    SyntaxError(String),
+
+    #[error("syntax error: unexpected end of file {0}")]
+    UnexpectedEof(String),
+
     #[error("{0}: command not found")]
     CommandNotFound(String),
 
-    #[error("cd: {path}: No such file or directory")]
-    DirectoryNotFound { path: String },
+    #[error("{0}: Permission denied")]
+    PermissionDenied(String),
+
+    #[error("{0}: Is a directory")]
+    IsADirectory(String),
+
+    #[error("{command}: {path}: No such file or directory")]
+    DirectoryNotFound { command: String, path: String },
+
+    #[error("{command}: directory stack empty")]
+    DirectoryStackEmpty { command: String },
+
+    #[error("pushd: no other directory")]
+    NoOtherDirectory,
+
+    #[error("cd: HOME not set")]
+    HomeNotSet,
 
     #[error("{0}: not found")]
     TypeNotFound(String),
 
+    #[error("which: {0}: not found")]
+    WhichNotFound(String),
+
+    #[error("hash: {0}: not found")]
+    HashNotFound(String),
+
     #[error("history: {flag}: argument required")]
     HistoryArgRequired { flag: String },
 
     #[error("history: {arg}: numeric argument required")]
     HistoryInvalidArg { arg: String },
 
+    #[error("{command}: no command found")]
+    HistoryEventNotFound { command: String },
+
+    #[error("{event}: event not found")]
+    EventNotFound { event: String },
+
+    #[error("fc: usage: fc -s [pat=rep] [command] or fc -e - [pat=rep] [command]")]
+    FcUsage,
+
+    #[error("help: no help topics match `{pattern}'")]
+    HelpNotFound { pattern: String },
+
+    #[error("{command}: `{name}': not a valid identifier")]
+    InvalidIdentifier { command: String, name: String },
+
+    #[error("{command}: {name}: not found")]
+    AliasNotFound { command: String, name: String },
+
+    #[error("printf: usage: printf format [arguments]")]
+    PrintfUsage,
+
+    #[error("printf: `{format}': invalid format character")]
+    PrintfInvalidFormat { format: String },
+
+    #[error("printf: {arg}: invalid number")]
+    PrintfInvalidNumber { arg: String },
+
+    #[error("{command}: filename argument required")]
+    SourceArgRequired { command: String },
+
+    #[error("wait: pid {pid} is not a child of this shell")]
+    WaitNotChild { pid: String },
+
+    #[error("{command}: {spec}: no such job")]
+    NoSuchJob { command: String, spec: String },
+
+    #[error("wait: {arg}: not a pid or valid job spec")]
+    WaitInvalidArg { arg: String },
+
+    #[error("kill: {arg}: arguments must be process or job IDs")]
+    KillInvalidArg { arg: String },
+
+    #[error("kill: {spec}: invalid signal specification")]
+    InvalidSignal { spec: String },
+
+    #[error("kill: ({target}) - {source}")]
+    KillFailed {
+        target: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{command}: {option}: option requires an argument")]
+    OptionArgRequired { command: String, option: String },
+
+    #[error("{command}: {arg}: numeric argument required")]
+    NumericArgRequired { command: String, arg: String },
+
+    #[error("trap: {spec}: invalid signal specification")]
+    InvalidSignalSpec { spec: String },
+
+    #[error("[: missing `]'")]
+    TestMissingBracket,
+
+    #[error("{command}: {arg}: unary operator expected")]
+    TestUnaryExpected { command: String, arg: String },
+
+    #[error("{command}: {arg}: binary operator expected")]
+    TestBinaryExpected { command: String, arg: String },
+
+    #[error("{command}: {arg}: integer expression expected")]
+    TestIntegerExpected { command: String, arg: String },
+
+    #[error("{command}: too many arguments")]
+    TestTooManyArguments { command: String },
+
+    #[error("{command}: {option}: invalid option name")]
+    InvalidOption { command: String, option: String },
+
+    #[error("{command}: {fd}: invalid file descriptor")]
+    InvalidFileDescriptor { command: String, fd: String },
+
+    #[error("{command}: invalid time interval `{arg}'")]
+    InvalidDuration { command: String, arg: String },
+
+    #[error("{command}: missing operand")]
+    MissingOperand { command: String },
+
+    #[error("{command}: extra operand `{operand}'")]
+    ExtraOperand { command: String, operand: String },
+
+    #[error("realpath: {path}: No such file or directory")]
+    RealpathNotFound { path: String },
+
+    #[error("{word}: ambiguous redirect")]
+    AmbiguousRedirect { word: String },
+
+    #[error("{command}: cannot create directory `{path}': {source}")]
+    CreateDirectory {
+        command: String,
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{path}: cannot overwrite existing file")]
+    CannotOverwrite { path: String },
+
     #[error("Failed to open {path}: {source}")]
     FileOpen {
         path: String,
@@ -37,4 +177,24 @@ pub enum ShellError {
     },
 }
 
+impl ShellError {
+    /// The `$?` value left behind by a command that fails with this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ShellError::CommandNotFound(_)
+            | ShellError::WaitNotChild { .. }
+            | ShellError::NoSuchJob { .. } => EXIT_COMMAND_NOT_FOUND,
+            ShellError::PermissionDenied(_) | ShellError::IsADirectory(_) => EXIT_CANNOT_EXECUTE,
+            ShellError::SyntaxError(_)
+            | ShellError::UnexpectedEof(_)
+            | ShellError::TestMissingBracket
+            | ShellError::TestUnaryExpected { .. }
+            | ShellError::TestBinaryExpected { .. }
+            | ShellError::TestIntegerExpected { .. }
+            | ShellError::TestTooManyArguments { .. } => EXIT_SYNTAX_ERROR,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
 pub type ShellResult<T> = Result<T, ShellError>;
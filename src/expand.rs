@@ -0,0 +1,693 @@
+use std::env;
+use std::ffi::{CStr, CString};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::error::{ShellError, ShellResult};
+use crate::glob;
+use crate::options::ShellOption;
+use crate::parser::{VariableLookup, is_valid_name};
+
+/// Parameters named by a single punctuation character, as in `$?`.
+const SPECIAL_PARAMETERS: &str = "?@*#";
+/// Characters a backslash escapes inside double quotes.
+const DOUBLE_QUOTE_ESCAPES: &str = "\"\\$";
+/// Characters a backslash escapes in a here-document body.
+const HEREDOC_ESCAPES: &str = "\\$`";
+const GLOB_CHARS: &[char] = &['*', '?', '['];
+/// Field separators when `$IFS` is unset.
+const DEFAULT_IFS: &str = " \t\n";
+
+/// Expands a leading `~` or `~user` prefix into a home directory.
+///
+/// `~` and `~/...` use `$HOME`; `~user` and `~user/...` look the user up in
+/// the passwd database. `~+` and `~-` are `$PWD` and `$OLDPWD`. Unknown
+/// users, unset variables and embedded tildes are left untouched.
+pub fn expand_tilde(word: &str) -> String {
+    let Some(rest) = word.strip_prefix('~') else {
+        return word.to_string();
+    };
+
+    let (user, suffix) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+
+    let home = match user {
+        "" => env::var("HOME").ok(),
+        "+" => env::var("PWD").ok(),
+        "-" => env::var("OLDPWD").ok(),
+        _ => lookup_user_home(user),
+    };
+
+    match home {
+        Some(home) => format!("{}{}", home, suffix),
+        None => word.to_string(),
+    }
+}
+
+/// Whether `word`, as typed, starts with an unquoted `~` or `~/` that has
+/// no home directory to expand to because `$HOME` is unset or empty.
+pub fn tilde_lacks_home(word: &str) -> bool {
+    (word == "~" || word.starts_with("~/"))
+        && env::var_os("HOME").is_none_or(|home| home.is_empty())
+}
+
+fn lookup_user_home(user: &str) -> Option<String> {
+    let name = CString::new(user).ok()?;
+    // SAFETY: passwd is plain old data, for which all zero bytes (null
+    // pointers and zero ids) is a valid value.
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let mut buffer = vec![0 as libc::c_char; 4096];
+
+    // SAFETY: all pointers refer to live, correctly sized buffers owned by this frame.
+    let status = unsafe {
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+
+    if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+        return None;
+    }
+
+    // SAFETY: pw_dir points into `buffer`, which getpwnam_r NUL-terminated.
+    let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    dir.to_str().ok().map(|s| s.to_string())
+}
+
+/// Splits `$CDPATH` into the directories `cd` searches for relative paths.
+/// Empty entries stand for the current directory, as in bash.
+pub fn cdpath_dirs() -> Vec<String> {
+    let Ok(cdpath) = env::var("CDPATH") else {
+        return Vec::new();
+    };
+
+    cdpath
+        .split(':')
+        .map(|dir| if dir.is_empty() { "." } else { dir }.to_string())
+        .collect()
+}
+
+/// Where a character of a partly expanded word came from, which decides
+/// what the later expansions may do with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Origin {
+    /// Typed unquoted: may be globbed but never splits the word.
+    Literal,
+    /// Quoted or escaped: neither split nor globbed.
+    Quoted,
+    /// Produced by an unquoted `$` expansion: split on `$IFS` and globbed.
+    Expanded,
+}
+
+/// A word between expansion and quote removal.
+#[derive(Debug, Clone, Default)]
+struct Field {
+    chars: Vec<(char, Origin)>,
+    /// Set once the word has quotes, which keep it as an argument even if
+    /// it expands to nothing, as `""` does.
+    quoted: bool,
+}
+
+impl Field {
+    fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn push_text(field: &mut Field, text: &str, origin: Origin) {
+    field.chars.extend(text.chars().map(|c| (c, origin)));
+}
+
+/// State shared by the expansions of one command's words.
+pub struct ExpandContext<'a> {
+    lookup: &'a dyn VariableLookup,
+    substitution_status: Option<i32>,
+}
+
+impl<'a> ExpandContext<'a> {
+    pub fn new(lookup: &'a dyn VariableLookup) -> Self {
+        Self {
+            lookup,
+            substitution_status: None,
+        }
+    }
+
+    /// The exit status of the last `$(...)` expanded, if there was one.
+    pub fn substitution_status(&self) -> Option<i32> {
+        self.substitution_status
+    }
+
+    /// Runs tilde, parameter and command expansion over one word. Usually
+    /// this yields one field, but `$@` starts a new field per argument.
+    fn expand_word(&mut self, word: &str) -> Vec<Field> {
+        let mut fields = vec![Field::new()];
+        let mut chars = match tilde_prefix(word) {
+            Some((home, rest)) => {
+                push_text(&mut fields[0], &home, Origin::Quoted);
+                rest.chars().peekable()
+            }
+            None => word.chars().peekable(),
+        };
+
+        while let Some(c) = chars.next() {
+            let field = fields.last_mut().expect("at least one field");
+            match c {
+                '\\' => field
+                    .chars
+                    .extend(chars.next().map(|c| (c, Origin::Quoted))),
+                '\'' => {
+                    field.quoted = true;
+                    for c in chars.by_ref().take_while(|&c| c != '\'') {
+                        field.chars.push((c, Origin::Quoted));
+                    }
+                }
+                '"' => {
+                    field.quoted = true;
+                    self.expand_double_quoted(&mut chars, &mut fields);
+                }
+                '$' => self.expand_dollar(&mut chars, &mut fields, false),
+                _ => field.chars.push((c, Origin::Literal)),
+            }
+        }
+
+        fields
+    }
+
+    /// Expands a word that is neither split nor globbed, such as the text
+    /// of a here-string. The fields `$@` makes are joined with spaces.
+    pub fn expand_text(&mut self, word: &str) -> String {
+        let words: Vec<String> = self.expand_word(word).iter().map(remove_quotes).collect();
+        words.join(" ")
+    }
+
+    /// Runs brace expansion, the expansions of `expand_word`, field
+    /// splitting and, unless `set -f` is on, pathname expansion over a word.
+    fn expand_fields(&mut self, word: &str) -> Vec<Field> {
+        let globstar = self.lookup.get_option(ShellOption::Globstar);
+        let noglob = self.lookup.get_option(ShellOption::Noglob);
+        let ifs = self
+            .lookup
+            .get_variable("IFS")
+            .unwrap_or_else(|| DEFAULT_IFS.to_string());
+
+        let mut fields = Vec::new();
+        for word in expand_braces(word) {
+            for field in self.expand_word(&word) {
+                for field in split_field(field, &ifs) {
+                    if noglob {
+                        fields.push(field);
+                    } else {
+                        fields.extend(expand_pathnames(field, globstar));
+                    }
+                }
+            }
+        }
+        fields
+    }
+
+    /// Expands the body of a here-document whose delimiter was unquoted.
+    /// `$` expansions run as inside double quotes and a backslash escapes
+    /// `$`, `` ` ``, `\` or a newline, but quotes are ordinary text.
+    pub fn expand_heredoc(&mut self, body: &str) -> String {
+        let mut fields = vec![Field::new()];
+        let mut chars = body.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if chars.next_if_eq(&'\n').is_some() => {}
+                '\\' => {
+                    let escaped = chars.next_if(|&c| HEREDOC_ESCAPES.contains(c));
+                    let field = fields.last_mut().expect("at least one field");
+                    field.chars.push((escaped.unwrap_or(c), Origin::Quoted));
+                }
+                '$' => self.expand_dollar(&mut chars, &mut fields, true),
+                _ => fields
+                    .last_mut()
+                    .expect("at least one field")
+                    .chars
+                    .push((c, Origin::Quoted)),
+            }
+        }
+
+        // `$@` splits into fields even when quoted; a body joins them back
+        let words: Vec<String> = fields.iter().map(remove_quotes).collect();
+        words.join(" ")
+    }
+
+    /// Expands the inside of a `"..."` after its opening quote.
+    fn expand_double_quoted(&mut self, chars: &mut Peekable<Chars>, fields: &mut Vec<Field>) {
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return,
+                '\\' => {
+                    let escaped = chars.next_if(|&c| DOUBLE_QUOTE_ESCAPES.contains(c));
+                    let field = fields.last_mut().expect("at least one field");
+                    field.chars.push((escaped.unwrap_or(c), Origin::Quoted));
+                }
+                '$' => self.expand_dollar(chars, fields, true),
+                _ => fields
+                    .last_mut()
+                    .expect("at least one field")
+                    .chars
+                    .push((c, Origin::Quoted)),
+            }
+        }
+    }
+
+    /// Expands `$NAME`, `${NAME}`, a special parameter or `$(command)`
+    /// after the `$` has been consumed. Unset variables expand to nothing;
+    /// a `$` not followed by a name, or an unclosed `${` or `$(`, is kept.
+    fn expand_dollar(
+        &mut self,
+        chars: &mut Peekable<Chars>,
+        fields: &mut Vec<Field>,
+        quoted: bool,
+    ) {
+        let literal = if quoted {
+            Origin::Quoted
+        } else {
+            Origin::Literal
+        };
+        let origin = if quoted {
+            Origin::Quoted
+        } else {
+            Origin::Expanded
+        };
+        let field = fields.last_mut().expect("at least one field");
+
+        let name = match chars.peek() {
+            Some('(') => {
+                chars.next();
+                let (command, closed) = read_substitution(chars);
+                let result = if closed {
+                    self.lookup.substitute_command(&command)
+                } else {
+                    None
+                };
+                match result {
+                    Some((output, status)) => {
+                        push_text(field, output.trim_end_matches('\n'), origin);
+                        self.substitution_status = Some(status);
+                    }
+                    None => {
+                        let close = if closed { ")" } else { "" };
+                        push_text(field, &format!("$({}{}", command, close), literal);
+                    }
+                }
+                return;
+            }
+            Some('{') => {
+                chars.next();
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if !closed {
+                    push_text(field, &format!("${{{}", name), literal);
+                    return;
+                }
+                name
+            }
+            Some(&c) if SPECIAL_PARAMETERS.contains(c) || c.is_ascii_digit() => {
+                chars.next();
+                c.to_string()
+            }
+            Some(&c) if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|&c| c.is_ascii_alphanumeric() || c == '_') {
+                    name.push(c);
+                }
+                name
+            }
+            _ => {
+                field.chars.push(('$', literal));
+                return;
+            }
+        };
+
+        match name.as_str() {
+            "@" | "*" => self.expand_positional_args(fields, name == "*", quoted),
+            _ => {
+                if let Some(value) = self.lookup.get_variable(&name) {
+                    push_text(field, &value, origin);
+                }
+            }
+        }
+    }
+
+    /// Expands `$@` and `$*`. Each positional parameter becomes its own
+    /// field, except in `"$*"`, which joins them with the first character
+    /// of `$IFS`.
+    fn expand_positional_args(&self, fields: &mut Vec<Field>, star: bool, quoted: bool) {
+        let args = self.lookup.get_positional_args();
+
+        if star && quoted {
+            let separator = match self.lookup.get_variable("IFS") {
+                Some(ifs) => ifs.chars().next().map(String::from).unwrap_or_default(),
+                None => " ".to_string(),
+            };
+            let field = fields.last_mut().expect("at least one field");
+            push_text(field, &args.join(&separator), Origin::Quoted);
+            return;
+        }
+
+        // Unlike `""`, a `"$@"` without arguments leaves no word behind
+        let field = fields.last_mut().expect("at least one field");
+        if args.is_empty() && field.chars.is_empty() {
+            field.quoted = false;
+        }
+
+        let origin = if quoted {
+            Origin::Quoted
+        } else {
+            Origin::Expanded
+        };
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                fields.push(Field {
+                    chars: Vec::new(),
+                    quoted,
+                });
+            }
+            let field = fields.last_mut().expect("at least one field");
+            push_text(field, arg, origin);
+        }
+    }
+}
+
+/// Reads the body of a `$(...)` after its `$(`, returning it and whether
+/// the closing `)` was found. Quoted and escaped parentheses are skipped.
+fn read_substitution(chars: &mut Peekable<Chars>) -> (String, bool) {
+    let mut command = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('"') | None, '\\') => {
+                command.push(c);
+                command.extend(chars.next());
+                continue;
+            }
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => return (command, true),
+            (None, ')') => depth -= 1,
+            (None, _) => {}
+        }
+        command.push(c);
+    }
+
+    (command, false)
+}
+
+/// Expands a command's words, still quoted as typed, into its arguments.
+///
+/// The expansions run in POSIX order: brace expansion, tilde expansion,
+/// parameter and command substitution, field splitting on `$IFS`,
+/// pathname expansion (unless `set -f` is on) and finally quote removal.
+/// Only unquoted results of
+/// `$` expansions are split, and quoted wildcards never glob. Leading
+/// `NAME=value` words are neither brace expanded, split nor globbed.
+/// Unquoted words that expand to nothing are dropped, but quoted ones such
+/// as `""` stay as empty arguments.
+pub fn expand_tokens(tokens: &[String], ctx: &mut ExpandContext) -> ShellResult<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut in_assignments = true;
+
+    for token in tokens {
+        in_assignments &= is_assignment_word(token);
+        if in_assignments {
+            fields.extend(ctx.expand_word(token));
+        } else {
+            fields.append(&mut ctx.expand_fields(token));
+        }
+    }
+
+    // Quoting is only dropped now that no step needs to know about it
+    Ok(fields.iter().map(remove_quotes).collect())
+}
+
+/// Expands the word naming a redirect's file, which must stay one word.
+pub fn expand_redirect_target(word: &str, ctx: &mut ExpandContext) -> ShellResult<String> {
+    match &ctx.expand_fields(word)[..] {
+        [field] => Ok(remove_quotes(field)),
+        _ => Err(ShellError::AmbiguousRedirect {
+            word: word.to_string(),
+        }),
+    }
+}
+
+/// Whether a raw word is a `NAME=value` assignment. The name must be typed
+/// unquoted, so quoting any of it makes the word an ordinary argument.
+fn is_assignment_word(word: &str) -> bool {
+    word.split_once('=')
+        .is_some_and(|(name, _)| is_valid_name(name))
+}
+
+/// Expands the first brace expression, either a list with an unquoted,
+/// top-level comma such as `{a,b}` or a sequence such as `{1..5}`, then
+/// recurses on each result. Other braces, unclosed braces, quoted braces
+/// and `${...}` are left alone.
+fn expand_braces(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let Some((open, alternatives, close)) = find_brace(&chars) else {
+        return vec![word.to_string()];
+    };
+
+    let prefix: String = chars[..open].iter().collect();
+    let suffix: String = chars[close + 1..].iter().collect();
+
+    alternatives
+        .iter()
+        .flat_map(|alternative| expand_braces(&format!("{}{}{}", prefix, alternative, suffix)))
+        .collect()
+}
+
+/// Finds the first `{` that closes and holds a list or sequence, returning
+/// the positions of the `{` and its `}` around the alternatives it yields.
+fn find_brace(chars: &[char]) -> Option<(usize, Vec<String>, usize)> {
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' | '\'' | '"' => i = skip_quoted(chars, i),
+            '$' if matches!(chars.get(i + 1), Some('{' | '(')) => {
+                i = skip_group(chars, i + 1);
+            }
+            '{' => {
+                if let Some((alternatives, close)) = match_brace(chars, i) {
+                    return Some((i, alternatives, close));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Scans from the `{` at `open` to its matching `}`, returning the text
+/// between the commas directly inside it, or the numbers of a sequence.
+/// Returns `None` if it never closes or is neither.
+fn match_brace(chars: &[char], open: usize) -> Option<(Vec<String>, usize)> {
+    let mut bounds = vec![open];
+    let mut depth = 0;
+    let mut i = open + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' | '\'' | '"' => i = skip_quoted(chars, i),
+            '{' => depth += 1,
+            ',' if depth == 0 => bounds.push(i),
+            '}' if depth == 0 => {
+                if bounds.len() == 1 {
+                    let body: String = chars[open + 1..i].iter().collect();
+                    return brace_sequence(&body).map(|numbers| (numbers, i));
+                }
+                bounds.push(i);
+                let alternatives = bounds
+                    .windows(2)
+                    .map(|pair| chars[pair[0] + 1..pair[1]].iter().collect())
+                    .collect();
+                return Some((alternatives, i));
+            }
+            '}' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Expands the body of a `{start..end}` or `{start..end..step}` sequence,
+/// counting down when `end` is smaller. A bound written with a leading
+/// zero pads every number to the width of the wider bound.
+fn brace_sequence(body: &str) -> Option<Vec<String>> {
+    let parts: Vec<&str> = body.split("..").collect();
+    let (start, end, step) = match parts[..] {
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step.parse::<i64>().ok()?.unsigned_abs()),
+        _ => return None,
+    };
+    let first: i64 = start.parse().ok()?;
+    let last: i64 = end.parse().ok()?;
+    let step = usize::try_from(step.max(1)).ok()?;
+
+    let zero_padded = |bound: &str| {
+        let digits = bound.trim_start_matches('-');
+        digits.len() > 1 && digits.starts_with('0')
+    };
+    let width = if zero_padded(start) || zero_padded(end) {
+        start.len().max(end.len())
+    } else {
+        0
+    };
+
+    let numbers: Vec<i64> = if first <= last {
+        (first..=last).step_by(step).collect()
+    } else {
+        (last..=first).rev().step_by(step).collect()
+    };
+    Some(
+        numbers
+            .iter()
+            .map(|n| format!("{:0width$}", n, width = width))
+            .collect(),
+    )
+}
+
+/// Given the index of a backslash or opening quote, returns the index of
+/// the last character it covers.
+fn skip_quoted(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    if quote == '\\' {
+        return start + 1;
+    }
+
+    let mut i = start + 1;
+    while i < chars.len() && chars[i] != quote {
+        if quote == '"' && chars[i] == '\\' {
+            i += 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Given the index of the `{` or `(` after a `$`, returns the index of its
+/// matching close.
+fn skip_group(chars: &[char], start: usize) -> usize {
+    let (open, close) = if chars[start] == '{' {
+        ('{', '}')
+    } else {
+        ('(', ')')
+    };
+    let mut depth = 0;
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' | '\'' | '"' => i = skip_quoted(chars, i),
+            c if c == close && depth == 0 => return i,
+            c if c == close => depth -= 1,
+            c if c == open => depth += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Splits off an unquoted `~`, `~user`, `~+` or `~-` prefix that names a
+/// directory, returning the directory and the rest of the word.
+fn tilde_prefix(word: &str) -> Option<(String, &str)> {
+    let rest = word.strip_prefix('~')?;
+    let end = rest.find('/').unwrap_or(rest.len());
+    let user = &rest[..end];
+    if !user
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c))
+    {
+        return None;
+    }
+
+    let prefix = &word[..end + 1];
+    let home = expand_tilde(prefix);
+    (home != prefix).then(|| (home, &rest[end..]))
+}
+
+/// Splits a field wherever an unquoted expansion produced a `$IFS`
+/// character. Empty pieces are dropped, unless quotes in the word leave
+/// nothing else.
+fn split_field(field: Field, ifs: &str) -> Vec<Field> {
+    let mut fields = Vec::new();
+    let mut current = Field::new();
+
+    for (c, origin) in field.chars {
+        if origin == Origin::Expanded && ifs.contains(c) {
+            if !current.chars.is_empty() {
+                fields.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.chars.push((c, origin));
+        }
+    }
+
+    if !current.chars.is_empty() || (fields.is_empty() && field.quoted) {
+        fields.push(current);
+    }
+    fields
+}
+
+/// Replaces a field holding unquoted wildcards with the paths it matches,
+/// which are quoted so nothing expands them again. A field without
+/// wildcards, or whose pattern matches nothing, is kept as it is.
+fn expand_pathnames(field: Field, globstar: bool) -> Vec<Field> {
+    let has_glob = field
+        .chars
+        .iter()
+        .any(|&(c, origin)| origin != Origin::Quoted && GLOB_CHARS.contains(&c));
+    if !has_glob {
+        return vec![field];
+    }
+
+    let mut pattern = String::new();
+    for &(c, origin) in &field.chars {
+        if origin == Origin::Quoted && (GLOB_CHARS.contains(&c) || c == '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+
+    let matches = glob::expand(&pattern, globstar);
+    if matches.is_empty() {
+        return vec![field];
+    }
+    matches
+        .iter()
+        .map(|path| {
+            let mut field = Field::new();
+            push_text(&mut field, path, Origin::Quoted);
+            field
+        })
+        .collect()
+}
+
+/// The last expansion: drops what was quoted, leaving the plain text.
+fn remove_quotes(field: &Field) -> String {
+    field.chars.iter().map(|&(c, _)| c).collect()
+}
@@ -1,9 +1,21 @@
-use std::fs::{File, OpenOptions};
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use crate::error::{ShellError, ShellResult};
 
-pub fn open_file(path: &Path, append: bool) -> ShellResult<File> {
+/// Opens `path` for writing, creating it if needed. With `noclobber`, an
+/// existing regular file is an error instead of being truncated.
+pub fn open_file(path: &Path, append: bool, noclobber: bool) -> ShellResult<File> {
+    if noclobber && !append && fs::metadata(path).is_ok_and(|meta| meta.is_file()) {
+        return Err(ShellError::CannotOverwrite {
+            path: path.display().to_string(),
+        });
+    }
+
     OpenOptions::new()
         .create(true)
         .write(true)
@@ -15,3 +27,123 @@ pub fn open_file(path: &Path, append: bool) -> ShellResult<File> {
             source: e,
         })
 }
+
+pub fn open_input_file(path: &Path) -> ShellResult<File> {
+    File::open(path).map_err(|e| ShellError::FileOpen {
+        path: path.display().to_string(),
+        source: e,
+    })
+}
+
+/// Opens a file for writing while holding an exclusive advisory lock.
+///
+/// Truncation is deferred until the lock is held so concurrent writers
+/// cannot clobber each other's output. The lock is released when the
+/// returned file is closed.
+pub fn open_locked_file(path: &Path, append: bool) -> ShellResult<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .open(path)
+        .map_err(|e| ShellError::FileOpen {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+
+    // SAFETY: the descriptor is owned by `file` and stays open for the call.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    if !append {
+        file.set_len(0)?;
+    }
+
+    Ok(file)
+}
+
+/// What an output redirect writes to once opened.
+pub enum OutputFile {
+    Disk(File),
+    Memory(MemoryFile),
+}
+
+/// Opens the files that `>` and `>>` redirects write to. The executor goes
+/// through this trait so tests can keep redirected output in memory; the
+/// default opens real files with `open_file`.
+pub trait FileSystem {
+    fn open_file(&self, path: &Path, append: bool, noclobber: bool) -> ShellResult<OutputFile> {
+        open_file(path, append, noclobber).map(OutputFile::Disk)
+    }
+}
+
+/// The real filesystem.
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {}
+
+type MemoryFiles = Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>;
+
+/// Files kept in memory by path, so redirect tests never touch the disk.
+/// Clones share the same files.
+#[derive(Clone, Default)]
+pub struct MemoryFileSystem {
+    files: MemoryFiles,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns what has been written to `path`, or `None` if no redirect
+    /// ever opened it.
+    pub fn read(&self, path: impl AsRef<Path>) -> Option<String> {
+        let files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .get(path.as_ref())
+            .map(|contents| String::from_utf8_lossy(contents).into_owned())
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn open_file(&self, path: &Path, append: bool, noclobber: bool) -> ShellResult<OutputFile> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        if noclobber && !append && files.contains_key(path) {
+            return Err(ShellError::CannotOverwrite {
+                path: path.display().to_string(),
+            });
+        }
+        let contents = files.entry(path.to_path_buf()).or_default();
+        if !append {
+            contents.clear();
+        }
+        Ok(OutputFile::Memory(MemoryFile {
+            files: Arc::clone(&self.files),
+            path: path.to_path_buf(),
+        }))
+    }
+}
+
+/// A handle that appends to one file of a `MemoryFileSystem`.
+#[derive(Clone)]
+pub struct MemoryFile {
+    files: MemoryFiles,
+    path: PathBuf,
+}
+
+impl Write for MemoryFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut files = self.files.lock().unwrap_or_else(PoisonError::into_inner);
+        files
+            .entry(self.path.clone())
+            .or_default()
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
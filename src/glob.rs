@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::Path;
+
+/// One element of a compiled pathname pattern.
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(char),
+    /// `?`: any single character.
+    Any,
+    /// `*`: any run of characters, including none.
+    Star,
+    /// `[...]`: one character from (or, if negated, not from) the ranges.
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// A single `/`-free component of a glob pattern, such as `*.txt`.
+struct Pattern {
+    pieces: Vec<Piece>,
+}
+
+impl Pattern {
+    /// Compiles a component. A backslash makes the next character literal,
+    /// and a `[` without a closing `]` is an ordinary character.
+    fn new(component: &str) -> Self {
+        let chars: Vec<char> = component.chars().collect();
+        let mut pieces = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    pieces.push(Piece::Literal(chars[i + 1]));
+                    i += 2;
+                }
+                '*' => {
+                    pieces.push(Piece::Star);
+                    i += 1;
+                }
+                '?' => {
+                    pieces.push(Piece::Any);
+                    i += 1;
+                }
+                '[' => match Self::parse_class(&chars, i + 1) {
+                    Some((class, next)) => {
+                        pieces.push(class);
+                        i = next;
+                    }
+                    None => {
+                        pieces.push(Piece::Literal('['));
+                        i += 1;
+                    }
+                },
+                c => {
+                    pieces.push(Piece::Literal(c));
+                    i += 1;
+                }
+            }
+        }
+
+        Self { pieces }
+    }
+
+    /// Parses a bracket expression starting just after its `[`. Returns the
+    /// class and the index after the closing `]`, or `None` if it never closes.
+    fn parse_class(chars: &[char], start: usize) -> Option<(Piece, usize)> {
+        let mut i = start;
+        let negated = matches!(chars.get(i), Some('!' | '^'));
+        if negated {
+            i += 1;
+        }
+
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let mut c = *chars.get(i)?;
+            if c == ']' && !first {
+                return Some((Piece::Class { negated, ranges }, i + 1));
+            }
+            if c == '\\' {
+                i += 1;
+                c = *chars.get(i)?;
+            }
+            first = false;
+            i += 1;
+
+            match (chars.get(i), chars.get(i + 1)) {
+                (Some('-'), Some(&end)) if end != ']' => {
+                    ranges.push((c, end));
+                    i += 2;
+                }
+                _ => ranges.push((c, c)),
+            }
+        }
+    }
+
+    fn is_literal(&self) -> bool {
+        self.pieces
+            .iter()
+            .all(|piece| matches!(piece, Piece::Literal(_)))
+    }
+
+    /// The component with its escapes removed, for when it has no wildcards.
+    fn literal_text(&self) -> String {
+        self.pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Literal(c) => Some(*c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Hidden names only match a pattern that itself starts with a literal `.`.
+    fn matches_hidden(&self) -> bool {
+        self.pieces.first() == Some(&Piece::Literal('.'))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        if name.starts_with('.') && !self.matches_hidden() {
+            return false;
+        }
+
+        let name: Vec<char> = name.chars().collect();
+        let (mut p, mut n) = (0, 0);
+        // Where to resume after the most recent `*` if a later piece fails
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while n < name.len() {
+            match self.pieces.get(p) {
+                Some(Piece::Star) => {
+                    backtrack = Some((p, n));
+                    p += 1;
+                    continue;
+                }
+                Some(piece) if piece_matches(piece, name[n]) => {
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let Some((star, resume)) = backtrack else {
+                return false;
+            };
+            p = star + 1;
+            n = resume + 1;
+            backtrack = Some((star, resume + 1));
+        }
+
+        self.pieces[p..].iter().all(|piece| *piece == Piece::Star)
+    }
+}
+
+fn piece_matches(piece: &Piece, c: char) -> bool {
+    match piece {
+        Piece::Literal(literal) => *literal == c,
+        Piece::Any => true,
+        Piece::Star => false,
+        Piece::Class { negated, ranges } => {
+            ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+        }
+    }
+}
+
+/// Expands a pathname pattern into the paths it matches, sorted lexically.
+///
+/// Wildcards never match a leading `.` unless the pattern component starts
+/// with one, so `*` skips dotfiles while `.*` finds them; `.` and `..` are
+/// never returned. With `globstar`, a `**` component matches any number of
+/// directories, or as the last component every path below. Returns an empty
+/// list when nothing matches.
+pub fn expand(pattern: &str, globstar: bool) -> Vec<String> {
+    let (root, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => ("/".to_string(), rest),
+        None => (String::new(), pattern),
+    };
+
+    let components: Vec<&str> = rest.split('/').collect();
+    let mut paths = vec![root];
+
+    for (index, component) in components.iter().enumerate() {
+        let pattern = Pattern::new(component);
+        let is_last = index + 1 == components.len();
+        let separator = if is_last { "" } else { "/" };
+        let mut next = Vec::new();
+
+        for prefix in &paths {
+            let dir = if prefix.is_empty() { "." } else { prefix };
+
+            if globstar && *component == "**" {
+                // Not last: zero or more directories, each ending in `/`
+                // for the next component. Last: every file and directory.
+                if !is_last {
+                    next.push(prefix.clone());
+                }
+                for (path, is_dir) in descendants(Path::new(dir)) {
+                    if is_last || is_dir {
+                        next.push(format!("{}{}{}", prefix, path, separator));
+                    }
+                }
+                continue;
+            }
+
+            if pattern.is_literal() {
+                next.push(format!("{}{}{}", prefix, pattern.literal_text(), separator));
+                continue;
+            }
+
+            for name in matching_names(Path::new(dir), &pattern) {
+                next.push(format!("{}{}{}", prefix, name, separator));
+            }
+        }
+
+        paths = next;
+    }
+
+    paths.retain(|path| fs::symlink_metadata(path).is_ok());
+    paths.sort();
+    paths
+}
+
+fn matching_names(dir: &Path, pattern: &Pattern) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| pattern.matches(name))
+        .collect()
+}
+
+/// Lists every non-hidden path below `dir` as `(relative path, is_dir)`.
+/// Symbolic links are listed but never descended into, so a link pointing
+/// back up the tree cannot cause a loop.
+fn descendants(dir: &Path) -> Vec<(String, bool)> {
+    let mut found = Vec::new();
+    let mut pending = vec![String::new()];
+
+    while let Some(relative) = pending.pop() {
+        let Ok(entries) = fs::read_dir(dir.join(&relative)) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let path = format!("{}{}", relative, name);
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir {
+                pending.push(format!("{}/", path));
+            }
+            found.push((path, is_dir));
+        }
+    }
+
+    found
+}
@@ -0,0 +1,469 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+const JOB_STATE_WIDTH: usize = 24;
+const EXIT_SIGNAL_OFFSET: i32 = 128;
+/// How often a wait for a background job checks whether Ctrl-C was pressed.
+const INTERRUPT_POLL: Duration = Duration::from_millis(10);
+
+/// Signal names as `kill` and `trap` accept them, without the `SIG` prefix.
+const SIGNALS: &[(&str, libc::c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("WINCH", libc::SIGWINCH),
+];
+
+/// Parses a signal given as a number or a name such as `TERM`, `SIGTERM`
+/// or `term`. Signal 0 sends nothing but checks that the process exists.
+pub fn parse_signal(spec: &str) -> Option<libc::c_int> {
+    if let Ok(number) = spec.parse() {
+        let known = number == 0 || SIGNALS.iter().any(|&(_, signal)| signal == number);
+        return known.then_some(number);
+    }
+
+    let name = spec.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    SIGNALS
+        .iter()
+        .find(|&&(candidate, _)| candidate == name)
+        .map(|&(_, signal)| signal)
+}
+
+/// Where to send a signal meant for the job whose process is `pid`: its
+/// whole process group when it has one of its own, as with job control,
+/// otherwise just the process.
+pub fn signal_target(pid: u32) -> libc::pid_t {
+    let pid = pid as libc::pid_t;
+    // SAFETY: getpgid and getpgrp take no pointers.
+    let group = unsafe { libc::getpgid(pid) };
+    if group > 0 && group != unsafe { libc::getpgrp() } {
+        -group
+    } else {
+        pid
+    }
+}
+
+/// Set by the SIGINT handler, and cleared by whoever acts on it.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn note_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Catches SIGINT so Ctrl-C interrupts a long-running builtin instead of
+/// killing the shell. Children get the default action back on exec.
+pub fn catch_interrupts() {
+    // SAFETY: the handler only stores to an atomic, which is
+    // async-signal-safe.
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            note_interrupt as *const () as libc::sighandler_t,
+        );
+    }
+}
+
+/// Returns whether SIGINT arrived since the last call, clearing the flag.
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// Converts a process exit status into a shell `$?` value.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .unwrap_or_else(|| EXIT_SIGNAL_OFFSET + status.signal().unwrap_or(0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobState {
+    Running,
+    /// Suspended by a signal such as SIGTSTP; it stays in the table until
+    /// resumed or killed.
+    Stopped,
+    Done(ExitStatus),
+}
+
+impl JobState {
+    pub fn is_done(&self) -> bool {
+        matches!(self, JobState::Done(_))
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobState::Running => write!(f, "Running"),
+            JobState::Stopped => write!(f, "Stopped"),
+            JobState::Done(status) => match (status.code(), status.signal()) {
+                (Some(0), _) => write!(f, "Done"),
+                (Some(code), _) => write!(f, "Exit {}", code),
+                (None, Some(libc::SIGKILL)) => write!(f, "Killed"),
+                (None, Some(libc::SIGTERM)) => write!(f, "Terminated"),
+                (None, Some(libc::SIGINT)) => write!(f, "Interrupt"),
+                (None, signal) => write!(f, "Signal {}", signal.unwrap_or(0)),
+            },
+        }
+    }
+}
+
+/// How a foreground child gave control back to the shell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaitOutcome {
+    Exited(ExitStatus),
+    /// Stopped by the given signal, still alive and not reaped.
+    Stopped(i32),
+    /// The wait was broken off by Ctrl-C; the child is still running.
+    Interrupted,
+}
+
+impl WaitOutcome {
+    /// The `$?` value for this outcome; bash reports a stop like a death
+    /// by the stopping signal.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            WaitOutcome::Exited(status) => exit_code(status),
+            WaitOutcome::Stopped(signal) => EXIT_SIGNAL_OFFSET + signal,
+            WaitOutcome::Interrupted => EXIT_SIGNAL_OFFSET + libc::SIGINT,
+        }
+    }
+}
+
+/// Waits for a foreground child to exit or be stopped. Unlike
+/// `Child::wait`, this returns when the child is suspended by Ctrl-Z so the
+/// shell can record it as a stopped job. An exited child is reaped here, so
+/// it must not be waited on again.
+pub fn wait_foreground(child: &Child) -> io::Result<WaitOutcome> {
+    let pid = child.id() as libc::pid_t;
+
+    let mut status = 0;
+    loop {
+        // SAFETY: `status` is a valid, writable c_int for waitpid to fill in.
+        if unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) } == pid {
+            if libc::WIFSTOPPED(status) {
+                return Ok(WaitOutcome::Stopped(libc::WSTOPSIG(status)));
+            }
+            return Ok(WaitOutcome::Exited(ExitStatus::from_raw(status)));
+        }
+
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::EINTR) {
+            return Err(error);
+        }
+    }
+}
+
+/// Waits for a background job's process to exit or be stopped. The
+/// process is polled rather than waited on, so Ctrl-C can break off the
+/// wait without reaching the job itself. An exited process is reaped here.
+fn wait_background(pid: u32) -> io::Result<WaitOutcome> {
+    let pid = pid as libc::pid_t;
+
+    // A Ctrl-C from before the wait started should not cut it short
+    take_interrupt();
+    let mut status = 0;
+    loop {
+        // SAFETY: `status` is a valid, writable c_int for waitpid to fill in.
+        match unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED | libc::WNOHANG) } {
+            0 => {}
+            reaped if reaped == pid => {
+                if libc::WIFSTOPPED(status) {
+                    return Ok(WaitOutcome::Stopped(libc::WSTOPSIG(status)));
+                }
+                return Ok(WaitOutcome::Exited(ExitStatus::from_raw(status)));
+            }
+            _ => {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(libc::EINTR) {
+                    return Err(error);
+                }
+            }
+        }
+
+        if take_interrupt() {
+            return Ok(WaitOutcome::Interrupted);
+        }
+        thread::sleep(INTERRUPT_POLL);
+    }
+}
+
+/// Waits for a child process the job table does not know about, such as
+/// one started outside the shell's own job control. Returns `None` if `pid`
+/// is not a child of this process.
+pub fn wait_for_untracked_pid(pid: u32) -> io::Result<Option<ExitStatus>> {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return Ok(None);
+    };
+
+    let mut status = 0;
+    loop {
+        // SAFETY: `status` is a valid, writable c_int for waitpid to fill in.
+        if unsafe { libc::waitpid(pid, &mut status, 0) } == pid {
+            return Ok(Some(ExitStatus::from_raw(status)));
+        }
+
+        let error = io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            Some(libc::ECHILD) => return Ok(None),
+            _ => return Err(error),
+        }
+    }
+}
+
+/// A child process the shell keeps track of after it stops waiting on it.
+struct Job {
+    id: usize,
+    pid: u32,
+    command: String,
+    state: JobState,
+    child: Child,
+}
+
+/// A point-in-time view of a job, formatted like bash's `jobs` output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo {
+    pub id: usize,
+    pub pid: u32,
+    pub command: String,
+    pub state: JobState,
+    /// `+` for the current job, `-` for the previous one, otherwise a space.
+    pub marker: char,
+}
+
+impl fmt::Display for JobInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.to_string();
+        write!(
+            f,
+            "[{}]{}  {:<width$}{}",
+            self.id,
+            self.marker,
+            state,
+            self.command,
+            width = JOB_STATE_WIDTH
+        )?;
+        if self.state == JobState::Running {
+            write!(f, " &")?;
+        }
+        Ok(())
+    }
+}
+
+/// Tracks background children so they can be listed and reaped between prompts.
+pub struct JobTable {
+    jobs: RefCell<Vec<Job>>,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        Self {
+            jobs: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn add(&self, child: Child, command: &str) -> usize {
+        self.add_with_state(child, command, JobState::Running)
+    }
+
+    /// Adds a foreground child that was just stopped, so it is not lost.
+    pub fn add_stopped(&self, child: Child, command: &str) -> usize {
+        self.add_with_state(child, command, JobState::Stopped)
+    }
+
+    fn add_with_state(&self, child: Child, command: &str, state: JobState) -> usize {
+        let mut jobs = self.jobs.borrow_mut();
+        let id = jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+
+        jobs.push(Job {
+            id,
+            pid: child.id(),
+            command: command.to_string(),
+            state,
+            child,
+        });
+
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.borrow().is_empty()
+    }
+
+    /// The ID of the current job, the one most recently started or stopped.
+    pub fn current_id(&self) -> Option<usize> {
+        self.jobs.borrow().last().map(|job| job.id)
+    }
+
+    /// Marks the job with `id` as running or stopped after signalling it.
+    pub fn set_state(&self, id: usize, state: JobState) {
+        if let Some(job) = self.jobs.borrow_mut().iter_mut().find(|job| job.id == id) {
+            job.state = state;
+        }
+    }
+
+    /// Waits for the job with `id` as a foreground command, returning when
+    /// it exits or is stopped again. An exited job leaves the table, and a
+    /// stopped one becomes the current job. Returns `None` for unknown jobs.
+    pub fn wait_in_foreground(&self, id: usize) -> io::Result<Option<WaitOutcome>> {
+        let mut jobs = self.jobs.borrow_mut();
+        let Some(index) = jobs.iter().position(|job| job.id == id) else {
+            return Ok(None);
+        };
+
+        let outcome = wait_foreground(&jobs[index].child)?;
+        let mut job = jobs.remove(index);
+        if let WaitOutcome::Stopped(_) = outcome {
+            job.state = JobState::Stopped;
+            jobs.push(job);
+        }
+        Ok(Some(outcome))
+    }
+
+    /// A view of the job with `id`, if there is one, without polling it.
+    pub fn find(&self, id: usize) -> Option<JobInfo> {
+        let jobs = self.jobs.borrow();
+        let index = jobs.iter().position(|job| job.id == id)?;
+        Some(Self::info(&jobs, index))
+    }
+
+    /// A view of `jobs[index]`, marked by its position from the end.
+    fn info(jobs: &[Job], index: usize) -> JobInfo {
+        let job = &jobs[index];
+        JobInfo {
+            id: job.id,
+            pid: job.pid,
+            command: job.command.clone(),
+            state: job.state,
+            marker: match jobs.len() - index {
+                1 => '+',
+                2 => '-',
+                _ => ' ',
+            },
+        }
+    }
+
+    /// Blocks until the job with `id` exits or is stopped. An exited job
+    /// leaves the table. Returns `None` if no such job exists.
+    pub fn wait_for_id(&self, id: usize) -> io::Result<Option<WaitOutcome>> {
+        self.wait_where(|job| job.id == id)
+    }
+
+    /// Like `wait_for_id`, but looks the job up by process ID.
+    pub fn wait_for_pid(&self, pid: u32) -> io::Result<Option<WaitOutcome>> {
+        self.wait_where(|job| job.pid == pid)
+    }
+
+    /// Blocks until every job exits and removes it from the table. Stopped
+    /// jobs would never finish on their own, so they are left in place.
+    /// Returns the outcome of the last job waited for, stopping early if
+    /// the wait is interrupted.
+    pub fn wait_all(&self) -> io::Result<Option<WaitOutcome>> {
+        let ids: Vec<usize> = self
+            .jobs
+            .borrow()
+            .iter()
+            .filter(|job| job.state != JobState::Stopped)
+            .map(|job| job.id)
+            .collect();
+
+        let mut last = None;
+        for id in ids {
+            last = self.wait_for_id(id)?;
+            if last == Some(WaitOutcome::Interrupted) {
+                break;
+            }
+        }
+        Ok(last)
+    }
+
+    /// Waits for the first job matching `predicate`, without holding the
+    /// table while blocked, then records how it ended.
+    fn wait_where(&self, predicate: impl Fn(&Job) -> bool) -> io::Result<Option<WaitOutcome>> {
+        let Some((id, pid, state)) = self
+            .jobs
+            .borrow()
+            .iter()
+            .find(|job| predicate(job))
+            .map(|job| (job.id, job.pid, job.state))
+        else {
+            return Ok(None);
+        };
+
+        // A job seen finishing by a poll has already been reaped
+        let outcome = match state {
+            JobState::Done(status) => WaitOutcome::Exited(status),
+            _ => wait_background(pid)?,
+        };
+
+        let mut jobs = self.jobs.borrow_mut();
+        match outcome {
+            WaitOutcome::Exited(_) => jobs.retain(|job| job.id != id),
+            WaitOutcome::Stopped(_) => {
+                if let Some(job) = jobs.iter_mut().find(|job| job.id == id) {
+                    job.state = JobState::Stopped;
+                }
+            }
+            WaitOutcome::Interrupted => {}
+        }
+        Ok(Some(outcome))
+    }
+
+    /// Polls every running job without blocking and returns a snapshot of
+    /// the table. Finished jobs appear in one snapshot and are then removed.
+    pub fn snapshot(&self, only_finished: bool) -> io::Result<Vec<JobInfo>> {
+        let mut jobs = self.jobs.borrow_mut();
+
+        for job in jobs.iter_mut() {
+            if !job.state.is_done()
+                && let Some(status) = job.child.try_wait()?
+            {
+                job.state = JobState::Done(status);
+            }
+        }
+
+        let infos = (0..jobs.len())
+            .filter(|&index| !only_finished || jobs[index].state.is_done())
+            .map(|index| Self::info(&jobs, index))
+            .collect();
+
+        jobs.retain(|job| !job.state.is_done());
+
+        Ok(infos)
+    }
+}
+
+impl Default for JobTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::{Display, Write as _};
+
+/// Appends `value` to `out` as a quoted, escaped JSON string.
+pub fn push_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Builds a flat JSON object one field at a time, in insertion order.
+pub struct JsonObject {
+    buffer: String,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::from("{"),
+        }
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> Self {
+        self.push_key(key);
+        push_string(&mut self.buffer, value);
+        self
+    }
+
+    pub fn number(mut self, key: &str, value: impl Display) -> Self {
+        self.push_key(key);
+        let _ = write!(self.buffer, "{}", value);
+        self
+    }
+
+    pub fn finish(mut self) -> String {
+        self.buffer.push('}');
+        self.buffer
+    }
+
+    fn push_key(&mut self, key: &str) {
+        if self.buffer.len() > 1 {
+            self.buffer.push(',');
+        }
+        push_string(&mut self.buffer, key);
+        self.buffer.push(':');
+    }
+}
+
+impl Default for JsonObject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
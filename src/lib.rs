@@ -1,5 +1,13 @@
+pub mod audit;
 pub mod commands;
 pub mod error;
+pub mod expand;
 pub mod files;
+pub mod glob;
+pub mod jobs;
+pub mod json;
+pub mod options;
 pub mod parser;
+pub mod prompt;
 pub mod shell;
+pub mod terminal;
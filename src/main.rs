@@ -1,28 +1,58 @@
-use rustyline::{CompletionType, Config, EditMode, Editor, error::ReadlineError};
+use std::io::{self, IsTerminal};
 
-use commands::{CommandRegistry, ShellExecutor, ShellStatus};
-use shell::Shell;
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Config, EditMode, Editor, error::ReadlineError};
 
-mod commands;
-mod error;
-mod files;
-mod parser;
-mod shell;
+use codecrafters_shell::audit::AuditLog;
+use codecrafters_shell::commands::{CommandRegistry, ShellExecutor, ShellStatus};
+use codecrafters_shell::jobs;
+use codecrafters_shell::options::ShellOption;
+use codecrafters_shell::parser;
+use codecrafters_shell::prompt;
+use codecrafters_shell::shell::Shell;
 
 const EXIT_INITIALIZATION_ERROR: i32 = 1;
 
-const SHELL_PROMPT: &str = "$ ";
+/// Reads more lines, under the continuation prompt, until `line` holds a
+/// complete command. End of input stops early, leaving the parser to
+/// report what was left open.
+fn read_continuation(
+    editor: &mut Editor<Shell, DefaultHistory>,
+    mut line: String,
+) -> rustyline::Result<String> {
+    while !parser::is_complete(&line) {
+        match editor.readline(prompt::CONTINUATION_PROMPT) {
+            Ok(more) => {
+                line.push('\n');
+                line.push_str(&more);
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(line)
+}
 
 fn main() {
     let registry = CommandRegistry::default();
-    let command_names = registry.get_command_names();
-    let helper = Shell::new(command_names);
-    let executor = ShellExecutor::new(&registry);
+    let helper =
+        Shell::from_registry(&registry).with_case_insensitive(Shell::case_insensitive_from_env());
+    let mut executor = ShellExecutor::new(&registry).with_job_control(io::stdin().is_terminal());
+    if let Some(audit_log) = AuditLog::from_env() {
+        executor = executor.with_audit_log(audit_log);
+    }
 
     if let Some(histfile) = CommandRegistry::get_histfile_path() {
         let _ = registry.load_history_from_file(&histfile);
     }
 
+    // Ctrl-Z should stop the foreground command, not the shell itself.
+    // SAFETY: installing SIG_IGN does not run any code in signal context.
+    unsafe {
+        libc::signal(libc::SIGTSTP, libc::SIG_IGN);
+    }
+    jobs::catch_interrupts();
+
     let config = Config::builder()
         .completion_type(CompletionType::List)
         .edit_mode(EditMode::Emacs)
@@ -34,21 +64,56 @@ fn main() {
     });
     editor.set_helper(Some(helper));
 
-    loop {
-        let readline = editor.readline(SHELL_PROMPT);
+    let status = loop {
+        match registry.reap_jobs() {
+            Ok(finished) => {
+                for job in finished {
+                    println!("{}", job);
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+
+        executor.run_prompt_command();
+        let readline = editor
+            .readline(&prompt::current_prompt())
+            .and_then(|line| read_continuation(&mut editor, line));
         match readline {
             Ok(line) => {
-                registry.add_history_entry(&line);
-                editor.add_history_entry(line.as_str()).ok();
+                let line = match parser::expand_history(&line, &registry.get_history()) {
+                    Ok(Some(expanded)) => {
+                        // Show the command that actually runs, as bash does.
+                        println!("{}", expanded);
+                        expanded
+                    }
+                    Ok(None) => line,
+                    Err(e) => {
+                        registry.set_last_status(e.exit_code());
+                        eprintln!("{}", e);
+                        continue;
+                    }
+                };
 
-                let commands = parser::parse_input(line.as_str());
+                if registry.get_option(ShellOption::History) {
+                    registry.add_history_entry(&line);
+                    editor.add_history_entry(line.as_str()).ok();
+                }
+
+                let pipelines = match parser::parse_input(line.as_str()) {
+                    Ok(pipelines) => pipelines,
+                    Err(e) => {
+                        registry.set_last_status(e.exit_code());
+                        eprintln!("{}", e);
+                        continue;
+                    }
+                };
 
-                if commands.is_empty() {
+                if pipelines.is_empty() {
                     continue;
                 }
 
-                match executor.run(&commands) {
-                    Ok(ShellStatus::Exit) => break,
+                match executor.run(&pipelines) {
+                    Ok(ShellStatus::Exit(code)) => break code,
                     Ok(ShellStatus::Continue) => continue,
                     Err(e) => eprintln!("{}", e),
                 }
@@ -59,12 +124,14 @@ fn main() {
             }
             Err(ReadlineError::Eof) => {
                 println!("exit");
-                break;
+                break registry.get_last_status();
             }
             Err(err) => {
                 eprintln!("Error: {:?}", err);
-                break;
+                break registry.get_last_status();
             }
         }
-    }
+    };
+
+    std::process::exit(executor.shutdown(status));
 }
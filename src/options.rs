@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+/// A named on/off shell option, toggled with `set -o NAME` / `set +o NAME`
+/// or, for bash's shopt options, `shopt -s NAME` / `shopt -u NAME`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShellOption {
+    /// Exit as soon as a command fails, unless `&&` or `||` tests it.
+    Errexit,
+    /// Record entered commands in the history list.
+    History,
+    /// Refuse to let `>` overwrite an existing file; `>|` still does.
+    Noclobber,
+    /// Turn off pathname expansion, leaving wildcards as typed.
+    Noglob,
+    /// Print each command to stderr, prefixed with `+`, before running it.
+    Xtrace,
+    /// Let `**` in a glob match any number of directories.
+    Globstar,
+    /// Let `cd` correct small typos in directory names.
+    Cdspell,
+}
+
+impl ShellOption {
+    /// Options managed by `set`.
+    pub const SET: &[ShellOption] = &[
+        ShellOption::Errexit,
+        ShellOption::History,
+        ShellOption::Noclobber,
+        ShellOption::Noglob,
+        ShellOption::Xtrace,
+    ];
+
+    /// Options managed by `shopt`.
+    pub const SHOPT: &[ShellOption] = &[ShellOption::Cdspell, ShellOption::Globstar];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShellOption::Errexit => "errexit",
+            ShellOption::History => "history",
+            ShellOption::Noclobber => "noclobber",
+            ShellOption::Noglob => "noglob",
+            ShellOption::Xtrace => "xtrace",
+            ShellOption::Globstar => "globstar",
+            ShellOption::Cdspell => "cdspell",
+        }
+    }
+
+    /// The `set` option with single-letter flag `flag`, as in `set -e`.
+    pub fn from_flag(flag: char) -> Option<Self> {
+        match flag {
+            'e' => Some(ShellOption::Errexit),
+            'C' => Some(ShellOption::Noclobber),
+            'f' => Some(ShellOption::Noglob),
+            'x' => Some(ShellOption::Xtrace),
+            _ => None,
+        }
+    }
+
+    /// Looks `name` up among `options`, such as `ShellOption::SET`.
+    pub fn from_name(options: &[ShellOption], name: &str) -> Option<Self> {
+        options.iter().copied().find(|option| option.name() == name)
+    }
+}
+
+/// The set of options currently enabled in the shell.
+pub struct ShellOptions {
+    enabled: RefCell<HashSet<ShellOption>>,
+}
+
+impl ShellOptions {
+    pub fn new() -> Self {
+        Self {
+            enabled: RefCell::new(HashSet::from([ShellOption::History])),
+        }
+    }
+
+    pub fn is_enabled(&self, option: ShellOption) -> bool {
+        self.enabled.borrow().contains(&option)
+    }
+
+    pub fn set(&self, option: ShellOption, enabled: bool) {
+        let mut options = self.enabled.borrow_mut();
+        if enabled {
+            options.insert(option);
+        } else {
+            options.remove(&option);
+        }
+    }
+}
+
+impl Default for ShellOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
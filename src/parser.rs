@@ -1,5 +1,3 @@
-const SPECIAL_CHARS: &[&str] = &["\"", "\\"];
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum TokenizerState {
     Normal,
@@ -9,149 +7,694 @@ enum TokenizerState {
     EscapedInDoubleQuote,
 }
 
+impl TokenizerState {
+    /// Describes the construct left open when input ends in this state.
+    fn unterminated(&self) -> Option<&'static str> {
+        match self {
+            TokenizerState::Normal => None,
+            TokenizerState::InSingleQuote => Some("in single-quoted string"),
+            TokenizerState::InDoubleQuote | TokenizerState::EscapedInDoubleQuote => {
+                Some("in double-quoted string")
+            }
+            TokenizerState::Escaped => Some("after backslash"),
+        }
+    }
+}
+
+const STDIN_FD: i32 = 0;
+const STDOUT_FD: i32 = 1;
+const STDERR_FD: i32 = 2;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum RedirectType {
-    StdoutTruncate,
-    StdoutAppend,
-    StderrTruncate,
-    StderrAppend,
+    File(i32, RedirectMode),
+    /// `&>` and `&>>`: stdout to a file, with stderr sharing it.
+    Combined(RedirectMode),
+    Duplicate(i32, i32),
+    HereString,
 }
 
 impl RedirectType {
+    /// Parses an operator such as `>`, `2>>`, `3<` or `2>&1`. A leading
+    /// number picks the descriptor; without one, `<` means stdin and the
+    /// `>` forms mean stdout.
     fn from_operator(op: &str) -> Option<Self> {
         match op {
-            ">" | "1>" => Some(RedirectType::StdoutTruncate),
-            ">>" | "1>>" => Some(RedirectType::StdoutAppend),
-            "2>" => Some(RedirectType::StderrTruncate),
-            "2>>" => Some(RedirectType::StderrAppend),
-            _ => None,
+            "&>" => return Some(RedirectType::Combined(RedirectMode::Truncate)),
+            "&>>" => return Some(RedirectType::Combined(RedirectMode::Append)),
+            HERE_STRING => return Some(RedirectType::HereString),
+            _ => {}
+        }
+
+        let digits = op.len() - op.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, operator) = op.split_at(digits);
+        let fd = |default| match number {
+            "" => Some(default),
+            number => number.parse().ok(),
+        };
+
+        match operator {
+            ">" => Some(RedirectType::File(fd(STDOUT_FD)?, RedirectMode::Truncate)),
+            ">>" => Some(RedirectType::File(fd(STDOUT_FD)?, RedirectMode::Append)),
+            ">|" => Some(RedirectType::File(fd(STDOUT_FD)?, RedirectMode::Clobber)),
+            "<" => Some(RedirectType::File(fd(STDIN_FD)?, RedirectMode::Read)),
+            _ => {
+                let target = operator.strip_prefix(">&")?;
+                if target.is_empty() || !target.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                Some(RedirectType::Duplicate(
+                    fd(STDOUT_FD)?,
+                    target.parse().ok()?,
+                ))
+            }
         }
     }
 }
 
+/// The operator whose following word becomes the command's input.
+const HERE_STRING: &str = "<<<";
+
+use std::env;
+use std::iter::Peekable;
 use std::path::PathBuf;
+use std::str::Chars;
 
-#[derive(Debug)]
+use crate::error::{ShellError, ShellResult};
+use crate::expand::{ExpandContext, expand_redirect_target, expand_tokens, tilde_lacks_home};
+use crate::options::ShellOption;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedirectMode {
+    Read,
+    Truncate,
+    Append,
+    /// `>|`: truncate even when `noclobber` is set.
+    Clobber,
+}
+
+/// A single redirection, applied in the order it appears on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Redirect {
+    /// Connect `fd` to the file at `path`.
+    File {
+        fd: i32,
+        path: PathBuf,
+        mode: RedirectMode,
+    },
+    /// Make `fd` a copy of whatever `target` currently points to, as in `2>&1`.
+    Duplicate { fd: i32, target: i32 },
+}
+
+/// A `<<` here-document. Its body is filled in once the tokenizer reaches
+/// the end of the line holding the operator.
+#[derive(Debug, Clone, PartialEq)]
+struct HereDoc {
+    /// The delimiter with its quotes removed.
+    delimiter: String,
+    /// Set when any of the delimiter was quoted, which turns off expansion.
+    quoted: bool,
+    /// Set for `<<-`, which strips leading tabs from the body lines.
+    strip_tabs: bool,
+    body: String,
+}
+
+/// A lexical token: either a word, still quoted and unexpanded, a control
+/// or redirect operator or a here-document.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// An unquoted redirect operator such as `>`, `2>&1` or `<<<`.
+    Redirect(String),
+    HereDoc(HereDoc),
+    Pipe,
+    /// `|&`, which pipes stderr along with stdout.
+    PipeBoth,
+    Semicolon,
+    And,
+    Or,
+    Background,
+}
+
+impl Token {
+    fn into_text(self) -> String {
+        match self {
+            Token::Word(word) | Token::Redirect(word) => word,
+            Token::HereDoc(doc) => format!("<<{}", doc.delimiter),
+            Token::Pipe => "|".to_string(),
+            Token::PipeBoth => "|&".to_string(),
+            Token::Semicolon => ";".to_string(),
+            Token::And => "&&".to_string(),
+            Token::Or => "||".to_string(),
+            Token::Background => "&".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ParsedCommand {
     pub command: String,
     pub args: Vec<String>,
 
-    pub stdout_redirect: Option<PathBuf>,
-    pub stderr_redirect: Option<PathBuf>,
+    pub redirects: Vec<Redirect>,
+
+    /// Leading `NAME=value` words. They apply only to this command, or to the
+    /// shell itself when `command` is empty.
+    pub env_assignments: Vec<(String, String)>,
+
+    /// Set when the pipeline ends with `&` and should not be waited on.
+    pub background: bool,
+
+    /// The status of the last `$(...)` in this command's words. It becomes
+    /// `$?` when there is no command word, as in `x=$(false)`.
+    pub substitution_status: Option<i32>,
+
+    /// The expanded body of the last `<<` here-document, or the text of a
+    /// `<<<` here-string plus a newline, fed to the command as its standard
+    /// input.
+    pub heredoc: Option<String>,
+}
+
+/// How a pipeline is joined to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connector {
+    /// First pipeline or after `;`: always runs.
+    Always,
+    /// After `&&`: runs only if the previous status was zero.
+    And,
+    /// After `||`: runs only if the previous status was non-zero.
+    Or,
+}
+
+/// A redirect as parsed, before its target word is expanded.
+#[derive(Debug, Clone)]
+enum RawRedirect {
+    File {
+        fd: i32,
+        mode: RedirectMode,
+        target: String,
+    },
+    /// `&>` and `&>>`, which also point stderr at the file.
+    Combined {
+        mode: RedirectMode,
+        target: String,
+    },
+    Duplicate {
+        fd: i32,
+        target: i32,
+    },
+    HereString(String),
+    HereDoc(HereDoc),
+}
+
+/// One command as typed, split into its leading assignments, words and
+/// redirects, with every value and word still quoted and unexpanded.
+#[derive(Debug, Clone, Default)]
+struct RawCommand {
+    assignments: Vec<(String, String)>,
+    words: Vec<String>,
+    redirects: Vec<RawRedirect>,
+}
+
+impl RawCommand {
+    /// Sorts a command's tokens into words and redirects. Only operators
+    /// the tokenizer found unquoted count, so `'>'` and `\>` are words.
+    /// Control operators are kept as words too. Assignments are told apart
+    /// the same way, so `"A=1"` is a word. Redirects may come anywhere,
+    /// even before the command word; one without a target is a syntax error.
+    fn parse(tokens: &[Token]) -> ShellResult<Self> {
+        let mut command = RawCommand::default();
+        let mut iter = tokens.iter();
+
+        while let Some(token) = iter.next() {
+            let operator = match token {
+                Token::Redirect(operator) => operator,
+                Token::HereDoc(doc) => {
+                    command.redirects.push(RawRedirect::HereDoc(doc.clone()));
+                    continue;
+                }
+                Token::Word(word) if command.words.is_empty() => {
+                    match parse_assignment(word) {
+                        Some(assignment) => command.assignments.push(assignment),
+                        None => command.words.push(word.clone()),
+                    }
+                    continue;
+                }
+                _ => {
+                    command.words.push(token.clone().into_text());
+                    continue;
+                }
+            };
+
+            let redirect_type = RedirectType::from_operator(operator)
+                .ok_or_else(|| ShellError::SyntaxError(operator.clone()))?;
+            if let RedirectType::Duplicate(fd, target) = redirect_type {
+                command
+                    .redirects
+                    .push(RawRedirect::Duplicate { fd, target });
+                continue;
+            }
+
+            let target = match iter.next() {
+                Some(Token::Word(word)) => word.clone(),
+                Some(other) => return Err(ShellError::SyntaxError(other.clone().into_text())),
+                None => return Err(ShellError::SyntaxError("newline".to_string())),
+            };
+            command.redirects.push(match redirect_type {
+                RedirectType::File(fd, mode) => RawRedirect::File { fd, mode, target },
+                RedirectType::Combined(mode) => RawRedirect::Combined { mode, target },
+                RedirectType::Duplicate(fd, target) => RawRedirect::Duplicate { fd, target },
+                RedirectType::HereString => RawRedirect::HereString(target),
+            });
+        }
+
+        Ok(command)
+    }
+
+    /// Expands the assignment values, the words and then the redirects, in
+    /// order, into a command ready to run.
+    fn expand(&self, ctx: &mut ExpandContext) -> ShellResult<ParsedCommand> {
+        let env_assignments = self
+            .assignments
+            .iter()
+            .map(|(name, value)| (name.clone(), ctx.expand_text(value)))
+            .collect();
+        let mut words = expand_tokens(&self.words, ctx)?.into_iter();
+        let mut command = ParsedCommand {
+            command: words.next().unwrap_or_default(),
+            args: words.collect(),
+            redirects: Vec::new(),
+            env_assignments,
+            background: false,
+            substitution_status: None,
+            heredoc: None,
+        };
 
-    pub stdout_redirect_append: bool,
-    pub stderr_redirect_append: bool,
+        // `cd ~` has nowhere to go without `$HOME`. Only the word as typed
+        // tells it apart from a quoted `"~"`, which names a directory.
+        if command.command == "cd" && self.words.get(1).is_some_and(|word| tilde_lacks_home(word)) {
+            return Err(ShellError::HomeNotSet);
+        }
+
+        for redirect in &self.redirects {
+            match redirect {
+                RawRedirect::File { fd, mode, target } => {
+                    command.redirects.push(Redirect::File {
+                        fd: *fd,
+                        path: PathBuf::from(expand_redirect_target(target, ctx)?),
+                        mode: *mode,
+                    });
+                }
+                // Opened once for stdout, then shared so the two interleave
+                RawRedirect::Combined { mode, target } => {
+                    command.redirects.push(Redirect::File {
+                        fd: STDOUT_FD,
+                        path: PathBuf::from(expand_redirect_target(target, ctx)?),
+                        mode: *mode,
+                    });
+                    command.redirects.push(Redirect::Duplicate {
+                        fd: STDERR_FD,
+                        target: STDOUT_FD,
+                    });
+                }
+                RawRedirect::Duplicate { fd, target } => {
+                    command.redirects.push(Redirect::Duplicate {
+                        fd: *fd,
+                        target: *target,
+                    });
+                }
+                RawRedirect::HereString(text) => {
+                    command.heredoc = Some(format!("{}\n", ctx.expand_text(text)));
+                }
+                RawRedirect::HereDoc(doc) => {
+                    command.heredoc = Some(if doc.quoted {
+                        doc.body.clone()
+                    } else {
+                        ctx.expand_heredoc(&doc.body)
+                    });
+                }
+            }
+        }
+
+        command.substitution_status = ctx.substitution_status();
+        Ok(command)
+    }
+}
+
+/// Commands connected by `|`, each feeding its output to the next. They
+/// are kept unexpanded until the pipeline is about to run, so that earlier
+/// pipelines on the same line can set what they expand to.
+#[derive(Debug)]
+pub struct Pipeline {
+    commands: Vec<RawCommand>,
+    background: bool,
+    pub connector: Connector,
+}
+
+impl Pipeline {
+    /// Expands each command's words and redirects through `lookup`.
+    pub fn expand(&self, lookup: &dyn VariableLookup) -> ShellResult<Vec<ParsedCommand>> {
+        self.commands
+            .iter()
+            .map(|command| {
+                let mut command = command.expand(&mut ExpandContext::new(lookup))?;
+                command.background = self.background;
+                Ok(command)
+            })
+            .collect()
+    }
+}
+
+/// Returns true if `name` is a valid shell variable name: a letter or `_`
+/// followed by letters, digits or `_`.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses input into pipelines separated by `;`, `&&` or `||`. Words are
+/// left for `Pipeline::expand`.
+pub fn parse_input(input: &str) -> ShellResult<Vec<Pipeline>> {
+    let tokens = Tokenizer::new(input).try_tokenize()?;
+    let mut pipelines: Vec<Pipeline> = Vec::new();
+    let mut segment: Vec<Token> = Vec::new();
+    let mut connector = Connector::Always;
+
+    for token in tokens {
+        let next_connector = match token {
+            Token::Semicolon | Token::Background => Connector::Always,
+            Token::And => Connector::And,
+            Token::Or => Connector::Or,
+            _ => {
+                segment.push(token);
+                continue;
+            }
+        };
+
+        let commands = parse_pipeline(&segment)?;
+        if !commands.is_empty() {
+            pipelines.push(Pipeline {
+                commands,
+                background: token == Token::Background,
+                connector,
+            });
+        }
+        segment.clear();
+        connector = next_connector;
+    }
+
+    let commands = parse_pipeline(&segment)?;
+    if !commands.is_empty() {
+        pipelines.push(Pipeline {
+            commands,
+            background: false,
+            connector,
+        });
+    }
+
+    Ok(pipelines)
 }
 
-pub fn parse_input(input: &str) -> Vec<ParsedCommand> {
-    let tokens = tokenize_input(input);
-    let mut commands: Vec<ParsedCommand> = Vec::new();
+fn parse_pipeline(tokens: &[Token]) -> ShellResult<Vec<RawCommand>> {
+    let mut commands: Vec<RawCommand> = Vec::new();
 
-    for token in tokens.split(|t| t == "|") {
+    if tokens.is_empty() {
+        return Ok(commands);
+    }
+
+    let is_pipe = |token: &Token| matches!(token, Token::Pipe | Token::PipeBoth);
+    let pipes: Vec<&Token> = tokens.iter().filter(|token| is_pipe(token)).collect();
+
+    for (index, token) in tokens.split(is_pipe).enumerate() {
+        // `| cat` or `echo |`: a pipe needs a command on both sides
         if token.is_empty() {
-            continue;
+            let pipe = pipes.get(index).or(pipes.last()).copied();
+            return Err(ShellError::SyntaxError(
+                pipe.cloned().unwrap_or(Token::Pipe).into_text(),
+            ));
         }
 
-        if let Some(parsed_command) = parse_command_line(token.to_vec()) {
-            commands.push(parsed_command);
+        let mut command = RawCommand::parse(token)?;
+        // `a |& b` is `a 2>&1 | b`, applied after a's own redirects
+        if pipes.get(index) == Some(&&Token::PipeBoth) {
+            command.redirects.push(RawRedirect::Duplicate {
+                fd: STDERR_FD,
+                target: STDOUT_FD,
+            });
         }
+        commands.push(command);
     }
 
-    commands
+    Ok(commands)
 }
 
-pub fn parse_command_line(tokens: Vec<String>) -> Option<ParsedCommand> {
-    let command = tokens[0].clone();
-    let mut args = Vec::new();
-    let mut stdout_redirect = None;
-    let mut stderr_redirect = None;
+/// Splits a `NAME=value` word into its parts, if it is an assignment.
+pub fn parse_assignment(word: &str) -> Option<(String, String)> {
+    let (name, value) = word.split_once('=')?;
+    is_valid_name(name).then(|| (name.to_string(), value.to_string()))
+}
 
-    let mut stdout_redirect_append = false;
-    let mut stderr_redirect_append = false;
+/// Parses and expands `input` as a single command, the way the text of an
+/// alias is used. Control operators in it are kept as plain words.
+pub fn parse_simple_command(
+    input: &str,
+    lookup: &dyn VariableLookup,
+) -> ShellResult<ParsedCommand> {
+    let tokens = Tokenizer::new(input).tokenize();
+    RawCommand::parse(&tokens)?.expand(&mut ExpandContext::new(lookup))
+}
 
-    let mut iter = tokens.iter().skip(1).peekable();
+/// Characters that end a `!prefix` history designator. A `!` followed by
+/// one of them, a blank or `=` is not a designator at all.
+const HISTORY_WORD_DELIMITERS: &[char] = &[';', '&', '|', '<', '>', '(', ')', '"', '\''];
 
-    while let Some(token) = iter.next() {
-        if let Some(redirect_type) = RedirectType::from_operator(token.as_str()) {
-            if let Some(path) = iter.next() {
-                match redirect_type {
-                    RedirectType::StdoutTruncate => {
-                        stdout_redirect = Some(PathBuf::from(path));
-                        stdout_redirect_append = false;
-                    }
-                    RedirectType::StdoutAppend => {
-                        stdout_redirect = Some(PathBuf::from(path));
-                        stdout_redirect_append = true;
-                    }
-                    RedirectType::StderrTruncate => {
-                        stderr_redirect = Some(PathBuf::from(path));
-                        stderr_redirect_append = false;
-                    }
-                    RedirectType::StderrAppend => {
-                        stderr_redirect = Some(PathBuf::from(path));
-                        stderr_redirect_append = true;
+/// Expands history designators in `line` against `history`, oldest first:
+/// `!!` is the previous command, `!n` is entry `n`, `!-n` is the `n`th most
+/// recent one and `!prefix` is the latest command starting with `prefix`.
+/// Returns `None` if the line contains no designators. Single-quoted text
+/// and `\!` are left alone.
+pub fn expand_history(line: &str, history: &[String]) -> ShellResult<Option<String>> {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut expanded = false;
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if !in_single_quote => {
+                result.push(c);
+                result.extend(chars.next());
+            }
+            '\'' if !in_double_quote => {
+                in_single_quote = !in_single_quote;
+                result.push(c);
+            }
+            '"' if !in_single_quote => {
+                in_double_quote = !in_double_quote;
+                result.push(c);
+            }
+            '!' if !in_single_quote
+                && chars.peek().is_some_and(|&next| starts_designator(next)) =>
+            {
+                let designator = read_history_designator(&mut chars);
+                let entry = find_history_event(&designator, history).ok_or_else(|| {
+                    ShellError::EventNotFound {
+                        event: format!("!{}", designator),
                     }
-                }
-            } else {
-                eprintln!("Syntax error: expected file path after redirect");
+                })?;
+                result.push_str(entry);
+                expanded = true;
             }
-        } else {
-            args.push(token.clone());
+            _ => result.push(c),
         }
     }
 
-    Some(ParsedCommand {
-        command,
-        args,
-        stdout_redirect,
-        stderr_redirect,
-        stdout_redirect_append,
-        stderr_redirect_append,
-    })
+    Ok(expanded.then_some(result))
+}
+
+fn starts_designator(c: char) -> bool {
+    !c.is_whitespace() && c != '=' && !HISTORY_WORD_DELIMITERS.contains(&c)
+}
+
+/// Reads what follows a `!`: another `!`, an optionally negative number,
+/// or a command prefix.
+fn read_history_designator(chars: &mut Peekable<Chars<'_>>) -> String {
+    if chars.next_if_eq(&'!').is_some() {
+        return "!".to_string();
+    }
+
+    let mut designator: String = chars.next_if_eq(&'-').into_iter().collect();
+    if designator.is_empty() && chars.peek().is_some_and(|c| !c.is_ascii_digit()) {
+        while let Some(c) = chars.next_if(|&c| starts_designator(c)) {
+            designator.push(c);
+        }
+    } else {
+        while let Some(c) = chars.next_if(char::is_ascii_digit) {
+            designator.push(c);
+        }
+    }
+    designator
+}
+
+fn find_history_event<'a>(designator: &str, history: &'a [String]) -> Option<&'a str> {
+    let entry = if designator == "!" {
+        history.last()
+    } else if let Some(offset) = designator.strip_prefix('-') {
+        let offset: usize = offset.parse().ok()?;
+        history
+            .len()
+            .checked_sub(offset)
+            .filter(|_| offset > 0)
+            .and_then(|index| history.get(index))
+    } else if let Ok(number) = designator.parse::<usize>() {
+        number.checked_sub(1).and_then(|index| history.get(index))
+    } else {
+        history
+            .iter()
+            .rev()
+            .find(|entry| entry.starts_with(designator))
+    };
+    entry.map(String::as_str)
 }
 
 pub fn tokenize_input(input: &str) -> Vec<String> {
-    let tokenizer = Tokenizer::new(input);
-    tokenizer.tokenize()
+    tokenize_input_with(input, &EnvLookup)
 }
 
-struct Tokenizer {
-    chars: std::iter::Peekable<std::str::Chars<'static>>,
-    state: TokenizerState,
-    tokens: Vec<String>,
-    current_token: String,
-    _input: String,
+/// Tokenizes `input` like `tokenize_input`, but reports an unterminated
+/// quote or trailing backslash as a syntax error.
+pub fn try_tokenize_input(input: &str) -> ShellResult<Vec<String>> {
+    let tokens = Tokenizer::new(input).try_tokenize()?;
+    expand_words(tokens, &EnvLookup)
+}
+
+/// Returns false if `input` ends inside a quote, after a line-continuation
+/// backslash or before a here-document's delimiter line, meaning the user
+/// has more of the command to type.
+pub fn is_complete(input: &str) -> bool {
+    !matches!(
+        Tokenizer::new(input).try_tokenize(),
+        Err(ShellError::UnexpectedEof(_))
+    )
 }
 
-impl Tokenizer {
-    fn new(input: &str) -> Self {
-        // Store input to control its lifetime
-        let owned_input = input.to_string();
-        // SAFETY: We're immediately consuming the chars iterator in tokenize()
-        // and the input string is stored in the struct, so this is safe.
-        let chars = unsafe {
-            std::mem::transmute::<std::str::Chars<'_>, std::str::Chars<'static>>(
-                owned_input.chars(),
-            )
+pub fn tokenize_input_with(input: &str, lookup: &dyn VariableLookup) -> Vec<String> {
+    let tokens = Tokenizer::new(input).tokenize();
+    expand_words(tokens, lookup).unwrap_or_default()
+}
+
+/// Expands each run of words between operators, keeping the operators as
+/// text.
+fn expand_words(tokens: Vec<Token>, lookup: &dyn VariableLookup) -> ShellResult<Vec<String>> {
+    let mut result = Vec::new();
+    let mut words = Vec::new();
+
+    for token in tokens {
+        if let Token::Word(word) = token {
+            words.push(word);
+            continue;
         }
-        .peekable();
+        result.extend(expand_tokens(&words, &mut ExpandContext::new(lookup))?);
+        result.push(token.into_text());
+        words.clear();
+    }
+
+    result.extend(expand_tokens(&words, &mut ExpandContext::new(lookup))?);
+    Ok(result)
+}
+
+/// Resolves variables, positional parameters and shell options during
+/// parsing.
+pub trait VariableLookup {
+    fn get_variable(&self, name: &str) -> Option<String>;
+    fn get_positional_args(&self) -> Vec<String> {
+        Vec::new()
+    }
+    fn get_option(&self, _option: ShellOption) -> bool {
+        false
+    }
+    /// Runs `command` for `$(...)`, returning its output and exit status.
+    /// `None` means commands cannot run here, so the text is kept as is.
+    fn substitute_command(&self, _command: &str) -> Option<(String, i32)> {
+        None
+    }
+}
+
+impl<F: Fn(&str) -> Option<String>> VariableLookup for F {
+    fn get_variable(&self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+/// Resolves variables straight from the process environment.
+struct EnvLookup;
+
+impl VariableLookup for EnvLookup {
+    fn get_variable(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+/// Splits a line into tokens. Words keep their quotes, backslashes and `$`
+/// expressions exactly as typed, for `expand_tokens` to interpret; only
+/// line continuations are removed. The character iterator borrows the
+/// input, so the tokenizer cannot outlive the line it reads.
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+    state: TokenizerState,
+    tokens: Vec<Token>,
+    current_token: String,
+    /// Indexes into `tokens` of here-documents whose bodies start on the
+    /// next line.
+    pending_heredocs: Vec<usize>,
+    /// Set when input ends before a here-document's delimiter line.
+    unterminated_heredoc: bool,
+    error: Option<ShellError>,
+}
 
+impl<'a> Tokenizer<'a> {
+    fn new(input: &'a str) -> Self {
         Self {
-            chars,
+            chars: input.chars().peekable(),
             state: TokenizerState::Normal,
             tokens: Vec::new(),
             current_token: String::new(),
-            _input: owned_input,
+            pending_heredocs: Vec::new(),
+            unterminated_heredoc: false,
+            error: None,
+        }
+    }
+
+    fn tokenize(mut self) -> Vec<Token> {
+        self.consume();
+        self.tokens
+    }
+
+    /// Like `tokenize`, but fails if the input ends inside a quote or right
+    /// after a backslash instead of closing the construct silently.
+    fn try_tokenize(mut self) -> ShellResult<Vec<Token>> {
+        self.consume();
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        match self.state.unterminated() {
+            Some(construct) => Err(ShellError::UnexpectedEof(construct.to_string())),
+            None if self.unterminated_heredoc => {
+                Err(ShellError::UnexpectedEof("in here-document".to_string()))
+            }
+            None => Ok(self.tokens),
         }
     }
 
-    fn tokenize(mut self) -> Vec<String> {
+    fn consume(&mut self) {
         while let Some(c) = self.chars.next() {
             self.process_char(c);
         }
 
         self.finish_token();
-        self.tokens
+        self.unterminated_heredoc |= !self.pending_heredocs.is_empty();
     }
 
     fn process_char(&mut self, c: char) {
@@ -170,14 +713,55 @@ impl Tokenizer {
                 self.state = TokenizerState::Escaped;
             }
             '\'' => {
+                self.current_token.push(c);
                 self.state = TokenizerState::InSingleQuote;
             }
             '"' => {
+                self.current_token.push(c);
                 self.state = TokenizerState::InDoubleQuote;
             }
+            '$' => {
+                self.read_dollar();
+            }
             '|' => {
                 self.finish_token();
-                self.tokens.push("|".to_string());
+                if self.chars.next_if_eq(&'|').is_some() {
+                    self.tokens.push(Token::Or);
+                } else if self.chars.next_if_eq(&'&').is_some() {
+                    self.tokens.push(Token::PipeBoth);
+                } else {
+                    self.tokens.push(Token::Pipe);
+                }
+            }
+            '&' if self.chars.peek() == Some(&'&') => {
+                self.chars.next();
+                self.finish_token();
+                self.tokens.push(Token::And);
+            }
+            // The start of `&>` or `&>>`
+            '&' if self.chars.peek() == Some(&'>') => {
+                self.finish_token();
+                self.current_token.push(c);
+            }
+            '&' => {
+                self.finish_token();
+                self.tokens.push(Token::Background);
+            }
+            ';' => {
+                self.finish_token();
+                self.tokens.push(Token::Semicolon);
+            }
+            '<' | '>' => self.read_redirect_operator(c),
+            // Here-document bodies start on the line after their operators,
+            // and that newline ends the command like `;`
+            '\n' if !self.pending_heredocs.is_empty() => {
+                self.finish_token();
+                self.tokens.push(Token::Semicolon);
+                self.read_heredoc_bodies();
+            }
+            // A `#` starting a word comments out the rest of the line
+            '#' if self.current_token.is_empty() => {
+                while self.chars.next_if(|&c| c != '\n').is_some() {}
             }
             c if c.is_whitespace() => {
                 self.finish_token();
@@ -189,32 +773,29 @@ impl Tokenizer {
     }
 
     fn handle_single_quote(&mut self, c: char) {
-        match c {
-            '\'' => {
-                self.state = TokenizerState::Normal;
-            }
-            _ => {
-                self.current_token.push(c);
-            }
+        self.current_token.push(c);
+        if c == '\'' {
+            self.state = TokenizerState::Normal;
         }
     }
 
     fn handle_double_quote(&mut self, c: char) {
         match c {
             '\\' => {
-                // Check if next char is a special char that should be escaped
-                if let Some(&next_c) = self.chars.peek()
-                    && SPECIAL_CHARS.contains(&next_c.to_string().as_str())
-                {
-                    self.state = TokenizerState::EscapedInDoubleQuote;
+                // A backslash-newline is a line continuation: drop both
+                if self.chars.next_if_eq(&'\n').is_some() {
                     return;
                 }
-                // Not a special escape, treat backslash literally
                 self.current_token.push(c);
+                self.state = TokenizerState::EscapedInDoubleQuote;
             }
             '"' => {
+                self.current_token.push(c);
                 self.state = TokenizerState::Normal;
             }
+            '$' => {
+                self.read_dollar();
+            }
             _ => {
                 self.current_token.push(c);
             }
@@ -222,7 +803,11 @@ impl Tokenizer {
     }
 
     fn handle_escaped(&mut self, c: char) {
-        self.current_token.push(c);
+        // A backslash-newline is a line continuation, not an escaped newline
+        if c != '\n' {
+            self.current_token.push('\\');
+            self.current_token.push(c);
+        }
         self.state = TokenizerState::Normal;
     }
 
@@ -231,10 +816,156 @@ impl Tokenizer {
         self.state = TokenizerState::InDoubleQuote;
     }
 
+    /// Copies a `$` and, for `${...}` or `$(...)`, everything up to the
+    /// matching close, so operators and blanks inside stay in the word.
+    fn read_dollar(&mut self) {
+        self.current_token.push('$');
+        if self.chars.next_if_eq(&'{').is_some() {
+            self.current_token.push('{');
+            for c in self.chars.by_ref() {
+                self.current_token.push(c);
+                if c == '}' {
+                    break;
+                }
+            }
+        } else if self.chars.next_if_eq(&'(').is_some() {
+            self.current_token.push('(');
+            self.read_substitution();
+        }
+    }
+
+    /// Copies the body of a `$(...)` and its closing `)`, skipping over
+    /// quoted and escaped parentheses.
+    fn read_substitution(&mut self) {
+        let mut depth = 0;
+        let mut quote = None;
+
+        while let Some(c) = self.chars.next() {
+            self.current_token.push(c);
+            match (quote, c) {
+                (Some('"') | None, '\\') => self.current_token.extend(self.chars.next()),
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') if depth == 0 => return,
+                (None, ')') => depth -= 1,
+                (None, _) => {}
+            }
+        }
+    }
+
+    /// Reads the redirect operator starting with `c` into a word of its
+    /// own. A descriptor number right before it, as in `2>`, and the `&` of
+    /// `&>` belong to the operator; any other word before it ends there.
+    fn read_redirect_operator(&mut self, c: char) {
+        let is_prefix = self.current_token == "&"
+            || (!self.current_token.is_empty()
+                && self.current_token.chars().all(|c| c.is_ascii_digit()));
+        if !is_prefix {
+            self.finish_token();
+        }
+
+        if c == '<' && self.current_token.is_empty() && self.chars.next_if_eq(&'<').is_some() {
+            if self.chars.next_if_eq(&'<').is_some() {
+                self.tokens.push(Token::Redirect(HERE_STRING.to_string()));
+            } else {
+                self.read_heredoc_operator();
+            }
+            return;
+        }
+
+        self.current_token.push(c);
+        if let Some(next) = self
+            .chars
+            .next_if(|&next| next == c || (c == '>' && next == '|'))
+        {
+            self.current_token.push(next);
+        }
+        // A duplication such as `2>&1` ends with its target descriptor
+        if self.chars.next_if_eq(&'&').is_some() {
+            self.current_token.push('&');
+            while let Some(digit) = self.chars.next_if(char::is_ascii_digit) {
+                self.current_token.push(digit);
+            }
+        }
+        let operator = std::mem::take(&mut self.current_token);
+        self.tokens.push(Token::Redirect(operator));
+    }
+
+    /// Reads the delimiter after `<<` or `<<-` and queues a here-document
+    /// for the body that follows this line.
+    fn read_heredoc_operator(&mut self) {
+        let strip_tabs = self.chars.next_if_eq(&'-').is_some();
+        while self.chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+
+        let mut delimiter = String::new();
+        let mut quoted = false;
+        let mut quote = None;
+        while let Some(&c) = self.chars.peek() {
+            match (quote, c) {
+                (None, c) if c.is_whitespace() || "|&;<>".contains(c) => break,
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    quoted = true;
+                }
+                (None, '\\') => {
+                    self.chars.next();
+                    quoted = true;
+                    delimiter.extend(self.chars.peek());
+                }
+                (Some(q), _) if c == q => quote = None,
+                _ => delimiter.push(c),
+            }
+            self.chars.next();
+        }
+
+        if delimiter.is_empty() && !quoted {
+            self.error
+                .get_or_insert(ShellError::SyntaxError("newline".to_string()));
+            return;
+        }
+        self.pending_heredocs.push(self.tokens.len());
+        self.tokens.push(Token::HereDoc(HereDoc {
+            delimiter,
+            quoted,
+            strip_tabs,
+            body: String::new(),
+        }));
+    }
+
+    /// Reads the bodies of the queued here-documents, in order, each up to
+    /// a line holding just its delimiter.
+    fn read_heredoc_bodies(&mut self) {
+        for index in std::mem::take(&mut self.pending_heredocs) {
+            let Token::HereDoc(doc) = &mut self.tokens[index] else {
+                continue;
+            };
+            let mut terminated = false;
+
+            while self.chars.peek().is_some() {
+                let line: String = self.chars.by_ref().take_while(|&c| c != '\n').collect();
+                let line = if doc.strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line.as_str()
+                };
+                if line == doc.delimiter {
+                    terminated = true;
+                    break;
+                }
+                doc.body.push_str(line);
+                doc.body.push('\n');
+            }
+
+            self.unterminated_heredoc |= !terminated;
+        }
+    }
+
     fn finish_token(&mut self) {
         if !self.current_token.is_empty() {
-            self.tokens.push(self.current_token.clone());
-            self.current_token.clear();
+            let word = std::mem::take(&mut self.current_token);
+            self.tokens.push(Token::Word(word));
         }
     }
 }
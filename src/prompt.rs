@@ -0,0 +1,77 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+pub const DEFAULT_PROMPT: &str = "$ ";
+/// Shown while reading the rest of a command left open by a quote, a
+/// trailing backslash or a here-document.
+pub const CONTINUATION_PROMPT: &str = "> ";
+
+const UNKNOWN_CWD: &str = "?";
+
+/// Builds the prompt from `$PS1`, falling back to [`DEFAULT_PROMPT`].
+pub fn current_prompt() -> String {
+    match env::var("PS1") {
+        Ok(template) if !template.is_empty() => {
+            let cwd = prompt_cwd(env::current_dir(), env::var("PWD").ok());
+            render_prompt(&template, &cwd)
+        }
+        _ => DEFAULT_PROMPT.to_string(),
+    }
+}
+
+/// Picks the directory shown by `\w`. When the working directory can no
+/// longer be resolved (e.g. it was deleted), `$PWD` is used, then `?`.
+pub fn prompt_cwd(current: io::Result<PathBuf>, pwd: Option<String>) -> String {
+    match current {
+        Ok(path) => path.display().to_string(),
+        Err(_) => pwd
+            .filter(|pwd| !pwd.is_empty())
+            .unwrap_or_else(|| UNKNOWN_CWD.to_string()),
+    }
+}
+
+/// Expands the `\w`, `\W`, `\$` and `\\` escapes in a prompt template.
+pub fn render_prompt(template: &str, cwd: &str) -> String {
+    let mut prompt = String::new();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            prompt.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('w') => prompt.push_str(&abbreviate_home(cwd)),
+            Some('W') => prompt.push_str(basename(cwd)),
+            Some('$') => prompt.push('$'),
+            Some('\\') => prompt.push('\\'),
+            Some(other) => {
+                prompt.push('\\');
+                prompt.push(other);
+            }
+            None => prompt.push('\\'),
+        }
+    }
+
+    prompt
+}
+
+/// Replaces a leading `$HOME` in `cwd` with `~`, as `\w` and `dirs` show it.
+pub fn abbreviate_home(cwd: &str) -> String {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() && cwd == home => "~".to_string(),
+        Ok(home) if !home.is_empty() && cwd.starts_with(&format!("{}/", home)) => {
+            format!("~{}", &cwd[home.len()..])
+        }
+        _ => cwd.to_string(),
+    }
+}
+
+fn basename(cwd: &str) -> &str {
+    if cwd == "/" {
+        return cwd;
+    }
+    cwd.rsplit('/').next().unwrap_or(cwd)
+}
@@ -1,16 +1,55 @@
+use std::fs;
+use std::path::Path;
+
 use rustyline::completion::{Candidate, Completer};
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
 use rustyline::validate::Validator;
 use rustyline::{Context, Helper, Result};
 
-pub struct Shell {
+use crate::commands::CommandRegistry;
+use crate::expand::{cdpath_dirs, expand_tilde};
+
+/// Set to a non-empty value other than `0` to complete case-insensitively.
+pub const COMPLETION_IGNORE_CASE_VAR: &str = "SHELL_COMPLETION_IGNORE_CASE";
+
+pub struct Shell<'a> {
     pub commands: Vec<String>,
+    /// Match candidates regardless of case, so `EC` completes `echo`.
+    pub case_insensitive: bool,
+    /// When set, command names come from the registry at completion time
+    /// instead of `commands`, so new aliases and programs show up.
+    registry: Option<&'a CommandRegistry>,
 }
 
-impl Shell {
+impl<'a> Shell<'a> {
     pub fn new(commands: Vec<String>) -> Self {
-        Self { commands }
+        Self {
+            commands,
+            case_insensitive: false,
+            registry: None,
+        }
+    }
+
+    /// Completes command names against the registry's current builtins,
+    /// aliases and `$PATH` rather than a list fixed at startup.
+    pub fn from_registry(registry: &'a CommandRegistry) -> Self {
+        Self {
+            registry: Some(registry),
+            ..Self::new(Vec::new())
+        }
+    }
+
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Reads `$SHELL_COMPLETION_IGNORE_CASE`; completion is case-sensitive
+    /// unless it is set.
+    pub fn case_insensitive_from_env() -> bool {
+        std::env::var(COMPLETION_IGNORE_CASE_VAR)
+            .is_ok_and(|value| !value.is_empty() && value != "0")
     }
 }
 
@@ -30,50 +69,216 @@ impl Candidate for CustomCandidate {
     }
 }
 
-impl Completer for Shell {
+/// Operators after which the next word is a command again.
+const COMMAND_SEPARATORS: &[char] = &['|', ';', '&'];
+
+impl Shell<'_> {
+    /// Returns where the word under the cursor starts and the candidates for
+    /// it: commands in command position, file names everywhere else. When
+    /// several match, each one only completes their longest common prefix,
+    /// so the word is never finished or followed by a space until the choice
+    /// is unambiguous.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<CustomCandidate>) {
+        let before_cursor = &line[..pos];
+        if before_cursor.is_empty() {
+            return (0, Vec::new());
+        }
+
+        let start = before_cursor
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        let word = &before_cursor[start..];
+        let preceding = before_cursor[..start].trim_end();
+
+        let is_command_position = preceding.is_empty() || preceding.ends_with(COMMAND_SEPARATORS);
+
+        let mut candidates = if is_command_position {
+            self.command_candidates(word)
+        } else if Self::current_command(preceding) == "cd" {
+            self.cd_candidates(word)
+        } else {
+            self.file_candidates(word)
+        };
+
+        if candidates.len() > 1 {
+            let prefix = common_prefix(&candidates);
+            for candidate in candidates.iter_mut() {
+                candidate.replacement = prefix.clone();
+            }
+        }
+
+        (start, candidates)
+    }
+
+    /// The words of the command the cursor is in, before the current word.
+    fn current_command(preceding: &str) -> &str {
+        preceding
+            .rsplit(COMMAND_SEPARATORS)
+            .next()
+            .unwrap_or_default()
+            .trim()
+    }
+
+    fn command_candidates(&self, word: &str) -> Vec<CustomCandidate> {
+        let commands = match self.registry {
+            Some(registry) => registry.get_command_names(),
+            None => self.commands.clone(),
+        };
+
+        commands
+            .iter()
+            .filter(|command| starts_with(command, word, self.case_insensitive))
+            .map(|command| CustomCandidate {
+                display: command.clone(),
+                replacement: format!("{} ", command),
+            })
+            .collect()
+    }
+
+    fn file_candidates(&self, word: &str) -> Vec<CustomCandidate> {
+        let (dir, prefix) = split_word(word);
+        let search_dir = if dir.is_empty() {
+            ".".to_string()
+        } else {
+            expand_tilde(dir)
+        };
+
+        let mut candidates: Vec<CustomCandidate> =
+            matching_entries(Path::new(&search_dir), prefix, self.case_insensitive)
+                .into_iter()
+                .map(|(name, is_dir)| {
+                    let suffix = if is_dir { "/" } else { " " };
+                    CustomCandidate {
+                        replacement: format!("{}{}{}", dir, name, suffix),
+                        display: name,
+                    }
+                })
+                .collect();
+
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        candidates
+    }
+
+    /// Directories for a `cd` argument: those under the current directory,
+    /// plus, for relative words, those under each `$CDPATH` entry.
+    fn cd_candidates(&self, word: &str) -> Vec<CustomCandidate> {
+        let (dir, prefix) = split_word(word);
+        let search_dir = expand_tilde(dir);
+
+        let mut bases = vec![".".to_string()];
+        if !search_dir.starts_with('/') {
+            bases.extend(cdpath_dirs());
+        }
+
+        let mut candidates: Vec<CustomCandidate> = bases
+            .iter()
+            .flat_map(|base| {
+                matching_entries(
+                    &Path::new(base).join(&search_dir),
+                    prefix,
+                    self.case_insensitive,
+                )
+            })
+            .filter(|(_, is_dir)| *is_dir)
+            .map(|(name, _)| CustomCandidate {
+                replacement: format!("{}{}/", dir, name),
+                display: name,
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        candidates.dedup_by(|a, b| a.display == b.display);
+        candidates
+    }
+}
+
+/// The longest prefix shared by every candidate's replacement.
+fn common_prefix(candidates: &[CustomCandidate]) -> String {
+    let Some((first, rest)) = candidates.split_first() else {
+        return String::new();
+    };
+
+    let mut len = first.replacement.len();
+    for candidate in rest {
+        len = first
+            .replacement
+            .char_indices()
+            .zip(candidate.replacement.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((index, a), _)| index + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+            .min(len);
+    }
+    first.replacement[..len].to_string()
+}
+
+/// Splits a word into the directory part, including its trailing `/`, and
+/// the file name prefix being completed. The directory part is returned as
+/// typed; callers expand a leading `~` only when reading the directory, so
+/// the completed word keeps it.
+fn split_word(word: &str) -> (&str, &str) {
+    match word.rfind('/') {
+        Some(index) => (&word[..=index], &word[index + 1..]),
+        None => ("", word),
+    }
+}
+
+/// Returns true if `name` starts with `prefix`, optionally ignoring case.
+fn starts_with(name: &str, prefix: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        name.to_lowercase().starts_with(&prefix.to_lowercase())
+    } else {
+        name.starts_with(prefix)
+    }
+}
+
+/// Lists the entries of `dir` starting with `prefix` as `(name, is_dir)`.
+/// Hidden entries are skipped unless the prefix itself starts with `.`.
+fn matching_entries(dir: &Path, prefix: &str, case_insensitive: bool) -> Vec<(String, bool)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !starts_with(&name, prefix, case_insensitive)
+                || (name.starts_with('.') && !prefix.starts_with('.'))
+            {
+                return None;
+            }
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            Some((name, is_dir))
+        })
+        .collect()
+}
+
+impl Completer for Shell<'_> {
     type Candidate = CustomCandidate;
 
     fn complete(
         &self,
         line: &str,
-        _pos: usize,
+        pos: usize,
         _ctx: &Context,
     ) -> Result<(usize, Vec<CustomCandidate>)> {
-        let mut candidates: Vec<CustomCandidate> = Vec::new();
-
-        if line.is_empty() {
-            return Ok((0, candidates));
-        }
-
-        for command in &self.commands {
-            if command.starts_with(line) {
-                candidates.push(CustomCandidate {
-                    display: command.clone(),
-                    replacement: format!("{} ", command),
-                });
-            }
-        }
-
-        Ok((0, candidates))
+        Ok(self.candidates(line, pos))
     }
 }
 
-impl Helper for Shell {}
+impl Helper for Shell<'_> {}
 
-impl Hinter for Shell {
+impl Hinter for Shell<'_> {
     type Hint = String;
     fn hint(&self, _line: &str, _pos: usize, _ctx: &Context) -> Option<String> {
         None
     }
 }
 
-impl Highlighter for Shell {}
+impl Highlighter for Shell<'_> {}
 
-impl Validator for Shell {
-    fn validate(
-        &self,
-        _ctx: &mut rustyline::validate::ValidationContext,
-    ) -> Result<rustyline::validate::ValidationResult> {
-        Ok(rustyline::validate::ValidationResult::Valid(None))
-    }
-}
+// Unfinished commands are completed line by line in the main loop, under
+// the continuation prompt, rather than in one multi-line edit
+impl Validator for Shell<'_> {}
@@ -0,0 +1,66 @@
+use std::io;
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::ptr;
+
+const ELLIPSIS: char = '…';
+
+/// Returns the width of the terminal on stdout, or `None` when stdout is
+/// not a terminal.
+pub fn terminal_width() -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+    // SAFETY: TIOCGWINSZ fills in the `winsize` struct we pass a pointer to.
+    let status = unsafe { libc::ioctl(io::stdout().as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+
+    (status == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+/// Makes `group` the foreground process group of the terminal on stdin, so
+/// Ctrl-C and Ctrl-Z signal it rather than the shell. Does nothing when
+/// stdin is not a terminal.
+pub fn set_foreground_group(group: libc::pid_t) -> io::Result<()> {
+    let fd = io::stdin().as_raw_fd();
+
+    // SAFETY: the signal sets are initialised by `sigemptyset` and
+    // `pthread_sigmask` before use, and the previous mask is restored.
+    unsafe {
+        if libc::isatty(fd) == 0 {
+            return Ok(());
+        }
+
+        // Once a child owns the terminal the shell is in the background, and
+        // taking the terminal back would stop it with SIGTTOU unless blocked.
+        let mut block: libc::sigset_t = mem::zeroed();
+        let mut previous: libc::sigset_t = mem::zeroed();
+        libc::sigemptyset(&mut block);
+        libc::sigaddset(&mut block, libc::SIGTTOU);
+        libc::pthread_sigmask(libc::SIG_BLOCK, &block, &mut previous);
+        let status = libc::tcsetpgrp(fd, group);
+        let error = io::Error::last_os_error();
+        libc::pthread_sigmask(libc::SIG_SETMASK, &previous, ptr::null_mut());
+
+        if status == -1 { Err(error) } else { Ok(()) }
+    }
+}
+
+/// Gives the terminal back to the shell's own process group.
+pub fn reclaim_terminal() -> io::Result<()> {
+    // SAFETY: getpgrp cannot fail.
+    set_foreground_group(unsafe { libc::getpgrp() })
+}
+
+/// Shortens `text` to at most `width` characters, replacing the tail with
+/// an ellipsis when anything had to be cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push(ELLIPSIS);
+    truncated
+}
@@ -1,5 +1,6 @@
-use codecrafters_shell::commands::{CommandRegistry, ShellStatus};
+use codecrafters_shell::commands::{CommandRegistry, IoContext, ShellStatus};
 use codecrafters_shell::error::ShellResult;
+use std::io;
 
 #[cfg(test)]
 mod echo_tests {
@@ -10,7 +11,11 @@ mod echo_tests {
         let echo_cmd = registry.get_builtin("echo").unwrap();
         let mut output = Vec::new();
         let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        let result = echo_cmd.execute(&args, &registry, &mut output);
+        let result = echo_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
         (String::from_utf8(output).unwrap(), result)
     }
 
@@ -66,7 +71,11 @@ mod pwd_tests {
         let registry = CommandRegistry::default();
         let pwd_cmd = registry.get_builtin("pwd").unwrap();
         let mut output = Vec::new();
-        let result = pwd_cmd.execute(&[], &registry, &mut output);
+        let result = pwd_cmd.execute(
+            &[],
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
         (String::from_utf8(output).unwrap(), result)
     }
 
@@ -89,9 +98,14 @@ mod pwd_tests {
 #[cfg(test)]
 mod cd_tests {
     use super::*;
+    use codecrafters_shell::commands::ShellExecutor;
+    use codecrafters_shell::options::ShellOption;
+    use codecrafters_shell::parser::parse_input;
     use std::env;
+    use std::fs;
     use std::path::PathBuf;
     use std::sync::Mutex;
+    use tempfile::TempDir;
 
     // Use a mutex to ensure CD tests run serially (since they modify global state)
     static CD_TEST_LOCK: Mutex<()> = Mutex::new(());
@@ -101,7 +115,18 @@ mod cd_tests {
         let cd_cmd = registry.get_builtin("cd").unwrap();
         let mut output = Vec::new();
         let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        cd_cmd.execute(&args, &registry, &mut output)
+        cd_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
+    }
+
+    /// Runs `line` through the parser, which expands tildes before `cd`
+    /// sees its argument.
+    fn run_line(line: &str) -> ShellResult<ShellStatus> {
+        let registry = CommandRegistry::default();
+        ShellExecutor::new(&registry).run(&parse_input(line).unwrap())
     }
 
     #[test]
@@ -149,7 +174,7 @@ mod cd_tests {
         let _lock = CD_TEST_LOCK.lock().unwrap();
         let original = env::current_dir().unwrap();
 
-        let result = execute_cd(&["~"]);
+        let result = run_line("cd ~");
         assert!(result.is_ok());
 
         let expected_home = env::var("HOME").unwrap();
@@ -159,6 +184,86 @@ mod cd_tests {
         env::set_current_dir(original).unwrap();
     }
 
+    #[test]
+    fn test_cd_to_home_subpath() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+
+        let result = run_line("cd ~/");
+        assert!(result.is_ok());
+
+        let expected_home = env::var("HOME").unwrap();
+        assert_eq!(
+            env::current_dir().unwrap().canonicalize().unwrap(),
+            PathBuf::from(expected_home).canonicalize().unwrap()
+        );
+
+        // Restore original directory
+        env::set_current_dir(original).unwrap();
+    }
+
+    #[test]
+    fn test_cd_to_home_without_home_set() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let home = env::var_os("HOME");
+
+        // SAFETY: guarded by CD_TEST_LOCK; HOME is restored before unlocking.
+        unsafe { env::remove_var("HOME") };
+        let unset = run_line("cd ~");
+        let unset_subpath = run_line("cd ~/projects");
+        let quoted = run_line("cd \"~\"");
+        unsafe { env::set_var("HOME", "") };
+        let empty = run_line("cd ~");
+        if let Some(home) = home {
+            unsafe { env::set_var("HOME", home) };
+        }
+
+        for result in [unset, unset_subpath, empty] {
+            assert_eq!(result.unwrap_err().to_string(), "cd: HOME not set");
+        }
+        assert!(
+            quoted
+                .unwrap_err()
+                .to_string()
+                .contains("No such file or directory")
+        );
+        assert_eq!(env::current_dir().unwrap(), original);
+    }
+
+    #[test]
+    fn test_cd_quoted_tilde_is_a_directory_name() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+
+        for word in ["\"~\"", "'~'", "'~root'", "\\~"] {
+            let error = run_line(&format!("cd {}", word)).unwrap_err();
+            assert!(
+                error.to_string().contains("No such file or directory"),
+                "{}: {}",
+                word,
+                error
+            );
+            assert_eq!(env::current_dir().unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn test_mkcd_quoted_tilde_is_a_directory_name() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+
+        env::set_current_dir(&temp_path).unwrap();
+        let result = run_line("mkcd '~'");
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&original).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(cwd, temp_path.join("~"));
+    }
+
     #[test]
     fn test_cd_no_args_does_nothing() {
         let _lock = CD_TEST_LOCK.lock().unwrap();
@@ -170,6 +275,321 @@ mod cd_tests {
         // With no args, cd currently does nothing (stays in current directory)
         assert_eq!(env::current_dir().unwrap(), original);
     }
+
+    #[test]
+    fn test_cd_searches_cdpath() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let cdpath = TempDir::new().unwrap();
+        fs::create_dir(cdpath.path().join("cdpath_project")).unwrap();
+
+        // SAFETY: guarded by CD_TEST_LOCK; no other test reads CDPATH.
+        unsafe { env::set_var("CDPATH", cdpath.path()) };
+        let registry = CommandRegistry::default();
+        let mut output = Vec::new();
+        let result = registry.get_builtin("cd").unwrap().execute(
+            &["cdpath_project".to_string()],
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        unsafe { env::remove_var("CDPATH") };
+
+        let expected = cdpath.path().join("cdpath_project").canonicalize().unwrap();
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(original).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(cwd.canonicalize().unwrap(), expected);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{}\n", cwd.display())
+        );
+    }
+
+    fn builtin_output(name: &str, args: &[&str]) -> String {
+        let registry = CommandRegistry::default();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        registry
+            .get_builtin(name)
+            .unwrap()
+            .execute(
+                &args,
+                &registry,
+                &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+            )
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_cd_through_symlink_round_trips_with_pwd() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let target = temp.path().join("target");
+        let link = temp.path().join("link");
+        fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        execute_cd(&[link.to_str().unwrap()]).unwrap();
+        let pwd = builtin_output("pwd", &[]);
+        let resolved = builtin_output("realpath", &["."]);
+        execute_cd(&[original.to_str().unwrap()]).unwrap();
+
+        let expected = format!("{}\n", target.canonicalize().unwrap().display());
+        assert_eq!(pwd, expected);
+        assert_eq!(resolved, expected);
+        assert_eq!(env::current_dir().unwrap(), original);
+    }
+
+    #[test]
+    fn test_realpath_resolves_relative_paths() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("file.txt"), "").unwrap();
+
+        env::set_current_dir(temp.path()).unwrap();
+        let existing = builtin_output("realpath", &["sub/../file.txt"]);
+        let missing = builtin_output("realpath", &["-m", "new/./dir/.."]);
+        env::set_current_dir(original).unwrap();
+
+        let root = temp.path().canonicalize().unwrap();
+        assert_eq!(existing, format!("{}\n", root.join("file.txt").display()));
+        assert_eq!(missing, format!("{}\n", root.join("new").display()));
+    }
+
+    fn run(
+        registry: &CommandRegistry,
+        name: &str,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        let result = registry.get_builtin(name).unwrap().execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_pushd_popd_round_trip() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let first_path = first.path().canonicalize().unwrap();
+        let second_path = second.path().canonicalize().unwrap();
+        let registry = CommandRegistry::default();
+
+        run(&registry, "pushd", &[first_path.to_str().unwrap()])
+            .1
+            .unwrap();
+        let after_first = env::current_dir().unwrap();
+        run(&registry, "pushd", &[second_path.to_str().unwrap()])
+            .1
+            .unwrap();
+        let after_second = env::current_dir().unwrap();
+        let stack = registry.get_dir_stack();
+        run(&registry, "popd", &[]).1.unwrap();
+        let after_pop = env::current_dir().unwrap();
+        run(&registry, "popd", &[]).1.unwrap();
+        let after_last_pop = env::current_dir().unwrap();
+        let (_, empty) = run(&registry, "popd", &[]);
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(after_first, first_path);
+        assert_eq!(after_second, second_path);
+        assert_eq!(stack, [first_path.clone(), original.clone()]);
+        assert_eq!(after_pop, first_path);
+        assert_eq!(after_last_pop, original);
+        assert_eq!(
+            empty.unwrap_err().to_string(),
+            "popd: directory stack empty"
+        );
+    }
+
+    #[test]
+    fn test_pushd_without_args_swaps_top_two() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        let registry = CommandRegistry::default();
+
+        let (_, no_other) = run(&registry, "pushd", &[]);
+        run(&registry, "pushd", &[temp_path.to_str().unwrap()])
+            .1
+            .unwrap();
+        run(&registry, "pushd", &[]).1.unwrap();
+        let swapped = env::current_dir().unwrap();
+        let stack = registry.get_dir_stack();
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(
+            no_other.unwrap_err().to_string(),
+            "pushd: no other directory"
+        );
+        assert_eq!(swapped, original);
+        assert_eq!(stack, [temp_path]);
+    }
+
+    #[test]
+    fn test_dirs_prints_the_stack() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        let registry = CommandRegistry::default();
+        registry.set_dir_stack(vec![
+            PathBuf::from("/saved/one"),
+            PathBuf::from("/saved/two"),
+        ]);
+
+        env::set_current_dir(&temp_path).unwrap();
+        let (dirs, _) = run(&registry, "dirs", &[]);
+        let (pushd, _) = run(&registry, "pushd", &["/"]);
+        run(&registry, "dirs", &["-c"]).1.unwrap();
+        let (cleared, _) = run(&registry, "dirs", &[]);
+        env::set_current_dir(&original).unwrap();
+
+        let cwd = temp_path.display();
+        assert_eq!(dirs, format!("{} /saved/one /saved/two\n", cwd));
+        assert_eq!(pushd, format!("/ {} /saved/one /saved/two\n", cwd));
+        assert_eq!(cleared, "/\n");
+    }
+
+    #[test]
+    fn test_pushd_missing_directory_leaves_stack_alone() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let registry = CommandRegistry::default();
+
+        let (_, result) = run(&registry, "pushd", &["/nonexistent_directory_12345"]);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "pushd: /nonexistent_directory_12345: No such file or directory"
+        );
+        assert!(registry.get_dir_stack().is_empty());
+        assert_eq!(env::current_dir().unwrap(), original);
+    }
+
+    #[test]
+    fn test_cd_tilde_minus_returns_to_previous_directory() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        let registry = CommandRegistry::default();
+
+        env::set_current_dir(&temp_path).unwrap();
+        run(&registry, "cd", &["/"]).1.unwrap();
+        run_line("cd ~-").unwrap();
+        let cwd = env::current_dir().unwrap();
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(cwd, temp_path);
+    }
+
+    #[test]
+    fn test_cdspell_corrects_typos() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        fs::create_dir_all(temp_path.join("documents/projects")).unwrap();
+        let registry = CommandRegistry::default();
+        registry.set_option(ShellOption::Cdspell, true);
+
+        env::set_current_dir(&temp_path).unwrap();
+        let (transposed, _) = run(&registry, "cd", &["documnets"]);
+        let after_transposed = env::current_dir().unwrap();
+        env::set_current_dir(&temp_path).unwrap();
+        let (nested, _) = run(&registry, "cd", &["documets/projectss"]);
+        let after_nested = env::current_dir().unwrap();
+        env::set_current_dir(&temp_path).unwrap();
+        let (_, too_far) = run(&registry, "cd", &["dcmnts"]);
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(transposed, "documents\n");
+        assert_eq!(after_transposed, temp_path.join("documents"));
+        assert_eq!(nested, "documents/projects\n");
+        assert_eq!(after_nested, temp_path.join("documents/projects"));
+        assert!(too_far.is_err());
+    }
+
+    #[test]
+    fn test_cd_typo_fails_without_cdspell() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        fs::create_dir(temp_path.join("documents")).unwrap();
+        let registry = CommandRegistry::default();
+
+        env::set_current_dir(&temp_path).unwrap();
+        let (_, result) = run(&registry, "cd", &["documnets"]);
+        let after = env::current_dir().unwrap();
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "cd: documnets: No such file or directory"
+        );
+        assert_eq!(after, temp_path);
+    }
+
+    #[test]
+    fn test_mkcd_creates_nested_directories() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        let target = temp_path.join("a/b/c");
+        let registry = CommandRegistry::default();
+
+        env::set_current_dir(&temp_path).unwrap();
+        run(&registry, "mkcd", &["a/b/c"]).1.unwrap();
+        let cwd = env::current_dir().unwrap();
+        let pwd = env::var("PWD").unwrap();
+        let oldpwd = env::var("OLDPWD").unwrap();
+        env::set_current_dir(&original).unwrap();
+
+        assert!(target.is_dir());
+        assert_eq!(cwd, target);
+        assert_eq!(PathBuf::from(pwd), target);
+        assert_eq!(PathBuf::from(oldpwd), temp_path);
+    }
+
+    #[test]
+    fn test_mkcd_enters_existing_directory() {
+        let _lock = CD_TEST_LOCK.lock().unwrap();
+        let original = env::current_dir().unwrap();
+        let temp = TempDir::new().unwrap();
+        let temp_path = temp.path().canonicalize().unwrap();
+        fs::write(temp_path.join("file"), "").unwrap();
+        let registry = CommandRegistry::default();
+
+        run(&registry, "mkcd", &[temp_path.to_str().unwrap()])
+            .1
+            .unwrap();
+        let cwd = env::current_dir().unwrap();
+        let (_, blocked) = run(&registry, "mkcd", &["file/sub"]);
+        env::set_current_dir(&original).unwrap();
+
+        assert_eq!(cwd, temp_path);
+        assert!(
+            blocked
+                .unwrap_err()
+                .to_string()
+                .starts_with("mkcd: cannot create directory `file/sub': ")
+        );
+    }
 }
 
 #[cfg(test)]
@@ -181,21 +601,109 @@ mod exit_tests {
         let exit_cmd = registry.get_builtin("exit").unwrap();
         let mut output = Vec::new();
         let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        exit_cmd.execute(&args, &registry, &mut output)
+        exit_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
     }
 
     #[test]
     fn test_exit_returns_exit_status() {
         let result = execute_exit(&[]);
         assert!(result.is_ok());
-        matches!(result.unwrap(), ShellStatus::Exit);
+        assert_eq!(result.unwrap(), ShellStatus::Exit(0));
     }
 
     #[test]
     fn test_exit_with_code() {
         let result = execute_exit(&["0"]);
         assert!(result.is_ok());
-        matches!(result.unwrap(), ShellStatus::Exit);
+        assert_eq!(result.unwrap(), ShellStatus::Exit(0));
+    }
+
+    #[test]
+    fn test_exit_sets_status_from_argument() {
+        let registry = CommandRegistry::default();
+        let exit_cmd = registry.get_builtin("exit").unwrap();
+        let mut error = Vec::new();
+        let mut exit = |arg: &str| {
+            exit_cmd
+                .execute(
+                    &[arg.to_string()],
+                    &registry,
+                    &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut error),
+                )
+                .unwrap()
+        };
+
+        assert_eq!(exit("3"), ShellStatus::Exit(3));
+        assert_eq!(registry.get_last_status(), 3);
+
+        assert_eq!(exit("abc"), ShellStatus::Exit(2));
+        assert_eq!(registry.get_last_status(), 2);
+        assert_eq!(
+            String::from_utf8(error).unwrap(),
+            "exit: abc: numeric argument required\n"
+        );
+    }
+
+    #[test]
+    fn test_exit_carries_code_in_status() {
+        assert_eq!(execute_exit(&["7"]).unwrap(), ShellStatus::Exit(7));
+        assert_eq!(execute_exit(&["256"]).unwrap(), ShellStatus::Exit(0));
+        assert_eq!(execute_exit(&["-1"]).unwrap(), ShellStatus::Exit(255));
+    }
+}
+
+#[cfg(test)]
+mod trap_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+
+    fn execute_trap(
+        registry: &CommandRegistry,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let trap_cmd = registry.get_builtin("trap").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = trap_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_trap_sets_and_lists_exit_action() {
+        let registry = CommandRegistry::default();
+        execute_trap(&registry, &["echo 'bye'", "0"]).1.unwrap();
+
+        assert_eq!(registry.get_trap("EXIT"), Some("echo 'bye'".to_string()));
+        let (output, _) = execute_trap(&registry, &[]);
+        assert_eq!(output, "trap -- 'echo '\\''bye'\\''' EXIT\n");
+    }
+
+    #[test]
+    fn test_trap_reset() {
+        let registry = CommandRegistry::default();
+
+        execute_trap(&registry, &["echo bye", "EXIT"]).1.unwrap();
+        execute_trap(&registry, &["-", "EXIT"]).1.unwrap();
+        assert_eq!(registry.get_trap("EXIT"), None);
+
+        execute_trap(&registry, &["echo bye", "EXIT"]).1.unwrap();
+        execute_trap(&registry, &["EXIT"]).1.unwrap();
+        assert_eq!(registry.get_trap("EXIT"), None);
+    }
+
+    #[test]
+    fn test_trap_rejects_unsupported_condition() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute_trap(&registry, &["echo hi", "NOPE"]);
+        assert!(matches!(result, Err(ShellError::InvalidSignalSpec { .. })));
     }
 }
 
@@ -208,7 +716,11 @@ mod type_tests {
         let type_cmd = registry.get_builtin("type").unwrap();
         let mut output = Vec::new();
         let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        let result = type_cmd.execute(&args, &registry, &mut output);
+        let result = type_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
         (String::from_utf8(output).unwrap(), result)
     }
 
@@ -219,6 +731,35 @@ mod type_tests {
         assert_eq!(output, "echo is a shell builtin\n");
     }
 
+    #[test]
+    fn test_type_json_builtin() {
+        let (output, result) = execute_type(&["--json", "echo"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "{\"name\":\"echo\",\"kind\":\"builtin\"}\n");
+    }
+
+    #[test]
+    fn test_type_json_external() {
+        let (output, result) = execute_type(&["--json", "sh"]);
+        assert!(result.is_ok());
+
+        let prefix = "{\"name\":\"sh\",\"kind\":\"file\",\"path\":\"";
+        assert!(output.starts_with(prefix), "{}", output);
+        assert!(output.ends_with("/sh\"}\n"), "{}", output);
+        assert_eq!(output.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_type_json_with_all_lists_every_match() {
+        let (output, result) = execute_type(&["--json", "-a", "echo"]);
+        assert!(result.is_ok());
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.len() >= 2, "{}", output);
+        assert_eq!(lines[0], "{\"name\":\"echo\",\"kind\":\"builtin\"}");
+        assert!(lines[1].starts_with("{\"name\":\"echo\",\"kind\":\"file\""));
+    }
+
     #[test]
     fn test_type_builtin_cd() {
         let (output, result) = execute_type(&["cd"]);
@@ -271,6 +812,62 @@ mod type_tests {
         assert!(output.contains("cd is a shell builtin"));
         assert!(output.contains("ls is "));
     }
+
+    #[test]
+    fn test_type_terse_builtin() {
+        let (output, result) = execute_type(&["-t", "echo"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "builtin\n");
+    }
+
+    #[test]
+    fn test_type_terse_file() {
+        let (output, result) = execute_type(&["-t", "ls"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "file\n");
+    }
+
+    #[test]
+    fn test_type_all_lists_builtin_and_executables() {
+        let (output, result) = execute_type(&["-a", "echo"]);
+        assert!(result.is_ok());
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "echo is a shell builtin");
+        assert!(lines.len() >= 2);
+        assert!(
+            lines[1..]
+                .iter()
+                .all(|line| line.starts_with("echo is /") && line.ends_with("/echo"))
+        );
+    }
+
+    #[test]
+    fn test_type_all_terse() {
+        let (output, result) = execute_type(&["-at", "echo"]);
+        assert!(result.is_ok());
+        let kinds: Vec<&str> = output.lines().collect();
+        assert_eq!(kinds[0], "builtin");
+        assert!(kinds[1..].iter().all(|kind| *kind == "file"));
+    }
+
+    #[test]
+    fn test_type_terse_not_found_prints_nothing() {
+        let registry = CommandRegistry::default();
+        let type_cmd = registry.get_builtin("type").unwrap();
+        let mut output = Vec::new();
+        let args = vec!["-t".to_string(), "nonexistent_command_xyz".to_string()];
+
+        let result = type_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+
+        assert!(result.is_ok());
+        assert!(output.is_empty());
+        assert_ne!(registry.get_last_status(), 0);
+    }
 }
 
 #[cfg(test)]
@@ -288,10 +885,40 @@ mod history_tests {
         let history_cmd = registry.get_builtin("history").unwrap();
         let mut output = Vec::new();
         let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
-        let result = history_cmd.execute(&args, &registry, &mut output);
+        let result = history_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
         (String::from_utf8(output).unwrap(), result)
     }
 
+    #[test]
+    fn test_history_truncate_accepts_limit() {
+        let (output, result) = execute_history(&["--truncate", "2"]);
+        assert!(result.is_ok());
+        assert!(!output.contains("echo hello"));
+        assert!(output.contains("pwd"));
+        assert!(output.contains("cd /tmp"));
+    }
+
+    #[test]
+    fn test_history_reverse_lists_newest_first() {
+        let (output, result) = execute_history(&["--reverse"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "    3  cd /tmp\n    2  pwd\n    1  echo hello\n");
+    }
+
+    #[test]
+    fn test_history_reverse_with_limit_keeps_numbers() {
+        let (output, result) = execute_history(&["-R", "2"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "    3  cd /tmp\n    2  pwd\n");
+
+        let (output, _) = execute_history(&["--truncate", "--reverse", "1"]);
+        assert_eq!(output, "    3  cd /tmp\n");
+    }
+
     #[test]
     fn test_history_displays_entries() {
         let (output, result) = execute_history(&[]);
@@ -319,4 +946,1997 @@ mod history_tests {
         assert!(output.contains("pwd"));
         assert!(output.contains("cd /tmp"));
     }
+
+    #[test]
+    fn test_history_clear() {
+        let registry = CommandRegistry::default();
+        registry.add_history_entry("echo hello");
+        registry.add_history_entry("pwd");
+
+        let history_cmd = registry.get_builtin("history").unwrap();
+        let mut output = Vec::new();
+        let result = history_cmd.execute(
+            &["-c".to_string()],
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        assert!(result.is_ok());
+        assert!(registry.get_history().is_empty());
+
+        history_cmd
+            .execute(
+                &[],
+                &registry,
+                &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+            )
+            .unwrap();
+        assert!(output.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod help_tests {
+    use super::*;
+
+    fn execute_help(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let help_cmd = registry.get_builtin("help").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = help_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_help_short_multiple_names() {
+        let (output, result) = execute_help(&["-s", "cd", "pwd"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "cd: cd [dir]\npwd: pwd\n");
+    }
+
+    #[test]
+    fn test_help_full_includes_description() {
+        let (output, result) = execute_help(&["pwd"]);
+        assert!(result.is_ok());
+        assert_eq!(
+            output,
+            "pwd: pwd\n    Print the name of the current working directory.\n"
+        );
+    }
+
+    #[test]
+    fn test_help_no_args_lists_builtins() {
+        let (output, result) = execute_help(&[]);
+        assert!(result.is_ok());
+        assert!(output.contains("cd [dir]"));
+        assert!(output.contains("help [-s] [pattern ...]"));
+    }
+
+    #[test]
+    fn test_help_unknown_topic() {
+        let (_output, result) = execute_help(&["nonexistent_builtin"]);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no help topics"));
+    }
+}
+
+#[cfg(test)]
+mod jobs_tests {
+    use super::*;
+    use codecrafters_shell::commands::ShellExecutor;
+    use codecrafters_shell::parser::parse_input;
+    use std::thread;
+    use std::time::Duration;
+
+    fn execute_jobs(registry: &CommandRegistry) -> String {
+        let jobs_cmd = registry.get_builtin("jobs").unwrap();
+        let mut output = Vec::new();
+        jobs_cmd
+            .execute(
+                &[],
+                registry,
+                &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+            )
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_jobs_lists_running_background_job() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("sleep 1 &").unwrap()).unwrap();
+
+        let output = execute_jobs(&registry);
+        assert_eq!(output, "[1]+  Running                 sleep 1 &\n");
+    }
+
+    #[test]
+    fn test_jobs_json_output() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("sleep 1 &").unwrap()).unwrap();
+
+        let jobs_cmd = registry.get_builtin("jobs").unwrap();
+        let mut output = Vec::new();
+        jobs_cmd
+            .execute(
+                &["--json".to_string()],
+                &registry,
+                &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("{\"id\":1,\"pid\":"), "{}", output);
+        assert!(
+            output.ends_with(",\"state\":\"Running\",\"command\":\"sleep 1\",\"marker\":\"+\"}\n"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_jobs_numbers_and_markers() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("sleep 1 &").unwrap()).unwrap();
+        executor.run(&parse_input("sleep 2 &").unwrap()).unwrap();
+
+        let output = execute_jobs(&registry);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("[1]-  Running"));
+        assert!(lines[1].starts_with("[2]+  Running"));
+    }
+
+    #[test]
+    fn test_jobs_reports_done_once() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("true &").unwrap()).unwrap();
+
+        thread::sleep(Duration::from_millis(200));
+
+        let output = execute_jobs(&registry);
+        assert_eq!(output, "[1]+  Done                    true\n");
+        assert_eq!(execute_jobs(&registry), "");
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+    use std::env;
+
+    fn execute_export(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let export_cmd = registry.get_builtin("export").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = export_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_export_sets_variable() {
+        let (_, result) = execute_export(&["EXPORT_TEST_FOO=bar"]);
+        assert!(result.is_ok());
+        assert_eq!(env::var("EXPORT_TEST_FOO").unwrap(), "bar");
+    }
+
+    #[test]
+    fn test_export_value_may_contain_equals() {
+        let (_, result) = execute_export(&["EXPORT_TEST_OPTS=a=b"]);
+        assert!(result.is_ok());
+        assert_eq!(env::var("EXPORT_TEST_OPTS").unwrap(), "a=b");
+    }
+
+    #[test]
+    fn test_export_bare_name_keeps_value() {
+        execute_export(&["EXPORT_TEST_BARE=kept"]).1.unwrap();
+        let (_, result) = execute_export(&["EXPORT_TEST_BARE"]);
+        assert!(result.is_ok());
+        assert_eq!(env::var("EXPORT_TEST_BARE").unwrap(), "kept");
+    }
+
+    #[test]
+    fn test_export_rejects_invalid_name() {
+        let (_, result) = execute_export(&["=bad"]);
+        match result {
+            Err(e @ ShellError::InvalidIdentifier { .. }) => {
+                assert_eq!(e.to_string(), "export: `=bad': not a valid identifier");
+            }
+            other => panic!("expected InvalidIdentifier, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_export_lists_variables_sorted() {
+        execute_export(&["EXPORT_TEST_LIST=\"quoted\""]).1.unwrap();
+        let (output, result) = execute_export(&[]);
+        assert!(result.is_ok());
+        assert!(output.contains("declare -x EXPORT_TEST_LIST=\"\\\"quoted\\\"\"\n"));
+
+        let names: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("declare -x "))
+            .map(|line| line.split('=').next().unwrap())
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_export_lists_unset_names_without_value() {
+        let registry = CommandRegistry::default();
+        let export_cmd = registry.get_builtin("export").unwrap();
+        let run = |args: &[&str]| {
+            let mut output = Vec::new();
+            let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            export_cmd
+                .execute(
+                    &args,
+                    &registry,
+                    &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+                )
+                .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        run(&["EXPORT_TEST_UNSET"]);
+        assert!(env::var("EXPORT_TEST_UNSET").is_err());
+        assert!(run(&["-p"]).contains("declare -x EXPORT_TEST_UNSET\n"));
+
+        // SAFETY: no other test reads this variable.
+        unsafe { env::set_var("EXPORT_TEST_UNSET", "set") };
+        let output = run(&[]);
+        assert!(output.contains("declare -x EXPORT_TEST_UNSET=\"set\"\n"));
+        assert!(!output.contains("declare -x EXPORT_TEST_UNSET\n"));
+
+        registry
+            .get_builtin("unset")
+            .unwrap()
+            .execute(
+                &["EXPORT_TEST_UNSET".to_string()],
+                &registry,
+                &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut io::sink()),
+            )
+            .unwrap();
+        assert!(!run(&[]).contains("EXPORT_TEST_UNSET"));
+    }
+
+    #[test]
+    fn test_export_is_completable() {
+        let registry = CommandRegistry::default();
+        assert!(registry.get_command_names().contains(&"export".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod unset_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+    use std::env;
+
+    fn execute(registry: &CommandRegistry, name: &str, args: &[&str]) -> ShellResult<ShellStatus> {
+        let cmd = registry.get_builtin(name).unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
+    }
+
+    #[test]
+    fn test_unset_removes_exported_variable() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "export", &["UNSET_TEST_FOO=1"]).unwrap();
+        assert_eq!(env::var("UNSET_TEST_FOO").unwrap(), "1");
+
+        execute(&registry, "unset", &["UNSET_TEST_FOO"]).unwrap();
+        assert!(env::var("UNSET_TEST_FOO").is_err());
+    }
+
+    #[test]
+    fn test_unset_multiple_variables() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "export", &["UNSET_TEST_A=1", "UNSET_TEST_B=2"]).unwrap();
+
+        execute(&registry, "unset", &["UNSET_TEST_A", "UNSET_TEST_B"]).unwrap();
+        assert!(env::var("UNSET_TEST_A").is_err());
+        assert!(env::var("UNSET_TEST_B").is_err());
+    }
+
+    #[test]
+    fn test_unset_missing_variable_succeeds() {
+        let registry = CommandRegistry::default();
+        assert!(execute(&registry, "unset", &["UNSET_TEST_NEVER_SET"]).is_ok());
+    }
+
+    #[test]
+    fn test_unset_rejects_invalid_identifiers() {
+        let registry = CommandRegistry::default();
+        for name in ["FOO=1", "1FOO"] {
+            let result = execute(&registry, "unset", &[name]);
+            assert!(matches!(result, Err(ShellError::InvalidIdentifier { .. })));
+        }
+    }
+}
+
+#[cfg(test)]
+mod env_tests {
+    use super::*;
+
+    fn execute_env(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let env_cmd = registry.get_builtin("env").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = env_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_env_lists_sorted_environment() {
+        let (output, result) = execute_env(&[]);
+        assert!(result.is_ok());
+        assert!(output.lines().any(|line| line.starts_with("PATH=")));
+
+        let keys: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    fn test_env_lists_extra_assignments() {
+        let (output, result) = execute_env(&["ENV_TEST_LISTED=yes"]);
+        assert!(result.is_ok());
+        assert!(output.contains("ENV_TEST_LISTED=yes\n"));
+        assert!(std::env::var("ENV_TEST_LISTED").is_err());
+    }
+
+    #[test]
+    fn test_env_runs_command_with_assignment() {
+        let (output, result) =
+            execute_env(&["ENV_TEST_CHILD=visible", "sh", "-c", "echo $ENV_TEST_CHILD"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "visible\n");
+        assert!(std::env::var("ENV_TEST_CHILD").is_err());
+    }
+
+    #[test]
+    fn test_env_sets_command_status() {
+        let registry = CommandRegistry::default();
+        let env_cmd = registry.get_builtin("env").unwrap();
+        let args = vec!["false".to_string()];
+        let result = env_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut io::sink()),
+        );
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 1);
+    }
+
+    #[test]
+    fn test_env_unknown_command() {
+        let (_, result) = execute_env(&["nonexistent_command_xyz"]);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod alias_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+
+    fn execute(
+        registry: &CommandRegistry,
+        name: &str,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let cmd = registry.get_builtin(name).unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_alias_defines_and_lists_sorted() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "alias", &["ll=ls -la", "gs=git status"])
+            .1
+            .unwrap();
+
+        let (output, result) = execute(&registry, "alias", &[]);
+        assert!(result.is_ok());
+        assert_eq!(output, "alias gs='git status'\nalias ll='ls -la'\n");
+    }
+
+    #[test]
+    fn test_alias_quotes_single_quotes() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "alias", &["say=echo it's"]).1.unwrap();
+
+        let (output, _) = execute(&registry, "alias", &["say"]);
+        assert_eq!(output, "alias say='echo it'\\''s'\n");
+    }
+
+    #[test]
+    fn test_alias_unknown_name() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute(&registry, "alias", &["missing"]);
+        assert!(matches!(result, Err(ShellError::AliasNotFound { .. })));
+    }
+
+    #[test]
+    fn test_unalias_removes_alias() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "alias", &["ll=ls -la"]).1.unwrap();
+
+        let (_, result) = execute(&registry, "unalias", &["ll"]);
+        assert!(result.is_ok());
+        assert_eq!(registry.get_alias("ll"), None);
+
+        let (_, result) = execute(&registry, "unalias", &["ll"]);
+        assert_eq!(result.unwrap_err().to_string(), "unalias: ll: not found");
+    }
+
+    #[test]
+    fn test_type_reports_alias() {
+        let registry = CommandRegistry::default();
+        execute(&registry, "alias", &["ll=ls -la"]).1.unwrap();
+
+        let (output, _) = execute(&registry, "type", &["ll"]);
+        assert_eq!(output, "ll is aliased to `ls -la'\n");
+    }
+}
+
+#[cfg(test)]
+mod printf_tests {
+    use super::*;
+
+    fn execute_printf(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let printf_cmd = registry.get_builtin("printf").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = printf_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_printf_string_and_integer() {
+        let (output, result) = execute_printf(&["%s=%d\\n", "x", "42"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "x=42\n");
+    }
+
+    #[test]
+    fn test_printf_reuses_format() {
+        let (output, _) = execute_printf(&["[%s]", "a", "b", "c"]);
+        assert_eq!(output, "[a][b][c]");
+    }
+
+    #[test]
+    fn test_printf_width() {
+        let (output, _) = execute_printf(&["%-4s|%3d", "ab", "7"]);
+        assert_eq!(output, "ab  |  7");
+    }
+
+    #[test]
+    fn test_printf_positional_strings() {
+        let (output, _) = execute_printf(&["%s-%s\\n", "a", "b"]);
+        assert_eq!(output, "a-b\n");
+    }
+
+    #[test]
+    fn test_printf_integer_line() {
+        let (output, _) = execute_printf(&["%d\\n", "42"]);
+        assert_eq!(output, "42\n");
+    }
+
+    #[test]
+    fn test_printf_reuses_format_for_extra_args() {
+        let (output, _) = execute_printf(&["%s=%d\\n", "a", "1", "b", "2", "c"]);
+        assert_eq!(output, "a=1\nb=2\nc=0\n");
+    }
+
+    #[test]
+    fn test_printf_hex_octal_and_char() {
+        let (output, _) = execute_printf(&["%x %X %o %c", "255", "255", "8", "hello"]);
+        assert_eq!(output, "ff FF 10 h");
+    }
+
+    #[test]
+    fn test_printf_width_zero_pad_and_precision() {
+        let (output, _) =
+            execute_printf(&["[%5d][%05d][%.2f][%.3s]", "42", "-42", "3.14159", "abcdef"]);
+        assert_eq!(output, "[   42][-0042][3.14][abc]");
+    }
+
+    #[test]
+    fn test_printf_float_default_precision() {
+        let (output, _) = execute_printf(&["%f", "1.5"]);
+        assert_eq!(output, "1.500000");
+    }
+
+    #[test]
+    fn test_printf_invalid_float() {
+        let (output, result) = execute_printf(&["%f", "abc"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "0.000000");
+    }
+
+    #[test]
+    fn test_printf_octal_and_control_escapes() {
+        let (output, _) = execute_printf(&["\\101\\t\\0102\\a"]);
+        assert_eq!(output, "A\tB\x07");
+    }
+
+    #[test]
+    fn test_printf_quote_spaces() {
+        let (output, _) = execute_printf(&["%q", "a b"]);
+        assert_eq!(output, "a\\ b");
+    }
+
+    #[test]
+    fn test_printf_quote_empty() {
+        let (output, _) = execute_printf(&["%q", ""]);
+        assert_eq!(output, "''");
+    }
+
+    #[test]
+    fn test_printf_quote_single_quote() {
+        let (output, _) = execute_printf(&["%q", "it's"]);
+        assert_eq!(output, "it\\'s");
+    }
+
+    #[test]
+    fn test_printf_quote_special_characters() {
+        let (output, _) = execute_printf(&["%q", "$HOME;rm *&"]);
+        assert_eq!(output, r"\$HOME\;rm\ \*\&");
+
+        let (output, _) = execute_printf(&["%q", "safe/path-1.txt"]);
+        assert_eq!(output, "safe/path-1.txt");
+    }
+
+    #[test]
+    fn test_printf_quote_control_characters() {
+        let (output, _) = execute_printf(&["%q", "a\nb"]);
+        assert_eq!(output, "$'a\\nb'");
+    }
+
+    #[test]
+    fn test_printf_invalid_number() {
+        let registry = CommandRegistry::default();
+        let printf_cmd = registry.get_builtin("printf").unwrap();
+        let mut output = Vec::new();
+        let mut error = Vec::new();
+        let args: Vec<String> = ["[%d|%.1f]\\n", "abc", "x"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let result = printf_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut error),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(String::from_utf8(output).unwrap(), "[0|0.0]\n");
+        assert_eq!(
+            String::from_utf8(error).unwrap(),
+            "printf: abc: invalid number\nprintf: x: invalid number\n"
+        );
+        assert_eq!(registry.get_last_status(), 1);
+    }
+
+    #[test]
+    fn test_printf_requires_format() {
+        let (_, result) = execute_printf(&[]);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod source_tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn execute_source(name: &str, args: &[&str]) -> ShellResult<ShellStatus> {
+        let registry = CommandRegistry::default();
+        let source_cmd = registry.get_builtin(name).unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        source_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
+    }
+
+    #[test]
+    fn test_source_exports_into_current_shell() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "export SOURCE_TEST_X=1\n").unwrap();
+
+        let result = execute_source("source", &[script.to_str().unwrap()]);
+
+        assert!(result.is_ok());
+        assert_eq!(env::var("SOURCE_TEST_X").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_dot_skips_comments_and_continues_past_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("script.sh");
+        let output = temp_dir.path().join("out.txt");
+        fs::write(
+            &script,
+            format!(
+                "# comment\n\nnonexistent_command_xyz\necho $1 > {}\n",
+                output.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+
+        let result = execute_source(".", &[script.to_str().unwrap(), "arg"]);
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(&output).unwrap(), "arg\n");
+    }
+
+    #[test]
+    fn test_source_stops_at_exit() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "exit\nexport SOURCE_TEST_AFTER_EXIT=1\n").unwrap();
+
+        let result = execute_source("source", &[script.to_str().unwrap()]);
+
+        assert_eq!(result.unwrap(), ShellStatus::Exit(0));
+        assert!(env::var("SOURCE_TEST_AFTER_EXIT").is_err());
+    }
+
+    #[test]
+    fn test_source_missing_file() {
+        let result = execute_source("source", &["/nonexistent/script.sh"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_source_requires_filename() {
+        let result = execute_source(".", &[]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            ".: filename argument required"
+        );
+    }
+}
+
+#[cfg(test)]
+mod wait_tests {
+    use super::*;
+    use codecrafters_shell::commands::ShellExecutor;
+    use codecrafters_shell::jobs::JobState;
+    use codecrafters_shell::parser::parse_input;
+    use std::process::Command as ProcessCommand;
+
+    fn execute_wait(registry: &CommandRegistry, args: &[&str]) -> ShellResult<ShellStatus> {
+        let wait_cmd = registry.get_builtin("wait").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        wait_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
+    }
+
+    #[test]
+    fn test_wait_for_untracked_child_pid() {
+        let registry = CommandRegistry::default();
+        // Only the raw PID is kept, so the shell's waitpid reaps the child.
+        let pid = ProcessCommand::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .unwrap()
+            .id();
+
+        let result = execute_wait(&registry, &[&pid.to_string()]);
+
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 3);
+    }
+
+    #[test]
+    fn test_wait_for_job_spec() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(&parse_input("sh -c 'exit 4' &").unwrap())
+            .unwrap();
+
+        let result = execute_wait(&registry, &["%1"]);
+
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 4);
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_wait_without_args_waits_for_all_jobs() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("sleep 0.1 &").unwrap()).unwrap();
+        executor.run(&parse_input("sleep 0.1 &").unwrap()).unwrap();
+
+        assert!(execute_wait(&registry, &[]).is_ok());
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_wait_for_job_pid_marks_it_done() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(&parse_input("sh -c 'sleep 0.05; exit 6' &").unwrap())
+            .unwrap();
+        let pid = registry.list_jobs().unwrap()[0].pid;
+
+        assert!(execute_wait(&registry, &[&pid.to_string()]).is_ok());
+        assert_eq!(registry.get_last_status(), 6);
+        assert!(registry.list_jobs().unwrap().is_empty());
+        assert_eq!(
+            execute_wait(&registry, &["%1"]).unwrap_err().to_string(),
+            "wait: %1: no such job"
+        );
+    }
+
+    #[test]
+    fn test_wait_without_args_skips_stopped_jobs() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(&parse_input("sh -c 'kill -STOP $$'").unwrap())
+            .unwrap();
+        executor.run(&parse_input("sleep 0.05 &").unwrap()).unwrap();
+
+        assert!(execute_wait(&registry, &[]).is_ok());
+        let jobs = registry.list_jobs().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].state, JobState::Stopped);
+
+        executor.run(&parse_input("kill -9 %1").unwrap()).unwrap();
+        assert!(execute_wait(&registry, &["%1"]).is_ok());
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_wait_returns_when_job_stops() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(&parse_input("sh -c 'kill -STOP $$' &").unwrap())
+            .unwrap();
+
+        assert!(execute_wait(&registry, &["%1"]).is_ok());
+        assert_eq!(registry.get_last_status(), 128 + libc::SIGSTOP);
+        let jobs = registry.list_jobs().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].state, JobState::Stopped);
+
+        executor.run(&parse_input("kill -9 %1").unwrap()).unwrap();
+        assert!(execute_wait(&registry, &["%1"]).is_ok());
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_wait_for_non_child_pid_sets_127() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input("wait 1").unwrap());
+
+        assert!(result.is_err());
+        assert_eq!(registry.get_last_status(), 127);
+    }
+
+    #[test]
+    fn test_wait_rejects_invalid_argument() {
+        let registry = CommandRegistry::default();
+        let result = execute_wait(&registry, &["abc"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "wait: abc: not a pid or valid job spec"
+        );
+    }
+}
+
+#[cfg(test)]
+mod hash_tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    static PATH_LOCK: Mutex<()> = Mutex::new(());
+
+    fn execute_hash(
+        registry: &CommandRegistry,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let hash_cmd = registry.get_builtin("hash").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = hash_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    fn install(dir: &Path, name: &str) -> String {
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    /// Runs `test` with each directory prepended to `$PATH`.
+    fn with_path_dirs(dirs: &[&Path], test: impl FnOnce()) {
+        let _lock = PATH_LOCK.lock().unwrap();
+        let original_path = env::var("PATH").unwrap_or_default();
+        let mut path: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        path.push(original_path.clone());
+        // SAFETY: PATH_LOCK serializes writers; other tests only read PATH
+        // for programs like `ls`, which prepending directories does not hide.
+        unsafe { env::set_var("PATH", path.join(":")) };
+        test();
+        unsafe { env::set_var("PATH", original_path) };
+    }
+
+    #[test]
+    fn test_hash_r_picks_up_new_binary() {
+        let bin_dir = TempDir::new().unwrap();
+        with_path_dirs(&[bin_dir.path()], || {
+            let registry = CommandRegistry::default();
+            let path = install(bin_dir.path(), "zz_hash_new_tool");
+
+            let (listing, _) = execute_hash(&registry, &[]);
+            assert!(!listing.contains("zz_hash_new_tool"));
+
+            execute_hash(&registry, &["-r"]).1.unwrap();
+            let (listing, _) = execute_hash(&registry, &[]);
+            assert!(listing.contains(&format!("zz_hash_new_tool\t{}\n", path)));
+        });
+    }
+
+    #[test]
+    fn test_hashed_location_is_used_until_rehash() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let old_path = install(second.path(), "zz_hash_shadowed");
+
+        with_path_dirs(&[first.path(), second.path()], || {
+            let registry = CommandRegistry::default();
+            assert_eq!(
+                registry.get_executable_path("zz_hash_shadowed"),
+                Some(old_path.clone())
+            );
+
+            // A new binary earlier on PATH is ignored while the old one is remembered
+            let new_path = install(first.path(), "zz_hash_shadowed");
+            assert_eq!(
+                registry.get_executable_path("zz_hash_shadowed"),
+                Some(old_path.clone())
+            );
+
+            execute_hash(&registry, &["-r"]).1.unwrap();
+            assert_eq!(
+                registry.get_executable_path("zz_hash_shadowed"),
+                Some(new_path)
+            );
+        });
+    }
+
+    #[test]
+    fn test_hash_name_remembers_it() {
+        let bin_dir = TempDir::new().unwrap();
+        with_path_dirs(&[bin_dir.path()], || {
+            let registry = CommandRegistry::default();
+            let path = install(bin_dir.path(), "zz_hash_named");
+
+            execute_hash(&registry, &["zz_hash_named"]).1.unwrap();
+            let (listing, _) = execute_hash(&registry, &[]);
+            assert!(listing.contains(&format!("zz_hash_named\t{}\n", path)));
+        });
+    }
+
+    #[test]
+    fn test_hash_missing_name_fails() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute_hash(&registry, &["zz_hash_missing"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "hash: zz_hash_missing: not found"
+        );
+    }
+}
+
+#[cfg(test)]
+mod which_tests {
+    use super::*;
+
+    fn execute_which(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let which_cmd = registry.get_builtin("which").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = which_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_which_prints_executable_path() {
+        let (output, result) = execute_which(&["ls"]);
+        assert!(result.is_ok());
+        assert!(output.trim_end().ends_with("/ls"));
+    }
+
+    #[test]
+    fn test_which_missing_command() {
+        let (output, result) = execute_which(&["definitely_missing"]);
+        assert_eq!(output, "");
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("not found"));
+        assert!(err.contains("definitely_missing"));
+    }
+
+    #[test]
+    fn test_which_ignores_builtins() {
+        let (output, result) = execute_which(&["cd"]);
+        assert_eq!(output, "");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_which_all_flags_builtins() {
+        let (output, result) = execute_which(&["-a", "cd"]);
+        assert!(result.is_ok());
+        assert_eq!(output, "cd: shell builtin\n");
+    }
+}
+
+#[cfg(test)]
+mod set_tests {
+    use super::*;
+    use codecrafters_shell::options::ShellOption;
+
+    fn execute_set(
+        registry: &CommandRegistry,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let set_cmd = registry.get_builtin("set").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = set_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_set_toggles_history_option() {
+        let registry = CommandRegistry::default();
+        assert!(registry.get_option(ShellOption::History));
+
+        execute_set(&registry, &["+o", "history"]).1.unwrap();
+        assert!(!registry.get_option(ShellOption::History));
+
+        execute_set(&registry, &["-o", "history"]).1.unwrap();
+        assert!(registry.get_option(ShellOption::History));
+    }
+
+    #[test]
+    fn test_set_lists_options() {
+        let registry = CommandRegistry::default();
+        execute_set(&registry, &["+o", "history"]).1.unwrap();
+
+        let (output, _) = execute_set(&registry, &["-o"]);
+        assert!(output.contains("history        \toff\n"));
+
+        let (output, _) = execute_set(&registry, &["+o"]);
+        assert!(output.contains("set +o history\n"));
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_option() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute_set(&registry, &["-o", "bogus"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "set: bogus: invalid option name"
+        );
+    }
+
+    #[test]
+    fn test_set_single_letter_flags() {
+        let registry = CommandRegistry::default();
+
+        execute_set(&registry, &["-ex"]).1.unwrap();
+        assert!(registry.get_option(ShellOption::Errexit));
+        assert!(registry.get_option(ShellOption::Xtrace));
+
+        execute_set(&registry, &["+x", "-C"]).1.unwrap();
+        assert!(registry.get_option(ShellOption::Errexit));
+        assert!(!registry.get_option(ShellOption::Xtrace));
+        assert!(registry.get_option(ShellOption::Noclobber));
+
+        execute_set(&registry, &["-f"]).1.unwrap();
+        assert!(registry.get_option(ShellOption::Noglob));
+
+        let (_, result) = execute_set(&registry, &["-q"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "set: -q: invalid option name"
+        );
+    }
+
+    #[test]
+    fn test_set_without_arguments_lists_variables() {
+        let registry = CommandRegistry::default();
+        // SAFETY: the names are unique to this test.
+        unsafe {
+            std::env::set_var("SET_TEST_PLAIN", "value");
+            std::env::set_var("SET_TEST_SPACED", "two words");
+        }
+
+        let (output, result) = execute_set(&registry, &[]);
+
+        assert!(result.is_ok());
+        assert!(output.contains("SET_TEST_PLAIN=value\nSET_TEST_SPACED='two words'\n"));
+    }
+}
+
+#[cfg(test)]
+mod shopt_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+    use codecrafters_shell::options::ShellOption;
+
+    fn execute_shopt(
+        registry: &CommandRegistry,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let shopt_cmd = registry.get_builtin("shopt").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = shopt_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_shopt_toggles_globstar() {
+        let registry = CommandRegistry::default();
+        assert!(!registry.get_option(ShellOption::Globstar));
+
+        execute_shopt(&registry, &["-s", "globstar"]).1.unwrap();
+        assert!(registry.get_option(ShellOption::Globstar));
+
+        execute_shopt(&registry, &["-u", "globstar"]).1.unwrap();
+        assert!(!registry.get_option(ShellOption::Globstar));
+    }
+
+    #[test]
+    fn test_shopt_lists_options() {
+        let registry = CommandRegistry::default();
+
+        let (output, _) = execute_shopt(&registry, &[]);
+        assert_eq!(output, "cdspell        \toff\nglobstar       \toff\n");
+
+        execute_shopt(&registry, &["-s", "globstar"]).1.unwrap();
+        let (output, _) = execute_shopt(&registry, &["-p"]);
+        assert_eq!(output, "shopt -u cdspell\nshopt -s globstar\n");
+    }
+
+    #[test]
+    fn test_shopt_query_fails_when_option_is_off() {
+        let registry = CommandRegistry::default();
+
+        execute_shopt(&registry, &["globstar"]).1.unwrap();
+        assert_eq!(registry.get_last_status(), 1);
+    }
+
+    #[test]
+    fn test_shopt_rejects_unknown_and_set_options() {
+        let registry = CommandRegistry::default();
+
+        for name in ["nosuchopt", "history"] {
+            let (_, result) = execute_shopt(&registry, &["-s", name]);
+            assert!(matches!(result, Err(ShellError::InvalidOption { .. })));
+        }
+    }
+}
+
+#[cfg(test)]
+mod fc_tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn execute_fc(registry: &CommandRegistry, args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let fc_cmd = registry.get_builtin("fc").unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = fc_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn test_fc_s_reexecutes_last_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_file = temp_dir.path().join("out.txt");
+        let command = format!("echo again >> {}", output_file.to_str().unwrap());
+
+        let registry = CommandRegistry::default();
+        registry.add_history_entry(&command);
+        registry.add_history_entry("fc -s");
+
+        let (output, result) = execute_fc(&registry, &["-s"]);
+
+        assert!(result.is_ok());
+        assert_eq!(output, format!("{}\n", command));
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "again\n");
+        assert_eq!(registry.get_history(), vec![command.clone(), command]);
+    }
+
+    #[test]
+    fn test_fc_e_dash_applies_substitution_and_prefix() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_file = temp_dir.path().join("out.txt");
+
+        let registry = CommandRegistry::default();
+        registry.add_history_entry(&format!("echo old > {}", output_file.to_str().unwrap()));
+        registry.add_history_entry("pwd");
+
+        let (_, result) = execute_fc(&registry, &["-e", "-", "old=new", "echo"]);
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn test_fc_without_history_fails() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute_fc(&registry, &["-s"]);
+        assert_eq!(result.unwrap_err().to_string(), "fc: no command found");
+    }
+
+    #[test]
+    fn test_fc_requires_supported_mode() {
+        let registry = CommandRegistry::default();
+        let (_, result) = execute_fc(&registry, &["-l"]);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod read_tests {
+    use super::*;
+    use std::env;
+    use std::io::Cursor;
+
+    fn execute_read(input: &mut Cursor<Vec<u8>>, args: &[&str]) -> (ShellResult<ShellStatus>, i32) {
+        let registry = CommandRegistry::default();
+        let read_cmd = registry.get_builtin("read").unwrap();
+        let mut output = Vec::new();
+        let mut error = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut ctx = IoContext::new(input, &mut output, &mut error);
+        let result = read_cmd.execute(&args, &registry, &mut ctx);
+        (result, registry.get_last_status())
+    }
+
+    #[test]
+    fn test_read_line_into_variable() {
+        let mut input = Cursor::new(b"hello world\nnext\n".to_vec());
+        let (result, status) = execute_read(&mut input, &["READ_TEST_LINE"]);
+
+        assert!(result.is_ok());
+        assert_eq!(status, 0);
+        assert_eq!(env::var("READ_TEST_LINE").unwrap(), "hello world");
+    }
+
+    #[test]
+    fn test_read_splits_fields_with_rest_in_last_name() {
+        let mut input = Cursor::new(b"  one two three four\n".to_vec());
+        execute_read(&mut input, &["READ_TEST_FIRST", "READ_TEST_REST"])
+            .0
+            .unwrap();
+
+        assert_eq!(env::var("READ_TEST_FIRST").unwrap(), "one");
+        assert_eq!(env::var("READ_TEST_REST").unwrap(), "two three four");
+    }
+
+    #[test]
+    fn test_read_nul_delimited_records() {
+        let mut input = Cursor::new(b"first file\0second\0".to_vec());
+
+        execute_read(&mut input, &["-d", "", "READ_TEST_NUL"])
+            .0
+            .unwrap();
+        assert_eq!(env::var("READ_TEST_NUL").unwrap(), "first file");
+
+        execute_read(&mut input, &["-d", "", "READ_TEST_NUL"])
+            .0
+            .unwrap();
+        assert_eq!(env::var("READ_TEST_NUL").unwrap(), "second");
+    }
+
+    #[test]
+    fn test_read_colon_delimited_records() {
+        let mut input = Cursor::new(b"/usr/bin:/bin".to_vec());
+
+        let (_, status) = execute_read(&mut input, &["-d", ":", "READ_TEST_COLON"]);
+        assert_eq!(status, 0);
+        assert_eq!(env::var("READ_TEST_COLON").unwrap(), "/usr/bin");
+
+        // The last record has no trailing delimiter, so read reports EOF.
+        let (_, status) = execute_read(&mut input, &["-d", ":", "READ_TEST_COLON"]);
+        assert_eq!(status, 1);
+        assert_eq!(env::var("READ_TEST_COLON").unwrap(), "/bin");
+    }
+
+    #[test]
+    fn test_read_backslash_escapes_unless_raw() {
+        let mut input = Cursor::new(b"a\\ b\\\nc\nx\\y\n".to_vec());
+
+        execute_read(&mut input, &["READ_TEST_ESCAPE"]).0.unwrap();
+        assert_eq!(env::var("READ_TEST_ESCAPE").unwrap(), "a bc");
+
+        execute_read(&mut input, &["-r", "READ_TEST_ESCAPE"])
+            .0
+            .unwrap();
+        assert_eq!(env::var("READ_TEST_ESCAPE").unwrap(), "x\\y");
+    }
+
+    #[test]
+    fn test_read_defaults_to_reply() {
+        let mut input = Cursor::new(b"  kept as is  \n".to_vec());
+        execute_read(&mut input, &[]).0.unwrap();
+
+        assert_eq!(env::var("REPLY").unwrap(), "  kept as is  ");
+    }
+
+    #[test]
+    fn test_read_at_eof_fails() {
+        let mut input = Cursor::new(Vec::new());
+        let (result, status) = execute_read(&mut input, &["READ_TEST_EOF"]);
+
+        assert!(result.is_ok());
+        assert_eq!(status, 1);
+    }
+
+    #[test]
+    fn test_read_d_requires_argument() {
+        let mut input = Cursor::new(Vec::new());
+        let (result, _) = execute_read(&mut input, &["-d"]);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "read: -d: option requires an argument"
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_invalid_name() {
+        let mut input = Cursor::new(b"x\n".to_vec());
+        let (result, _) = execute_read(&mut input, &["1bad"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_u_reads_from_open_descriptor() {
+        use std::os::fd::AsRawFd;
+
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), "first line\nsecond line\n").unwrap();
+        let file = std::fs::File::open(temp.path()).unwrap();
+        let fd = file.as_raw_fd().to_string();
+
+        let mut stdin = Cursor::new(b"from stdin\n".to_vec());
+        let (result, status) = execute_read(&mut stdin, &["-u", &fd, "READ_TEST_FD_FIRST"]);
+        assert!(result.is_ok());
+        assert_eq!(status, 0);
+        assert_eq!(env::var("READ_TEST_FD_FIRST").unwrap(), "first line");
+
+        // Only the first line was consumed, and the descriptor is still open
+        execute_read(&mut stdin, &["-u", &fd, "READ_TEST_FD_SECOND"])
+            .0
+            .unwrap();
+        assert_eq!(env::var("READ_TEST_FD_SECOND").unwrap(), "second line");
+        assert_eq!(stdin.position(), 0);
+    }
+
+    #[test]
+    fn test_read_u_rejects_bad_descriptor() {
+        let mut input = Cursor::new(Vec::new());
+        for fd in ["999", "-1", "x"] {
+            let (result, _) = execute_read(&mut input, &["-u", fd]);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("read: {}: invalid file descriptor", fd)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod io_context_tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    /// Runs a builtin with the given stdin, returning its stdout and stderr.
+    fn run(registry: &CommandRegistry, name: &str, args: &[&str], input: &str) -> (String, String) {
+        let command = registry.get_builtin(name).unwrap();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut input = Cursor::new(input.as_bytes().to_vec());
+        let mut output = Vec::new();
+        let mut error = Vec::new();
+
+        let mut ctx = IoContext::new(&mut input, &mut output, &mut error);
+        command.execute(&args, registry, &mut ctx).unwrap();
+
+        (
+            String::from_utf8(output).unwrap(),
+            String::from_utf8(error).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_builtins_write_to_context_output() {
+        let registry = CommandRegistry::default();
+        registry.add_history_entry("echo hi");
+
+        let cases: [(&str, &[&str]); 5] = [
+            ("echo", &["hi"]),
+            ("pwd", &[]),
+            ("type", &["echo"]),
+            ("history", &[]),
+            ("help", &["-s", "echo"]),
+        ];
+        for (name, args) in cases {
+            let (output, error) = run(&registry, name, args, "");
+            assert!(!output.is_empty(), "{} wrote nothing", name);
+            assert!(error.is_empty(), "{} wrote to stderr: {}", name, error);
+        }
+    }
+
+    #[test]
+    fn test_read_takes_context_input() {
+        let registry = CommandRegistry::default();
+        let (output, _) = run(&registry, "read", &["IO_CONTEXT_READ"], "from ctx\n");
+
+        assert!(output.is_empty());
+        assert_eq!(env::var("IO_CONTEXT_READ").unwrap(), "from ctx");
+    }
+
+    #[test]
+    fn test_source_reports_errors_to_context_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "no_such_command_xyz\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let (_, error) = run(&registry, "source", &[script.to_str().unwrap()], "");
+
+        assert_eq!(error, "no_such_command_xyz: command not found\n");
+    }
+}
+
+#[cfg(test)]
+mod test_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+    use tempfile::NamedTempFile;
+
+    /// Runs `name` with `args` and returns the resulting `$?`, or the error.
+    fn evaluate(name: &str, args: &[&str]) -> ShellResult<i32> {
+        let registry = CommandRegistry::default();
+        let test_cmd = registry.get_builtin(name).unwrap();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        test_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut io::sink()),
+        )?;
+        Ok(registry.get_last_status())
+    }
+
+    #[test]
+    fn test_file_operators_on_temp_file() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let dir = file.path().parent().unwrap().to_str().unwrap();
+
+        assert_eq!(evaluate("test", &["-e", path]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["-f", path]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["-d", path]).unwrap(), 1);
+        assert_eq!(evaluate("test", &["-d", dir]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["-e", "/no/such/file/xyz"]).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_string_operators() {
+        assert_eq!(evaluate("test", &["abc", "=", "abc"]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["abc", "!=", "abc"]).unwrap(), 1);
+        assert_eq!(evaluate("test", &["-z", ""]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["-n", ""]).unwrap(), 1);
+        assert_eq!(evaluate("test", &["word"]).unwrap(), 0);
+        assert_eq!(evaluate("test", &[]).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_numeric_comparison() {
+        assert_eq!(evaluate("test", &["3", "-lt", "10"]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["3", "-gt", "10"]).unwrap(), 1);
+        assert_eq!(evaluate("test", &["7", "-eq", "7"]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["7", "-ne", "7"]).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_malformed_integer_is_an_error() {
+        let err = evaluate("test", &["abc", "-eq", "1"]).unwrap_err();
+        assert!(matches!(err, ShellError::TestIntegerExpected { .. }));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_negation() {
+        assert_eq!(evaluate("test", &["!", "-z", "x"]).unwrap(), 0);
+        assert_eq!(evaluate("test", &["!", "a", "=", "a"]).unwrap(), 1);
+        assert_eq!(evaluate("test", &["!", "=", "!"]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_bracket_requires_closing_bracket() {
+        assert_eq!(evaluate("[", &["1", "-le", "2", "]"]).unwrap(), 0);
+        let err = evaluate("[", &["1", "-le", "2"]).unwrap_err();
+        assert!(matches!(err, ShellError::TestMissingBracket));
+    }
+}
+
+#[cfg(test)]
+mod realpath_tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn execute_realpath(args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        let result = registry.get_builtin("realpath").unwrap().execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    fn linked_dir() -> (TempDir, String) {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("target")).unwrap();
+        std::os::unix::fs::symlink(temp.path().join("target"), temp.path().join("link")).unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        (temp, root.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_realpath_resolves_symlinks_and_dot_dot() {
+        let (_temp, root) = linked_dir();
+        let (output, result) = execute_realpath(&[&format!("{}/link/../link/.", root)]);
+
+        assert!(result.is_ok());
+        assert_eq!(output, format!("{}/target\n", root));
+    }
+
+    #[test]
+    fn test_realpath_rejects_missing_path() {
+        let (_temp, root) = linked_dir();
+        let missing = format!("{}/link/missing/file", root);
+        let (output, result) = execute_realpath(&[&missing, &root]);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("realpath: {}: No such file or directory", missing)
+        );
+        // Paths that do resolve are still printed
+        assert_eq!(output, format!("{}\n", root));
+    }
+
+    #[test]
+    fn test_realpath_m_allows_missing_components() {
+        let (_temp, root) = linked_dir();
+        let (output, result) =
+            execute_realpath(&["-m", &format!("{}/link/missing/../new/file", root)]);
+
+        assert!(result.is_ok());
+        assert_eq!(output, format!("{}/target/new/file\n", root));
+        assert!(!Path::new(&format!("{}/target/new", root)).exists());
+    }
+
+    #[test]
+    fn test_realpath_requires_an_operand() {
+        let (_, result) = execute_realpath(&[]);
+        assert_eq!(result.unwrap_err().to_string(), "realpath: missing operand");
+    }
+}
+
+#[cfg(test)]
+mod pathname_tests {
+    use super::*;
+
+    fn execute(name: &str, args: &[&str]) -> (String, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        let result = registry.get_builtin(name).unwrap().execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    fn basename(args: &[&str]) -> String {
+        execute("basename", args).0
+    }
+
+    fn dirname(path: &str) -> String {
+        execute("dirname", &[path]).0
+    }
+
+    #[test]
+    fn test_basename_strips_directories_and_trailing_slashes() {
+        assert_eq!(basename(&["/a/b/c.txt"]), "c.txt\n");
+        assert_eq!(basename(&["/a/b/"]), "b\n");
+        assert_eq!(basename(&["plain"]), "plain\n");
+        assert_eq!(basename(&["/"]), "/\n");
+        assert_eq!(basename(&["///"]), "/\n");
+    }
+
+    #[test]
+    fn test_basename_strips_suffix_unless_it_is_the_whole_name() {
+        assert_eq!(basename(&["/a/b/c.txt", ".txt"]), "c\n");
+        assert_eq!(basename(&["/a/b/c.txt/", ".txt"]), "c\n");
+        assert_eq!(basename(&["/a/b/c.txt", ".rs"]), "c.txt\n");
+        assert_eq!(basename(&["/a/.txt", ".txt"]), ".txt\n");
+    }
+
+    #[test]
+    fn test_basename_checks_operand_count() {
+        assert_eq!(
+            execute("basename", &[]).1.unwrap_err().to_string(),
+            "basename: missing operand"
+        );
+        assert_eq!(
+            execute("basename", &["a", "b", "c"])
+                .1
+                .unwrap_err()
+                .to_string(),
+            "basename: extra operand `c'"
+        );
+    }
+
+    #[test]
+    fn test_dirname_removes_last_component() {
+        assert_eq!(dirname("/a/b"), "/a\n");
+        assert_eq!(dirname("/a/b/"), "/a\n");
+        assert_eq!(dirname("/a//b"), "/a\n");
+        assert_eq!(dirname("a/b"), "a\n");
+    }
+
+    #[test]
+    fn test_dirname_of_root_and_bare_names() {
+        assert_eq!(dirname("/"), "/\n");
+        assert_eq!(dirname("//"), "/\n");
+        assert_eq!(dirname("/a"), "/\n");
+        assert_eq!(dirname("file"), ".\n");
+        assert_eq!(dirname(""), ".\n");
+        assert_eq!(execute("dirname", &["/a/b", "c"]).0, "/a\n.\n");
+    }
+}
+
+#[cfg(test)]
+mod sleep_tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn execute_sleep(args: &[&str]) -> (Duration, ShellResult<ShellStatus>) {
+        let registry = CommandRegistry::default();
+        let sleep_cmd = registry.get_builtin("sleep").unwrap();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let start = Instant::now();
+        let result = sleep_cmd.execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut io::sink()),
+        );
+        (start.elapsed(), result)
+    }
+
+    #[test]
+    fn test_sleep_integer_seconds() {
+        let (elapsed, result) = execute_sleep(&["0"]);
+        assert_eq!(result.unwrap(), ShellStatus::Continue);
+        assert!(elapsed < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_sleep_fractional_seconds() {
+        let (elapsed, result) = execute_sleep(&["0.05"]);
+        assert_eq!(result.unwrap(), ShellStatus::Continue);
+        assert!(elapsed >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_sleep_with_units_adds_up() {
+        let (elapsed, result) = execute_sleep(&["30ms", "0.02s"]);
+        assert_eq!(result.unwrap(), ShellStatus::Continue);
+        assert!(elapsed >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_sleep_invalid_duration() {
+        for arg in ["abc", "-1", "1x", "inf", ""] {
+            let (_, result) = execute_sleep(&[arg]);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("sleep: invalid time interval `{}'", arg)
+            );
+        }
+        assert_eq!(
+            execute_sleep(&[]).1.unwrap_err().to_string(),
+            "sleep: missing operand"
+        );
+    }
+
+    #[test]
+    fn test_sleep_too_long_is_invalid() {
+        let (_, result) = execute_sleep(&["10000000000000000000"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "sleep: invalid time interval `10000000000000000000'"
+        );
+
+        let (_, result) = execute_sleep(&["5000000000000000000", "5000000000000000000"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "sleep: invalid time interval `5000000000000000000'"
+        );
+    }
+}
+
+#[cfg(test)]
+mod timeout_tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn execute_timeout(
+        registry: &CommandRegistry,
+        args: &[&str],
+    ) -> (String, Duration, ShellResult<ShellStatus>) {
+        let timeout_cmd = registry.get_builtin("timeout").unwrap();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut output = Vec::new();
+        let start = Instant::now();
+        let result = timeout_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), start.elapsed(), result)
+    }
+
+    #[test]
+    fn test_timeout_kills_slow_command() {
+        let registry = CommandRegistry::default();
+        let (_, elapsed, result) = execute_timeout(&registry, &["0.1", "sleep", "5"]);
+        assert_eq!(result.unwrap(), ShellStatus::Continue);
+        assert_eq!(registry.get_last_status(), 124);
+        assert!(elapsed < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_timeout_kills_whole_group_and_ignored_term() {
+        let registry = CommandRegistry::default();
+        let (output, elapsed, result) = execute_timeout(
+            &registry,
+            &[
+                "50ms",
+                "sh",
+                "-c",
+                "trap '' TERM; echo started; sleep 5; echo late",
+            ],
+        );
+        assert!(result.is_ok());
+        assert_eq!(output, "started\n");
+        assert_eq!(registry.get_last_status(), 124);
+        assert!(elapsed < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_timeout_passes_through_fast_command() {
+        let registry = CommandRegistry::default();
+        let (output, elapsed, _) = execute_timeout(&registry, &["5", "echo", "hi"]);
+        assert_eq!(output, "hi\n");
+        assert_eq!(registry.get_last_status(), 0);
+        assert!(elapsed < Duration::from_secs(1));
+
+        let (_, _, result) = execute_timeout(&registry, &["5", "sh", "-c", "exit 3"]);
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 3);
+    }
+
+    #[test]
+    fn test_timeout_errors() {
+        let registry = CommandRegistry::default();
+        let message = |args: &[&str]| execute_timeout(&registry, args).2.unwrap_err().to_string();
+        assert_eq!(message(&["5"]), "timeout: missing operand");
+        assert_eq!(
+            message(&["soon", "true"]),
+            "timeout: invalid time interval `soon'"
+        );
+        assert_eq!(
+            message(&["10000000000000000000", "true"]),
+            "timeout: invalid time interval `10000000000000000000'"
+        );
+        assert_eq!(
+            message(&["1", "no_such_command_xyz"]),
+            "no_such_command_xyz: command not found"
+        );
+    }
+}
+
+#[cfg(test)]
+mod kill_tests {
+    use super::*;
+    use codecrafters_shell::commands::ShellExecutor;
+    use codecrafters_shell::parser::parse_input;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command as ProcessCommand;
+
+    fn execute_kill(registry: &CommandRegistry, args: &[&str]) -> ShellResult<ShellStatus> {
+        let kill_cmd = registry.get_builtin("kill").unwrap();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        kill_cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut io::sink(), &mut io::sink()),
+        )
+    }
+
+    #[test]
+    fn test_kill_job_spec_terminates_job() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input("sleep 30 &").unwrap()).unwrap();
+
+        assert!(execute_kill(&registry, &["%1"]).is_ok());
+        executor.run(&parse_input("wait %1").unwrap()).unwrap();
+        assert_eq!(registry.get_last_status(), 128 + libc::SIGTERM);
+    }
+
+    #[test]
+    fn test_kill_job_in_own_group_with_named_signal() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_job_control(true);
+        executor
+            .run(&parse_input("sleep 30 | sleep 30 &").unwrap())
+            .unwrap();
+
+        assert!(execute_kill(&registry, &["-KILL", "%1"]).is_ok());
+        executor.run(&parse_input("wait %1").unwrap()).unwrap();
+        assert_eq!(registry.get_last_status(), 128 + libc::SIGKILL);
+    }
+
+    #[test]
+    fn test_kill_pid_with_signal_number() {
+        let registry = CommandRegistry::default();
+        let mut child = ProcessCommand::new("sleep").arg("30").spawn().unwrap();
+
+        assert!(execute_kill(&registry, &["-9", &child.id().to_string()]).is_ok());
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+
+        let mut child = ProcessCommand::new("sleep").arg("30").spawn().unwrap();
+        assert!(execute_kill(&registry, &["-s", "sigint", &child.id().to_string()]).is_ok());
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_kill_errors() {
+        let registry = CommandRegistry::default();
+        let message = |args: &[&str]| execute_kill(&registry, args).unwrap_err().to_string();
+
+        assert_eq!(message(&["%3"]), "kill: %3: no such job");
+        assert_eq!(
+            message(&["-BOGUS", "1"]),
+            "kill: BOGUS: invalid signal specification"
+        );
+        assert_eq!(
+            message(&["-s", "99", "1"]),
+            "kill: 99: invalid signal specification"
+        );
+        assert_eq!(
+            message(&["abc"]),
+            "kill: abc: arguments must be process or job IDs"
+        );
+        assert_eq!(message(&["-9"]), "kill: missing operand");
+    }
+}
+
+#[cfg(test)]
+mod fg_bg_tests {
+    use super::*;
+    use codecrafters_shell::commands::ShellExecutor;
+    use codecrafters_shell::jobs::JobState;
+    use codecrafters_shell::parser::parse_input;
+
+    fn execute(
+        registry: &CommandRegistry,
+        name: &str,
+        args: &[&str],
+    ) -> (String, ShellResult<ShellStatus>) {
+        let cmd = registry.get_builtin(name).unwrap();
+        let mut output = Vec::new();
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let result = cmd.execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        );
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    /// Starts a command that stops itself, leaving it as stopped job 1.
+    fn stopped_job(executor: &ShellExecutor, then: &str) {
+        let line = format!("sh -c 'kill -STOP $$; {}'", then);
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_fg_resumes_stopped_job_until_it_completes() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        stopped_job(&executor, "sleep 0.05; exit 5");
+        assert_eq!(registry.find_job(1).unwrap().state, JobState::Stopped);
+
+        let (output, result) = execute(&registry, "fg", &["%1"]);
+
+        assert!(result.is_ok());
+        assert_eq!(output, "sh -c kill -STOP $$; sleep 0.05; exit 5\n");
+        assert_eq!(registry.get_last_status(), 5);
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_fg_waits_for_background_job() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_job_control(true);
+        executor
+            .run(&parse_input("sh -c 'sleep 0.05; exit 3' &").unwrap())
+            .unwrap();
+
+        let (_, result) = execute(&registry, "fg", &[]);
+
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 3);
+        assert_eq!(registry.get_job_count(), 0);
+    }
+
+    #[test]
+    fn test_fg_reports_job_stopped_again() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        stopped_job(&executor, "kill -STOP $$; exit 2");
+
+        let (output, _) = execute(&registry, "fg", &["%%"]);
+
+        assert!(output.ends_with(
+            "\n[1]+  Stopped                 sh -c kill -STOP $$; kill -STOP $$; exit 2\n"
+        ));
+        assert_eq!(registry.get_last_status(), 128 + libc::SIGSTOP);
+        assert_eq!(registry.find_job(1).unwrap().state, JobState::Stopped);
+
+        execute(&registry, "kill", &["-9", "%1"]).1.unwrap();
+        execute(&registry, "wait", &["%1"]).1.unwrap();
+    }
+
+    #[test]
+    fn test_bg_resumes_stopped_job_in_background() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        stopped_job(&executor, "exit 7");
+
+        let (output, result) = execute(&registry, "bg", &["1"]);
+
+        assert!(result.is_ok());
+        assert_eq!(output, "[1]+ sh -c kill -STOP $$; exit 7 &\n");
+        assert_eq!(registry.find_job(1).unwrap().state, JobState::Running);
+        execute(&registry, "wait", &["%1"]).1.unwrap();
+        assert_eq!(registry.get_last_status(), 7);
+    }
+
+    #[test]
+    fn test_fg_and_bg_without_jobs() {
+        let registry = CommandRegistry::default();
+        assert_eq!(
+            execute(&registry, "fg", &[]).1.unwrap_err().to_string(),
+            "fg: current: no such job"
+        );
+        assert_eq!(
+            execute(&registry, "bg", &["%4"]).1.unwrap_err().to_string(),
+            "bg: %4: no such job"
+        );
+    }
 }
@@ -0,0 +1,70 @@
+// Each test binary that includes this module uses only part of it.
+#![allow(dead_code)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Serializes the tests in a binary that change the environment or the
+/// working directory, both of which are process-wide.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Exclusive use of the process environment and working directory for one
+/// test. Whatever the test changes through it is put back when the guard is
+/// dropped, even if the test panics.
+pub struct EnvGuard {
+    vars: Vec<(String, Option<String>)>,
+    cwd: Option<PathBuf>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    pub fn lock() -> Self {
+        // A test that panicked has already restored what it changed
+        let lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        EnvGuard {
+            vars: Vec::new(),
+            cwd: None,
+            _lock: lock,
+        }
+    }
+
+    /// Sets `name` to `value`, or unsets it for `None`, until the guard is
+    /// dropped.
+    pub fn set_var(&mut self, name: &str, value: Option<&str>) {
+        if !self.vars.iter().any(|(saved, _)| saved == name) {
+            self.vars.push((name.to_string(), env::var(name).ok()));
+        }
+        set_or_remove(name, value);
+    }
+
+    /// Moves into `dir` until the guard is dropped.
+    pub fn set_current_dir(&mut self, dir: &Path) {
+        if self.cwd.is_none() {
+            self.cwd = Some(env::current_dir().unwrap());
+        }
+        env::set_current_dir(dir).unwrap();
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (name, value) in self.vars.drain(..).rev() {
+            set_or_remove(&name, value.as_deref());
+        }
+        if let Some(cwd) = self.cwd.take() {
+            let _ = env::set_current_dir(cwd);
+        }
+    }
+}
+
+fn set_or_remove(name: &str, value: Option<&str>) {
+    // SAFETY: the caller holds ENV_LOCK, and the tests in a binary that uses
+    // it only touch the environment through an EnvGuard.
+    unsafe {
+        match value {
+            Some(value) => env::set_var(name, value),
+            None => env::remove_var(name),
+        }
+    }
+}
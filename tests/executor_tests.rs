@@ -1,4 +1,5 @@
 use codecrafters_shell::commands::{CommandRegistry, ShellExecutor, ShellStatus};
+use codecrafters_shell::error::ShellError;
 use codecrafters_shell::parser::parse_input;
 use std::fs;
 use tempfile::TempDir;
@@ -16,7 +17,7 @@ mod builtin_execution_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("echo hello world");
+        let commands = parse_input("echo hello world").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -28,7 +29,7 @@ mod builtin_execution_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("pwd");
+        let commands = parse_input("pwd").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -39,11 +40,31 @@ mod builtin_execution_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("exit");
+        let commands = parse_input("exit").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
-        matches!(result.unwrap(), ShellStatus::Exit);
+        assert_eq!(result.unwrap(), ShellStatus::Exit(0));
+    }
+
+    #[test]
+    fn test_exit_without_argument_uses_last_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input("false; exit").unwrap());
+
+        assert_eq!(result.unwrap(), ShellStatus::Exit(1));
+    }
+
+    #[test]
+    fn test_exit_with_bad_argument_exits_with_two() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input("exit abc 2>/dev/null").unwrap());
+
+        assert_eq!(result.unwrap(), ShellStatus::Exit(2));
     }
 }
 
@@ -56,7 +77,7 @@ mod external_execution_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("true");
+        let commands = parse_input("true").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -67,13 +88,63 @@ mod external_execution_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("nonexistent_command_xyz");
+        let commands = parse_input("nonexistent_command_xyz").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("command not found"));
     }
+
+    #[test]
+    fn test_execute_non_executable_file() {
+        let temp_dir = setup_test_env();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "#!/bin/sh\ntrue\n").unwrap();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input(script.to_str().unwrap()).unwrap());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("{}: Permission denied", script.display())
+        );
+        assert_eq!(registry.get_last_status(), 126);
+    }
+
+    #[test]
+    fn test_execute_directory() {
+        let temp_dir = setup_test_env();
+        let dir = temp_dir.path().to_str().unwrap();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input(dir).unwrap());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("{}: Is a directory", dir)
+        );
+        assert_eq!(registry.get_last_status(), 126);
+    }
+
+    #[test]
+    fn test_external_receives_help_flags_verbatim() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands =
+            parse_input("sh -c 'printf \"[%s]\" \"$@\"' sh --help --version -h").unwrap();
+        let (_, output) = executor.run_capture(&commands).unwrap();
+        assert_eq!(output, "[--help][--version][-h]");
+
+        let (_, output) = executor
+            .run_capture(&parse_input("ls --help").unwrap())
+            .unwrap();
+        assert!(output.starts_with("Usage: ls"));
+        assert_eq!(registry.get_last_status(), 0);
+    }
 }
 
 #[cfg(test)]
@@ -89,7 +160,7 @@ mod redirect_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input(&format!("echo hello > {}", output_path));
+        let commands = parse_input(&format!("echo hello > {}", output_path)).unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -108,11 +179,11 @@ mod redirect_tests {
         let executor = ShellExecutor::new(&registry);
 
         // First write
-        let commands = parse_input(&format!("echo first >> {}", output_path));
+        let commands = parse_input(&format!("echo first >> {}", output_path)).unwrap();
         executor.run(&commands).unwrap();
 
         // Second write (append)
-        let commands = parse_input(&format!("echo second >> {}", output_path));
+        let commands = parse_input(&format!("echo second >> {}", output_path)).unwrap();
         executor.run(&commands).unwrap();
 
         let content = fs::read_to_string(&output_file).unwrap();
@@ -129,11 +200,11 @@ mod redirect_tests {
         let executor = ShellExecutor::new(&registry);
 
         // First write
-        let commands = parse_input(&format!("echo first > {}", output_path));
+        let commands = parse_input(&format!("echo first > {}", output_path)).unwrap();
         executor.run(&commands).unwrap();
 
         // Second write (overwrite)
-        let commands = parse_input(&format!("echo second > {}", output_path));
+        let commands = parse_input(&format!("echo second > {}", output_path)).unwrap();
         executor.run(&commands).unwrap();
 
         let content = fs::read_to_string(&output_file).unwrap();
@@ -150,7 +221,8 @@ mod redirect_tests {
         let executor = ShellExecutor::new(&registry);
 
         // Use a command that writes to stderr (cat with nonexistent file)
-        let commands = parse_input(&format!("cat /nonexistent_file_xyz 2> {}", error_path));
+        let commands =
+            parse_input(&format!("cat /nonexistent_file_xyz 2> {}", error_path)).unwrap();
         let result = executor.run(&commands);
 
         // Command should execute (even if it fails)
@@ -176,13 +248,268 @@ mod redirect_tests {
         let commands = parse_input(&format!(
             "cat /nonexistent_file_xyz > {} 2> {}",
             output_path, error_path
-        ));
+        ))
+        .unwrap();
         executor.run(&commands).ok();
 
         // Both files should exist
         assert!(output_file.exists());
         assert!(error_file.exists());
     }
+
+    #[test]
+    fn test_stderr_to_stdout_after_file_redirect() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "ls /nonexistent_dir_xyz > {} 2>&1",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert!(content.contains("nonexistent_dir_xyz"));
+    }
+
+    #[test]
+    fn test_stderr_to_stdout_before_file_redirect() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        // stderr is duplicated from the original stdout, so the file stays empty.
+        let commands = parse_input(&format!(
+            "ls /nonexistent_dir_xyz 2>&1 > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert!(content.is_empty());
+    }
+
+    #[test]
+    fn test_stderr_to_stdout_through_pipe() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "ls /nonexistent_dir_xyz 2>&1 | cat > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert!(content.contains("nonexistent_dir_xyz"));
+    }
+
+    #[test]
+    fn test_builtin_error_to_stdout_file() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "type nonexistent_command_xyz > {} 2>&1",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content, "nonexistent_command_xyz: not found\n");
+    }
+
+    #[test]
+    fn test_stdin_redirect_feeds_file() {
+        let temp_dir = setup_test_env();
+        let input_file = temp_dir.path().join("in.txt");
+        let output_file = temp_dir.path().join("out.txt");
+        fs::write(&input_file, "one\ntwo\nthree\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "cat < {} > {}",
+            input_file.to_str().unwrap(),
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content, "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn test_stdin_redirect_missing_file() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input("cat < /nonexistent_file_xyz").unwrap();
+        let result = executor.run(&commands);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_leading_redirects_apply_to_the_command() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("out.txt");
+        let path = output_file.to_str().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!("2>/dev/null >{} echo hi", path)).unwrap();
+        executor.run(&commands).unwrap();
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "hi\n");
+
+        let commands = parse_input(&format!(">{}", path)).unwrap();
+        executor.run(&commands).unwrap();
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "");
+
+        let commands = parse_input("< /nonexistent_file_xyz").unwrap();
+        assert!(executor.run(&commands).is_err());
+        assert_eq!(executor.last_status(), 1);
+    }
+
+    #[test]
+    fn test_combined_redirect_captures_both_streams() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("all.txt");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!(
+            "sh -c 'echo out; echo err >&2; echo again' &> {}",
+            output_file.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output_file).unwrap(),
+            "out\nerr\nagain\n"
+        );
+
+        let line = format!("type nonexistent_command_xyz &>> {}", output_file.display());
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output_file).unwrap(),
+            "out\nerr\nagain\nnonexistent_command_xyz: not found\n"
+        );
+    }
+
+    #[test]
+    fn test_noclobber_refuses_to_overwrite() {
+        let temp_dir = setup_test_env();
+        let existing = temp_dir.path().join("existing");
+        let fresh = temp_dir.path().join("fresh");
+        fs::write(&existing, "keep\n").unwrap();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("set -o noclobber").unwrap())
+            .unwrap();
+        let result =
+            executor.run(&parse_input(&format!("echo x > {}", existing.display())).unwrap());
+        executor
+            .run(&parse_input(&format!("echo new > {}", fresh.display())).unwrap())
+            .unwrap();
+        executor
+            .run(&parse_input(&format!("echo more >> {}", existing.display())).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("{}: cannot overwrite existing file", existing.display())
+        );
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "keep\nmore\n");
+        assert_eq!(fs::read_to_string(&fresh).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn test_force_redirect_overwrites_with_noclobber() {
+        let temp_dir = setup_test_env();
+        let existing = temp_dir.path().join("existing");
+        fs::write(&existing, "old\n").unwrap();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("set -o noclobber").unwrap())
+            .unwrap();
+        executor
+            .run(&parse_input(&format!("echo x >| {}", existing.display())).unwrap())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "x\n");
+
+        executor
+            .run(&parse_input("set +o noclobber").unwrap())
+            .unwrap();
+        executor
+            .run(&parse_input(&format!("echo y > {}", existing.display())).unwrap())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "y\n");
+    }
+
+    #[test]
+    fn test_numeric_fd_redirects_reach_external_commands() {
+        let temp_dir = setup_test_env();
+        let log = temp_dir.path().join("log");
+        let input = temp_dir.path().join("input");
+        let output = temp_dir.path().join("output");
+        fs::write(&input, "from input\n").unwrap();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!(
+            "sh -c 'echo three >&3; cat <&4' 3> {} 4< {} 0< /dev/null > {}",
+            log.display(),
+            input.display(),
+            output.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        assert_eq!(fs::read_to_string(&log).unwrap(), "three\n");
+        assert_eq!(fs::read_to_string(&output).unwrap(), "from input\n");
+    }
+
+    #[test]
+    fn test_numeric_fd_duplicates_and_swaps() {
+        let temp_dir = setup_test_env();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        // fd 3 and 4 end up on each other's files, which needs the sources
+        // moved out of the way before either is replaced
+        let line = format!(
+            "sh -c 'echo to-three >&3; echo to-four >&4' 4> {} 3> {} 5>&3 3>&4 4>&5",
+            first.display(),
+            second.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), "to-three\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "to-four\n");
+    }
 }
 
 #[cfg(test)]
@@ -194,7 +521,7 @@ mod pipeline_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("echo hello | cat");
+        let commands = parse_input("echo hello | cat").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -209,7 +536,8 @@ mod pipeline_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input(&format!("cat {} | wc -l", test_file.to_str().unwrap()));
+        let commands =
+            parse_input(&format!("cat {} | wc -l", test_file.to_str().unwrap())).unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -227,7 +555,8 @@ mod pipeline_tests {
         let commands = parse_input(&format!(
             "cat {} | grep apple | wc -l",
             test_file.to_str().unwrap()
-        ));
+        ))
+        .unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -242,7 +571,7 @@ mod pipeline_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input(&format!("echo hello | cat > {}", output_path));
+        let commands = parse_input(&format!("echo hello | cat > {}", output_path)).unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -256,7 +585,7 @@ mod pipeline_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("echo test | echo hello");
+        let commands = parse_input("echo test | echo hello").unwrap();
         let result = executor.run(&commands);
 
         // This should work (second echo ignores input)
@@ -273,7 +602,7 @@ mod empty_command_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("");
+        let commands = parse_input("").unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
@@ -285,9 +614,1283 @@ mod empty_command_tests {
         let registry = CommandRegistry::default();
         let executor = ShellExecutor::new(&registry);
 
-        let commands = parse_input("   \t  ");
+        let commands = parse_input("   \t  ").unwrap();
+        let result = executor.run(&commands);
+
+        assert!(result.is_ok());
+    }
+}
+
+#[cfg(test)]
+mod exit_status_tests {
+    use super::*;
+    use codecrafters_shell::parser::tokenize_input_with;
+
+    fn expand_last_status(registry: &CommandRegistry) -> Vec<String> {
+        tokenize_input_with("echo $?", registry)
+    }
+
+    #[test]
+    fn test_status_after_false_is_nonzero() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+
+        assert_ne!(registry.get_last_status(), 0);
+        assert_eq!(expand_last_status(&registry), vec!["echo", "1"]);
+    }
+
+    #[test]
+    fn test_status_after_true_is_zero() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+        executor.run(&parse_input("true").unwrap()).unwrap();
+
+        assert_eq!(expand_last_status(&registry), vec!["echo", "0"]);
+    }
+
+    #[test]
+    fn test_status_after_builtin_is_zero() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+        executor.run(&parse_input("pwd").unwrap()).unwrap();
+
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_status_after_command_not_found() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input("nonexistent_command_xyz").unwrap());
+
+        assert!(result.is_err());
+        assert_eq!(registry.get_last_status(), 127);
+    }
+
+    #[test]
+    fn test_status_of_pipeline_is_last_command() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("true | false").unwrap()).unwrap();
+
+        assert_eq!(registry.get_last_status(), 1);
+    }
+
+    #[test]
+    fn test_executor_reports_external_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+        assert_eq!(executor.last_status(), 1);
+
+        executor.run(&parse_input("true").unwrap()).unwrap();
+        assert_eq!(executor.last_status(), 0);
+
+        executor
+            .run(&parse_input("sh -c 'exit 42'").unwrap())
+            .unwrap();
+        assert_eq!(executor.last_status(), 42);
+    }
+
+    #[test]
+    fn test_status_of_signalled_command() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("sh -c 'kill -9 $$'").unwrap())
+            .unwrap();
+
+        assert_eq!(executor.last_status(), 128 + 9);
+    }
+}
+
+#[cfg(test)]
+mod command_list_tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_pipelines_all_run() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+        let output_path = output_file.to_str().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "echo a >> {0}; nonexistent_command_xyz; echo b >> {0}",
+            output_path
+        ))
+        .unwrap();
         let result = executor.run(&commands);
 
         assert!(result.is_ok());
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content, "a\nb\n");
+    }
+
+    #[test]
+    fn test_exit_stops_remaining_pipelines() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "exit; echo late > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        let result = executor.run(&commands).unwrap();
+
+        assert_eq!(result, ShellStatus::Exit(0));
+        assert!(!output_file.exists());
+    }
+
+    fn run_to_file(script: &str) -> Option<String> {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&script.replace("OUT", output_file.to_str().unwrap())).unwrap();
+        executor.run(&commands).ok();
+
+        fs::read_to_string(&output_file).ok()
+    }
+
+    #[test]
+    fn test_sequential_pipeline_sees_earlier_effects() {
+        assert_eq!(run_to_file("false; echo $? > OUT"), Some("1\n".to_string()));
+        assert_eq!(
+            run_to_file("export SEQ_EXPORT_TEST_VAR=1; echo \"[$SEQ_EXPORT_TEST_VAR]\" > OUT"),
+            Some("[1]\n".to_string())
+        );
+        assert_eq!(
+            run_to_file("read SEQ_READ_TEST_VAR <<< hello; echo $SEQ_READ_TEST_VAR > OUT"),
+            Some("hello\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_and_skips_right_side_after_failure() {
+        assert_eq!(run_to_file("false && echo no > OUT"), None);
+    }
+
+    #[test]
+    fn test_and_runs_right_side_after_success() {
+        assert_eq!(
+            run_to_file("true && echo yes > OUT"),
+            Some("yes\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_or_skips_right_side_after_success() {
+        assert_eq!(run_to_file("true || echo no > OUT"), None);
+    }
+
+    #[test]
+    fn test_or_runs_right_side_after_failure() {
+        assert_eq!(
+            run_to_file("nonexistent_command_xyz || echo failed > OUT"),
+            Some("failed\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_skipped_right_side_is_not_expanded() {
+        assert_eq!(run_to_file("true || echo $(echo no > OUT)"), None);
+        assert_eq!(run_to_file("false && echo $(echo no > OUT)"), None);
+    }
+
+    #[test]
+    fn test_chained_connectors_use_last_status() {
+        assert_eq!(
+            run_to_file("false && echo a > OUT || echo b > OUT"),
+            Some("b\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bracket_builtin_drives_connectors() {
+        assert_eq!(
+            run_to_file("[ 2 -gt 1 ] && echo more > OUT || echo less > OUT"),
+            Some("more\n".to_string())
+        );
+        assert_eq!(
+            run_to_file("test x = y && echo same > OUT || echo differ > OUT"),
+            Some("differ\n".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod background_tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_background_command_returns_immediately() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let start = Instant::now();
+        let result = executor.run(&parse_input("sleep 1 &").unwrap());
+
+        assert!(result.is_ok());
+        assert!(start.elapsed() < Duration::from_millis(900));
+        assert_eq!(registry.get_job_count(), 1);
+        assert!(registry.reap_jobs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_background_then_foreground_command() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "sleep 1 & echo done > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "done\n");
+        assert_eq!(registry.get_job_count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use super::*;
+    use codecrafters_shell::audit::AuditLog;
+
+    #[test]
+    fn test_audit_log_records_expanded_argv() {
+        let temp_dir = setup_test_env();
+        let log_file = temp_dir.path().join("audit.log");
+
+        let registry = CommandRegistry::default();
+        registry.set_positional_args(vec!["first arg".to_string()]);
+        let executor = ShellExecutor::new(&registry).with_audit_log(AuditLog::new(&log_file));
+
+        let commands = parse_input("echo \"$1\" '$1' | cat").unwrap();
+        executor.run(&commands).unwrap();
+
+        let log = fs::read_to_string(&log_file).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"timestamp\":"));
+        assert!(lines[0].ends_with(",\"argv\":[\"echo\",\"first arg\",\"$1\"]}"));
+        assert!(lines[1].ends_with(",\"argv\":[\"cat\"]}"));
+    }
+
+    #[test]
+    fn test_audit_log_skips_commands_that_do_not_run() {
+        let temp_dir = setup_test_env();
+        let log_file = temp_dir.path().join("audit.log");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_audit_log(AuditLog::new(&log_file));
+
+        executor
+            .run(&parse_input("true || echo skipped").unwrap())
+            .unwrap();
+
+        let log = fs::read_to_string(&log_file).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("\"argv\":[\"true\"]"));
+    }
+}
+
+#[cfg(test)]
+mod memory_redirect_tests {
+    use super::*;
+    use codecrafters_shell::files::MemoryFileSystem;
+    use std::path::Path;
+
+    fn run_in_memory(script: &str) -> MemoryFileSystem {
+        let files = MemoryFileSystem::new();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_file_system(files.clone());
+        executor.run(&parse_input(script).unwrap()).unwrap();
+        files
+    }
+
+    #[test]
+    fn test_builtin_redirect_writes_to_memory() {
+        let files = run_in_memory("echo hello > /memory/out.txt");
+
+        assert_eq!(files.read("/memory/out.txt").as_deref(), Some("hello\n"));
+        assert!(!Path::new("/memory/out.txt").exists());
+    }
+
+    #[test]
+    fn test_redirect_truncates_and_appends_in_memory() {
+        let files = run_in_memory("echo old > out; echo first > out; echo second >> out");
+
+        assert_eq!(files.read("out").as_deref(), Some("first\nsecond\n"));
+    }
+
+    #[test]
+    fn test_builtin_error_redirect_writes_to_memory() {
+        let files = run_in_memory("cd /nonexistent/directory 2> err");
+
+        assert!(
+            files
+                .read("err")
+                .unwrap()
+                .contains("/nonexistent/directory")
+        );
+    }
+
+    #[test]
+    fn test_external_redirect_writes_to_memory() {
+        let files = run_in_memory("echo abc | tr a-z A-Z > out");
+
+        assert_eq!(files.read("out").as_deref(), Some("ABC\n"));
+    }
+
+    #[test]
+    fn test_external_stderr_redirect_writes_to_memory() {
+        let files = run_in_memory("ls /nonexistent/directory 2> err");
+
+        assert!(
+            files
+                .read("err")
+                .unwrap()
+                .contains("/nonexistent/directory")
+        );
+    }
+
+    #[test]
+    fn test_unopened_file_reads_as_none() {
+        let files = run_in_memory("true");
+
+        assert_eq!(files.read("out"), None);
+    }
+}
+
+#[cfg(test)]
+mod cd_environment_tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_child_sees_pwd_and_oldpwd_after_cd() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("env.txt");
+        let original = env::current_dir().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "cd / && cd /tmp && printenv PWD OLDPWD > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        let result = executor.run(&commands);
+        env::set_current_dir(original).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "/tmp\n/\n");
+    }
+
+    #[test]
+    fn test_glob_after_and_matches_new_directory() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("glob.txt");
+        let original = env::current_dir().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "cd {} && mkdir -p sub && cd sub && touch inner && echo * > {}",
+            temp_dir.path().to_str().unwrap(),
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        let result = executor.run(&commands);
+        env::set_current_dir(original).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "inner\n");
+    }
+
+    #[test]
+    fn test_pwd_is_expanded_after_earlier_cd() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("pwd.txt");
+        let original = env::current_dir().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "cd /tmp; echo $PWD > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        let result = executor.run(&commands);
+        env::set_current_dir(original).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "/tmp\n");
+    }
+}
+
+#[cfg(test)]
+mod env_assignment_tests {
+    use super::*;
+    use std::env;
+
+    fn capture(line: &str) -> (String, i32) {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        let (_, output) = executor.run_capture(&parse_input(line).unwrap()).unwrap();
+        (output, registry.get_last_status())
+    }
+
+    #[test]
+    fn test_env_sets_status_of_its_command() {
+        assert_eq!(capture("env false"), (String::new(), 1));
+        assert_eq!(capture("env false || echo failed").0, "failed\n");
+        assert_eq!(capture("env true && echo passed").0, "passed\n");
+    }
+
+    #[test]
+    fn test_env_command_uses_redirected_streams() {
+        let temp_dir = setup_test_env();
+        let input_file = temp_dir.path().join("in.txt");
+        let error_file = temp_dir.path().join("err.txt");
+        fs::write(&input_file, "from file\n").unwrap();
+
+        assert_eq!(capture("echo piped | env cat").0, "piped\n");
+        assert_eq!(
+            capture(&format!("env cat < {}", input_file.display())).0,
+            "from file\n"
+        );
+
+        capture(&format!(
+            "env sh -c 'echo oops >&2' 2> {}",
+            error_file.display()
+        ));
+        assert_eq!(fs::read_to_string(&error_file).unwrap(), "oops\n");
+    }
+
+    #[test]
+    fn test_inline_assignment_is_visible_to_child_only() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("env.txt");
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let commands = parse_input(&format!(
+            "INLINE_TEST_VAR=child printenv INLINE_TEST_VAR > {}",
+            output_file.to_str().unwrap()
+        ))
+        .unwrap();
+        executor.run(&commands).unwrap();
+
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "child\n");
+        assert!(env::var("INLINE_TEST_VAR").is_err());
+    }
+
+    #[test]
+    fn test_bare_assignment_sets_shell_variable() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("BARE_TEST_VAR=shell").unwrap())
+            .unwrap();
+
+        assert_eq!(env::var("BARE_TEST_VAR").unwrap(), "shell");
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_bare_assignment_resets_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+        executor
+            .run(&parse_input("STATUS_ZERO_TEST_VAR=5").unwrap())
+            .unwrap();
+
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_bare_assignment_takes_substitution_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = "SUBST_STATUS_TEST_VAR=$(false)";
+        executor.run(&parse_input(line).unwrap()).unwrap();
+
+        assert_eq!(env::var("SUBST_STATUS_TEST_VAR").unwrap(), "");
+        assert_eq!(registry.get_last_status(), 1);
+    }
+}
+
+#[cfg(test)]
+mod command_substitution_tests {
+    use super::*;
+
+    fn capture(line: &str) -> (String, i32) {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        let pipelines = parse_input(line).unwrap();
+        let (_, output) = executor.run_capture(&pipelines).unwrap();
+        (output, registry.get_last_status())
+    }
+
+    #[test]
+    fn test_substitution_strips_trailing_newlines() {
+        assert_eq!(capture("echo [$(echo hi)]").0, "[hi]\n");
+    }
+
+    #[test]
+    fn test_substitution_runs_pipelines_and_nests() {
+        assert_eq!(
+            capture("echo $(echo $(echo inner) | tr a-z A-Z)").0,
+            "INNER\n"
+        );
+    }
+
+    #[test]
+    fn test_substitution_in_double_quotes_keeps_parentheses_in_quotes() {
+        assert_eq!(capture("echo \"$(echo ')')\"").0, ")\n");
+    }
+
+    #[test]
+    fn test_command_word_status_wins_over_substitution() {
+        assert_eq!(capture("echo $(false)"), ("\n".to_string(), 0));
+    }
+
+    #[test]
+    fn test_substitution_sees_earlier_pipelines_on_the_line() {
+        let line = "LAZY_SUBST_TEST_VAR=$(echo hi); echo [$LAZY_SUBST_TEST_VAR]";
+        assert_eq!(capture(line).0, "[hi]\n");
+    }
+
+    #[test]
+    fn test_skipped_pipeline_runs_no_substitution() {
+        let temp_dir = setup_test_env();
+        let side = temp_dir.path().join("side");
+
+        capture(&format!("false && echo $(touch {})", side.display()));
+
+        assert!(!side.exists());
+    }
+
+    #[test]
+    fn test_unclosed_substitution_is_literal() {
+        assert_eq!(capture("echo '$(' x").0, "$( x\n");
+        let registry = CommandRegistry::default();
+        let pipelines = parse_input("echo $(echo").unwrap();
+        let commands = pipelines[0].expand(&ShellExecutor::new(&registry)).unwrap();
+        assert_eq!(commands[0].args, ["$(echo"]);
+    }
+}
+
+#[cfg(test)]
+mod alias_tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_expands_in_command_position() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("alias.txt");
+        let output = output_file.to_str().unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias greet='echo hello'").unwrap())
+            .unwrap();
+        executor
+            .run(&parse_input(&format!("greet world > {}", output)).unwrap())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "hello world\n");
+
+        executor
+            .run(&parse_input(&format!("echo greet > {}", output)).unwrap())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "greet\n");
+    }
+
+    #[test]
+    fn test_alias_is_not_recursive() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias ls='ls -d'").unwrap())
+            .unwrap();
+        let result = executor.run(&parse_input("ls / > /dev/null").unwrap());
+
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_unalias_stops_expansion() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias greet='echo hello'").unwrap())
+            .unwrap();
+        executor
+            .run(&parse_input("unalias greet").unwrap())
+            .unwrap();
+        let result = executor.run(&parse_input("greet").unwrap());
+
+        assert!(result.is_err());
+        assert_eq!(registry.get_last_status(), 127);
+    }
+}
+
+#[cfg(test)]
+mod capture_tests {
+    use super::*;
+
+    fn capture(input: &str) -> (String, i32) {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        let (_, output) = executor.run_capture(&parse_input(input).unwrap()).unwrap();
+        (output, registry.get_last_status())
+    }
+
+    #[test]
+    fn test_capture_builtin_output() {
+        assert_eq!(capture("echo hi"), ("hi\n".to_string(), 0));
+    }
+
+    #[test]
+    fn test_capture_builtin_at_end_of_pipeline() {
+        assert_eq!(capture("true | echo piped").0, "piped\n");
+    }
+
+    #[test]
+    fn test_capture_external_output() {
+        assert_eq!(capture("echo hi | tr a-z A-Z").0, "HI\n");
+    }
+
+    #[test]
+    fn test_capture_and_pipe_reach_commands_run_by_source() {
+        let temp_dir = setup_test_env();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "echo hi\nsh -c 'echo from child'\n").unwrap();
+        let path = script.display();
+
+        assert_eq!(capture(&format!("source {}", path)).0, "hi\nfrom child\n");
+        assert_eq!(
+            capture(&format!("source {} | tr a-z A-Z", path)).0,
+            "HI\nFROM CHILD\n"
+        );
+    }
+
+    #[test]
+    fn test_redirect_reaches_commands_run_by_source_and_fc() {
+        let temp_dir = setup_test_env();
+        let script = temp_dir.path().join("script.sh");
+        let output_file = temp_dir.path().join("out.txt");
+        let error_file = temp_dir.path().join("err.txt");
+        fs::write(&script, "echo hi\nsh -c 'echo oops >&2'\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        let line = format!(
+            "source {} > {} 2> {}",
+            script.display(),
+            output_file.display(),
+            error_file.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "hi\n");
+        assert_eq!(fs::read_to_string(&error_file).unwrap(), "oops\n");
+
+        registry.add_history_entry("sh -c 'echo again'");
+        let line = format!("fc -s > {}", output_file.display());
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output_file).unwrap(),
+            "sh -c 'echo again'\nagain\n"
+        );
+    }
+
+    #[test]
+    fn test_capture_collects_every_pipeline() {
+        assert_eq!(capture("echo a; printf b").0, "a\nb");
+    }
+
+    #[test]
+    fn test_capture_keeps_exit_status() {
+        assert_eq!(
+            capture("sh -c 'echo out; exit 3'"),
+            ("out\n".to_string(), 3)
+        );
+    }
+
+    #[test]
+    fn test_capture_respects_file_redirect() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("out.txt");
+
+        let (output, _) = capture(&format!(
+            "echo to-file > {}; echo captured",
+            output_file.to_str().unwrap()
+        ));
+
+        assert_eq!(output, "captured\n");
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "to-file\n");
+    }
+
+    #[test]
+    fn test_capture_stderr_duplicated_to_stdout() {
+        assert_eq!(
+            capture("cat /nonexistent_file_xyz 2>&1").0,
+            "cat: /nonexistent_file_xyz: No such file or directory\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod command_not_found_tests {
+    use super::*;
+
+    #[test]
+    fn test_handler_runs_with_original_argv() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias command_not_found_handle='echo missing:'").unwrap())
+            .unwrap();
+        let (_, output) = executor
+            .run_capture(&parse_input("no_such_command_xyz arg1 arg2").unwrap())
+            .unwrap();
+
+        assert_eq!(output, "missing: no_such_command_xyz arg1 arg2\n");
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_handler_status_becomes_exit_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias command_not_found_handle=false").unwrap())
+            .unwrap();
+        let result = executor.run(&parse_input("no_such_command_xyz").unwrap());
+
+        assert!(result.is_ok());
+        assert_eq!(registry.get_last_status(), 1);
+    }
+
+    #[test]
+    fn test_handler_not_used_for_existing_commands() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(&parse_input("alias command_not_found_handle='echo missing:'").unwrap())
+            .unwrap();
+        let (_, output) = executor
+            .run_capture(&parse_input("echo found").unwrap())
+            .unwrap();
+
+        assert_eq!(output, "found\n");
+    }
+
+    #[test]
+    fn test_missing_command_without_handler_errors() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let result = executor.run(&parse_input("no_such_command_xyz").unwrap());
+
+        assert!(matches!(result, Err(ShellError::CommandNotFound(_))));
+        assert_eq!(registry.get_last_status(), 127);
+    }
+}
+
+#[cfg(test)]
+mod builtin_stream_tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_builtin_reads_input_redirect() {
+        let temp_dir = setup_test_env();
+        let input_file = temp_dir.path().join("input.txt");
+        fs::write(&input_file, "redirected line\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(
+                &parse_input(&format!(
+                    "read BUILTIN_STREAM_READ < {}",
+                    input_file.to_str().unwrap()
+                ))
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(env::var("BUILTIN_STREAM_READ").unwrap(), "redirected line");
+    }
+
+    #[test]
+    fn test_builtin_error_stream_follows_redirect() {
+        let temp_dir = setup_test_env();
+        let script = temp_dir.path().join("script.sh");
+        let errors = temp_dir.path().join("errors.txt");
+        fs::write(&script, "no_such_command_xyz\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor
+            .run(
+                &parse_input(&format!(
+                    "source {} 2> {}",
+                    script.to_str().unwrap(),
+                    errors.to_str().unwrap()
+                ))
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&errors).unwrap(),
+            "no_such_command_xyz: command not found\n"
+        );
+    }
+
+    #[test]
+    fn test_builtin_error_stream_joins_pipe() {
+        let temp_dir = setup_test_env();
+        let script = temp_dir.path().join("script.sh");
+        fs::write(&script, "no_such_command_xyz\n").unwrap();
+
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        let (_, output) = executor
+            .run_capture(
+                &parse_input(&format!("source {} 2>&1 | cat", script.to_str().unwrap())).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(output, "no_such_command_xyz: command not found\n");
+    }
+
+    #[test]
+    fn test_pipe_both_sends_stderr_downstream() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = "sh -c 'echo out; echo err >&2' |& sort";
+        let (_, output) = executor.run_capture(&parse_input(line).unwrap()).unwrap();
+        assert_eq!(output, "err\nout\n");
+
+        // The command's own stderr redirect is overridden, as in bash
+        let line = "sh -c 'echo err >&2' 2> /dev/null |& tr a-z A-Z";
+        let (_, output) = executor.run_capture(&parse_input(line).unwrap()).unwrap();
+        assert_eq!(output, "ERR\n");
+    }
+
+    fn run_line(line: &str) {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        executor.run(&parse_input(line).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_builtin_reads_buffered_builtin_output() {
+        run_line("echo piped words | read PIPE_READ_FIRST PIPE_READ_REST");
+
+        assert_eq!(env::var("PIPE_READ_FIRST").unwrap(), "piped");
+        assert_eq!(env::var("PIPE_READ_REST").unwrap(), "words");
+    }
+
+    #[test]
+    fn test_builtin_reads_process_output() {
+        run_line("echo from process | tr a-z A-Z | read PIPE_READ_PROCESS");
+
+        assert_eq!(env::var("PIPE_READ_PROCESS").unwrap(), "FROM PROCESS");
+    }
+
+    #[test]
+    fn test_builtin_leaving_input_unread_does_not_hang() {
+        run_line("yes | read PIPE_READ_YES");
+
+        assert_eq!(env::var("PIPE_READ_YES").unwrap(), "y");
+    }
+
+    #[test]
+    fn test_input_redirect_wins_over_pipe() {
+        let temp_dir = setup_test_env();
+        let input_file = temp_dir.path().join("input.txt");
+        fs::write(&input_file, "from file\n").unwrap();
+
+        run_line(&format!(
+            "echo from pipe | read PIPE_READ_REDIRECT < {}",
+            input_file.to_str().unwrap()
+        ));
+
+        assert_eq!(env::var("PIPE_READ_REDIRECT").unwrap(), "from file");
+    }
+
+    /// A line for a builtin writing `lines` lines of 65,000 digits each,
+    /// several times what an OS pipe holds.
+    fn large_printf(lines: usize) -> String {
+        let args: Vec<String> = (1..=lines).map(|n| n.to_string()).collect();
+        format!("printf '%065000d\\n' {}", args.join(" "))
+    }
+
+    #[test]
+    fn test_builtin_streams_large_output_into_external() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!("{} | wc -c", large_printf(8));
+        let (_, output) = executor.run_capture(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(output.trim(), "520008");
+
+        // Buffering all of it before starting `cat` would leave `cat`
+        // blocked on a full pipe to `wc`, and the shell blocked on `cat`
+        let line = format!("{} | cat | wc -c", large_printf(8));
+        let (_, output) = executor.run_capture(&parse_input(&line).unwrap()).unwrap();
+        assert_eq!(output.trim(), "520008");
+    }
+
+    #[test]
+    fn test_streaming_builtin_stops_when_reader_exits() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!("{} | head -c 5", large_printf(8));
+        let (_, output) = executor.run_capture(&parse_input(&line).unwrap()).unwrap();
+
+        assert_eq!(output, "00000");
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_streaming_builtin_reports_its_own_errors() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("out.txt");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!(
+            "cd /no/such/dir 2>&1 | tr a-z A-Z > {}",
+            output_file.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&output_file).unwrap(),
+            "CD: /NO/SUCH/DIR: NO SUCH FILE OR DIRECTORY\n"
+        );
+        assert_eq!(registry.get_last_status(), 0);
+    }
+}
+
+#[cfg(test)]
+mod stopped_job_tests {
+    use super::*;
+    use codecrafters_shell::jobs::JobState;
+
+    #[test]
+    fn test_sigtstp_records_foreground_child_as_stopped_job() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        // The child suspends itself, as if Ctrl-Z had been pressed
+        let commands = parse_input("sh -c 'kill -TSTP $$'").unwrap();
+        executor.run(&commands).unwrap();
+
+        assert_eq!(registry.get_last_status(), 128 + libc::SIGTSTP);
+        let jobs = registry.list_jobs().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].state, JobState::Stopped);
+        assert_eq!(jobs[0].command, "sh -c kill -TSTP $$");
+        assert_eq!(
+            jobs[0].to_string(),
+            format!("[1]+  {:<24}{}", "Stopped", jobs[0].command)
+        );
+
+        // Stopped jobs are not reported as finished between prompts
+        assert!(registry.reap_jobs().unwrap().is_empty());
+
+        // SAFETY: the pid belongs to our own stopped child.
+        unsafe { libc::kill(jobs[0].pid as libc::pid_t, libc::SIGKILL) };
+        registry.wait_for_all_jobs().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod set_option_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    /// A trace sink the test can read back after the executor writes to it.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_xtrace_prints_each_command() {
+        let trace = SharedBuffer::default();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_trace_output(trace.clone());
+
+        executor
+            .run(&parse_input("set -x; true hi; true 'a b' | true; set +x; true").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(trace.0.borrow().clone()).unwrap(),
+            "+ true hi\n+ true 'a b'\n+ true\n+ set +x\n"
+        );
+    }
+
+    #[test]
+    fn test_errexit_stops_after_failure() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!("set -e; false; echo late > {}", output_file.display());
+        let result = executor.run(&parse_input(&line).unwrap());
+
+        assert_eq!(result.unwrap(), ShellStatus::Exit(1));
+        assert!(!output_file.exists());
+    }
+
+    #[test]
+    fn test_errexit_ignores_tested_failures() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!(
+            "set -e; false || true; false && true; echo reached > {}",
+            output_file.display()
+        );
+        let result = executor.run(&parse_input(&line).unwrap());
+
+        assert_eq!(result.unwrap(), ShellStatus::Continue);
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "reached\n");
+    }
+}
+
+#[cfg(test)]
+mod prompt_command_tests {
+    use super::*;
+    use std::env;
+
+    // The only test that sets PROMPT_COMMAND, so nothing races it
+    #[test]
+    fn test_prompt_command_runs_before_next_command() {
+        let temp_dir = setup_test_env();
+        let output_file = temp_dir.path().join("output.txt");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+        // SAFETY: no other test reads these variables.
+        unsafe {
+            env::set_var("PROMPT_COMMAND", "PROMPT_TEST_COUNT=seen; true");
+            env::remove_var("PROMPT_TEST_COUNT");
+        }
+
+        executor.run(&parse_input("false").unwrap()).unwrap();
+        executor.run_prompt_command();
+        let status = registry.get_last_status();
+        let line = format!("echo $PROMPT_TEST_COUNT > {}", output_file.display());
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        unsafe { env::set_var("PROMPT_COMMAND", "exit 3; nonexistent_command_xyz") };
+        executor.run_prompt_command();
+        let status_after_exit = registry.get_last_status();
+        unsafe { env::remove_var("PROMPT_COMMAND") };
+
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "seen\n");
+        assert_eq!(status, 1);
+        assert_eq!(status_after_exit, 0);
+    }
+}
+
+#[cfg(test)]
+mod exit_trap_tests {
+    use super::*;
+
+    fn run_line(executor: &ShellExecutor, line: &str) -> ShellStatus {
+        executor.run(&parse_input(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_exit_trap_preserves_pending_status() {
+        let temp_dir = setup_test_env();
+        let marker = temp_dir.path().join("trap_ran");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!("trap 'echo bye > {}' EXIT; exit 3", marker.display());
+        assert_eq!(run_line(&executor, &line), ShellStatus::Exit(3));
+
+        assert_eq!(executor.shutdown(3), 3);
+        assert_eq!(fs::read_to_string(&marker).unwrap(), "bye\n");
+    }
+
+    #[test]
+    fn test_exit_inside_trap_sets_new_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        run_line(&executor, "trap 'exit 5' EXIT; exit 3");
+        assert_eq!(executor.shutdown(3), 5);
+    }
+
+    #[test]
+    fn test_shutdown_without_trap_keeps_last_status() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        run_line(&executor, "false");
+        assert_eq!(executor.shutdown(registry.get_last_status()), 1);
+    }
+
+    #[test]
+    fn test_exit_trap_runs_once() {
+        let temp_dir = setup_test_env();
+        let marker = temp_dir.path().join("trap_count");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        run_line(
+            &executor,
+            &format!("trap 'echo ran >> {}' EXIT", marker.display()),
+        );
+        executor.shutdown(0);
+        executor.shutdown(0);
+
+        assert_eq!(fs::read_to_string(&marker).unwrap(), "ran\n");
+    }
+}
+
+#[cfg(test)]
+mod heredoc_tests {
+    use super::*;
+    use codecrafters_shell::files::MemoryFileSystem;
+    use std::env;
+
+    fn run_to_memory(script: &str) -> MemoryFileSystem {
+        let files = MemoryFileSystem::new();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_file_system(files.clone());
+        executor.run(&parse_input(script).unwrap()).unwrap();
+        files
+    }
+
+    #[test]
+    fn test_heredoc_feeds_external_command() {
+        let files = run_to_memory("cat <<EOF > out\nfirst\n  second\nEOF");
+        assert_eq!(files.read("out").as_deref(), Some("first\n  second\n"));
+    }
+
+    #[test]
+    fn test_heredoc_feeds_builtin() {
+        run_to_memory("read HEREDOC_READ_TEST <<-END\n\tfrom heredoc\n\tEND");
+        assert_eq!(env::var("HEREDOC_READ_TEST").unwrap(), "from heredoc");
+    }
+
+    #[test]
+    fn test_heredoc_replaces_pipe_input() {
+        let files = run_to_memory("yes | cat <<EOF | tr a-z A-Z > out\nbody\nEOF");
+        assert_eq!(files.read("out").as_deref(), Some("BODY\n"));
+    }
+
+    #[test]
+    fn test_commands_after_heredoc_still_run() {
+        let files = run_to_memory("cat <<EOF > out\nbody\nEOF\n");
+        assert_eq!(files.read("out").as_deref(), Some("body\n"));
+
+        let files = run_to_memory("true <<EOF && echo ran > out\nignored\nEOF");
+        assert_eq!(files.read("out").as_deref(), Some("ran\n"));
+    }
+}
+
+#[cfg(test)]
+mod here_string_tests {
+    use super::*;
+    use codecrafters_shell::files::MemoryFileSystem;
+    use std::env;
+
+    #[test]
+    fn test_here_string_feeds_cat() {
+        let files = MemoryFileSystem::new();
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_file_system(files.clone());
+
+        executor
+            .run(&parse_input("cat <<< 'some string' > out").unwrap())
+            .unwrap();
+
+        assert_eq!(files.read("out").as_deref(), Some("some string\n"));
+    }
+
+    #[test]
+    fn test_here_string_feeds_read() {
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        executor
+            .run(
+                &parse_input("read HERE_STRING_FIRST HERE_STRING_REST <<< \"one two three\"")
+                    .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(env::var("HERE_STRING_FIRST").unwrap(), "one");
+        assert_eq!(env::var("HERE_STRING_REST").unwrap(), "two three");
     }
 }
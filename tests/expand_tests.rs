@@ -0,0 +1,238 @@
+use codecrafters_shell::expand::{ExpandContext, expand_tilde, expand_tokens};
+use codecrafters_shell::parser::VariableLookup;
+use std::env;
+
+#[cfg(test)]
+mod tilde_tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_bare_tilde() {
+        let home = env::var("HOME").unwrap();
+        assert_eq!(expand_tilde("~"), home);
+    }
+
+    #[test]
+    fn test_expand_tilde_with_subdir() {
+        let home = env::var("HOME").unwrap();
+        assert_eq!(expand_tilde("~/sub"), format!("{}/sub", home));
+    }
+
+    #[test]
+    fn test_expand_known_user() {
+        let result = expand_tilde("~root/file");
+        assert!(!result.starts_with('~'));
+        assert!(result.ends_with("/file"));
+    }
+
+    #[test]
+    fn test_expand_unknown_user_stays_literal() {
+        assert_eq!(expand_tilde("~baduser_xyz_12345"), "~baduser_xyz_12345");
+        assert_eq!(
+            expand_tilde("~baduser_xyz_12345/sub"),
+            "~baduser_xyz_12345/sub"
+        );
+    }
+
+    #[test]
+    fn test_embedded_tilde_untouched() {
+        assert_eq!(expand_tilde("foo~bar"), "foo~bar");
+        assert_eq!(expand_tilde("a/~/b"), "a/~/b");
+    }
+}
+
+#[cfg(test)]
+mod expand_tokens_tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Variables for the test, plus `$(...)` that echoes its command and
+    /// fails when the command is `false`.
+    struct Lookup(Vec<(&'static str, String)>);
+
+    impl VariableLookup for Lookup {
+        fn get_variable(&self, name: &str) -> Option<String> {
+            self.0
+                .iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.clone())
+        }
+
+        fn substitute_command(&self, command: &str) -> Option<(String, i32)> {
+            let status = if command == "false" { 1 } else { 0 };
+            Some((format!("{}\n", command), status))
+        }
+    }
+
+    fn expand(line: &str, vars: Vec<(&'static str, String)>) -> Vec<String> {
+        let lookup = Lookup(vars);
+        let words: Vec<String> = line.split(' ').map(String::from).collect();
+        expand_tokens(&words, &mut ExpandContext::new(&lookup)).unwrap()
+    }
+
+    fn text_files() -> (TempDir, String) {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+        fs::write(dir.path().join("b.txt"), "").unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_brace_variable_and_glob_combined() {
+        let vars = vec![("HOME", "/no/such/home".to_string())];
+        assert_eq!(
+            expand("echo {a,b}$HOME/*.txt", vars),
+            ["echo", "a/no/such/home/*.txt", "b/no/such/home/*.txt"]
+        );
+    }
+
+    #[test]
+    fn test_braces_expand_before_variables_and_globs() {
+        let (_dir, root) = text_files();
+        let words = expand("{,x}$DIR/*.txt", vec![("DIR", root.clone())]);
+        assert_eq!(
+            words,
+            [
+                format!("{}/a.txt", root),
+                format!("{}/b.txt", root),
+                format!("x{}/*.txt", root)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variable_values_are_not_brace_expanded() {
+        assert_eq!(expand("$X", vec![("X", "{a,b}".to_string())]), ["{a,b}"]);
+    }
+
+    #[test]
+    fn test_nested_and_literal_braces() {
+        assert_eq!(expand("{a,{b,c}}d", vec![]), ["ad", "bd", "cd"]);
+        assert_eq!(
+            expand("{a} {a,b '{a,b}' \"{a,b}\"", vec![]),
+            ["{a}", "{a,b", "{a,b}", "{a,b}"]
+        );
+    }
+
+    #[test]
+    fn test_comma_list_with_prefix_and_suffix() {
+        assert_eq!(
+            expand("echo file{1,2,3}.txt", vec![]),
+            ["echo", "file1.txt", "file2.txt", "file3.txt"]
+        );
+    }
+
+    #[test]
+    fn test_numeric_ranges() {
+        assert_eq!(expand("{1..5}", vec![]), ["1", "2", "3", "4", "5"]);
+        assert_eq!(expand("{3..-1}", vec![]), ["3", "2", "1", "0", "-1"]);
+        assert_eq!(expand("{1..10..3}", vec![]), ["1", "4", "7", "10"]);
+        assert_eq!(expand("{9..1..-4}", vec![]), ["9", "5", "1"]);
+        assert_eq!(expand("{08..10}", vec![]), ["08", "09", "10"]);
+    }
+
+    #[test]
+    fn test_ranges_nest_inside_lists() {
+        assert_eq!(
+            expand("v{1..2}.{a,b}", vec![]),
+            ["v1.a", "v1.b", "v2.a", "v2.b"]
+        );
+        assert_eq!(expand("{x,{1..2}}", vec![]), ["x", "1", "2"]);
+    }
+
+    #[test]
+    fn test_malformed_sequences_stay_literal() {
+        assert_eq!(
+            expand("{1..} {a..5} {1..2..x} {1...3} '{1..3}'", vec![]),
+            ["{1..}", "{a..5}", "{1..2..x}", "{1...3}", "{1..3}"]
+        );
+    }
+
+    #[test]
+    fn test_only_unquoted_expansions_are_split() {
+        let vars = vec![("X", "a  b".to_string())];
+        assert_eq!(expand("$X \"$X\" '$X'", vars), ["a", "b", "a  b", "$X"]);
+    }
+
+    #[test]
+    fn test_split_uses_ifs() {
+        let vars = vec![("X", "a:b c".to_string()), ("IFS", ":".to_string())];
+        assert_eq!(expand("$X", vars), ["a", "b c"]);
+    }
+
+    #[test]
+    fn test_expanded_wildcards_glob_unless_quoted() {
+        let (_dir, root) = text_files();
+        let pattern = format!("{}/*.txt", root);
+        let words = expand("$P \"$P\"", vec![("P", pattern.clone())]);
+        assert_eq!(
+            words,
+            [
+                format!("{}/a.txt", root),
+                format!("{}/b.txt", root),
+                pattern
+            ]
+        );
+    }
+
+    // The only test here that touches PWD and OLDPWD, so nothing races it
+    #[test]
+    fn test_expand_working_directory_tildes() {
+        // SAFETY: no other test in this binary reads PWD or OLDPWD.
+        unsafe {
+            env::set_var("PWD", "/current");
+            env::set_var("OLDPWD", "/previous");
+        }
+        assert_eq!(expand_tilde("~+"), "/current");
+        assert_eq!(expand_tilde("~+/sub"), "/current/sub");
+        assert_eq!(expand_tilde("~-"), "/previous");
+        assert_eq!(expand_tilde("~-/sub"), "/previous/sub");
+        assert_eq!(expand_tilde("a~+"), "a~+");
+        assert_eq!(expand_tilde("~+x"), "~+x");
+        assert_eq!(
+            expand("~+/a '~-' ~-", vec![]),
+            ["/current/a", "~-", "/previous"]
+        );
+
+        unsafe { env::remove_var("OLDPWD") };
+        assert_eq!(expand_tilde("~-"), "~-");
+        assert_eq!(expand_tilde("~-/sub"), "~-/sub");
+    }
+
+    #[test]
+    fn test_tilde_expands_only_unquoted_at_word_start() {
+        let home = env::var("HOME").unwrap();
+        assert_eq!(
+            expand("~/x '~' \\~ a~", vec![]),
+            [
+                format!("{}/x", home),
+                "~".to_string(),
+                "~".to_string(),
+                "a~".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assignment_values_are_not_split_or_braced() {
+        let vars = vec![("V", "a b".to_string())];
+        assert_eq!(
+            expand("X=$V Y={a,b} echo $V", vars),
+            ["X=a b", "Y={a,b}", "echo", "a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_substitution_is_split_and_reports_status() {
+        let lookup = Lookup(Vec::new());
+        let mut ctx = ExpandContext::new(&lookup);
+        let words = vec!["$(echo a b)".to_string(), "\"$(false)\"".to_string()];
+
+        let expanded = expand_tokens(&words, &mut ctx).unwrap();
+
+        assert_eq!(expanded, ["echo", "a", "b", "false"]);
+        assert_eq!(ctx.substitution_status(), Some(1));
+    }
+}
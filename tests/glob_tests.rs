@@ -0,0 +1,304 @@
+use std::fs;
+
+use codecrafters_shell::error::ShellResult;
+use codecrafters_shell::glob::expand;
+use codecrafters_shell::parser::{ParsedCommand, VariableLookup, parse_input};
+use tempfile::TempDir;
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Parses `input` and expands its first command through `lookup`.
+fn expand_first(input: &str, lookup: &dyn VariableLookup) -> ShellResult<ParsedCommand> {
+    Ok(parse_input(input)?[0].expand(lookup)?.remove(0))
+}
+
+fn setup() -> (TempDir, String) {
+    let dir = TempDir::new().unwrap();
+    for name in [
+        "zeta.txt",
+        "alpha.txt",
+        "beta.log",
+        ".hidden",
+        ".config.txt",
+    ] {
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("sub/inner.txt"), "").unwrap();
+    let path = dir.path().to_str().unwrap().to_string();
+    (dir, path)
+}
+
+fn names(paths: Vec<String>, root: &str) -> Vec<String> {
+    let prefix = format!("{}/", root);
+    paths
+        .into_iter()
+        .map(|path| path.strip_prefix(&prefix).unwrap().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod glob_expand_tests {
+    use super::*;
+
+    #[test]
+    fn test_star_skips_dotfiles_and_sorts() {
+        let (_dir, root) = setup();
+        let matches = names(expand(&format!("{}/*", root), false), &root);
+        assert_eq!(matches, ["alpha.txt", "beta.log", "sub", "zeta.txt"]);
+    }
+
+    #[test]
+    fn test_leading_dot_matches_dotfiles() {
+        let (_dir, root) = setup();
+        let matches = names(expand(&format!("{}/.*", root), false), &root);
+        assert_eq!(matches, [".config.txt", ".hidden"]);
+    }
+
+    #[test]
+    fn test_suffix_pattern_skips_hidden_match() {
+        let (_dir, root) = setup();
+        let matches = names(expand(&format!("{}/*.txt", root), false), &root);
+        assert_eq!(matches, ["alpha.txt", "zeta.txt"]);
+    }
+
+    #[test]
+    fn test_question_mark_and_bracket_classes() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            names(expand(&format!("{}/[ab]*", root), false), &root),
+            ["alpha.txt", "beta.log"]
+        );
+        assert_eq!(
+            names(expand(&format!("{}/[!ab]*", root), false), &root),
+            ["sub", "zeta.txt"]
+        );
+        assert_eq!(
+            names(expand(&format!("{}/s?b", root), false), &root),
+            ["sub"]
+        );
+    }
+
+    #[test]
+    fn test_wildcard_in_directory_component() {
+        let (_dir, root) = setup();
+        let matches = names(expand(&format!("{}/s*/*.txt", root), false), &root);
+        assert_eq!(matches, ["sub/inner.txt"]);
+    }
+
+    #[test]
+    fn test_escaped_wildcard_matches_literally() {
+        let (_dir, root) = setup();
+        assert!(expand(&format!("{}/\\*", root), false).is_empty());
+    }
+
+    #[test]
+    fn test_no_match_is_empty() {
+        let (_dir, root) = setup();
+        assert!(expand(&format!("{}/*.rs", root), false).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod glob_parse_tests {
+    use super::*;
+
+    fn args(input: &str) -> Vec<String> {
+        expand_first(input, &env_lookup).unwrap().args
+    }
+
+    #[test]
+    fn test_unquoted_glob_expands_to_sorted_paths() {
+        let (_dir, root) = setup();
+        let args = names(args(&format!("echo {}/*.txt", root)), &root);
+        assert_eq!(args, ["alpha.txt", "zeta.txt"]);
+    }
+
+    #[test]
+    fn test_quoted_glob_stays_literal() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            args(&format!("echo '{}/*.txt'", root)),
+            [format!("{}/*.txt", root)]
+        );
+        assert_eq!(
+            args(&format!("echo {}/\\*.txt", root)),
+            [format!("{}/*.txt", root)]
+        );
+    }
+
+    #[test]
+    fn test_question_mark_matches_one_character() {
+        let (dir, root) = setup();
+        for name in ["file1.log", "file2.log", "file10.log"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let args = names(args(&format!("echo {}/file?.log", root)), &root);
+        assert_eq!(args, ["file1.log", "file2.log"]);
+    }
+
+    #[test]
+    fn test_unmatched_glob_is_kept() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            args(&format!("echo {}/*.rs", root)),
+            [format!("{}/*.rs", root)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod globstar_tests {
+    use super::*;
+    use codecrafters_shell::commands::CommandRegistry;
+    use codecrafters_shell::options::ShellOption;
+
+    fn nested() -> (TempDir, String) {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src/cmd/inner")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("build.rs"), "").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        fs::write(dir.path().join("src/cmd/cd.rs"), "").unwrap();
+        fs::write(dir.path().join("src/cmd/inner/deep.rs"), "").unwrap();
+        fs::write(dir.path().join("src/cmd/notes.txt"), "").unwrap();
+        fs::write(dir.path().join(".git/hook.rs"), "").unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_double_star_matches_every_depth_with_globstar() {
+        let (_dir, root) = nested();
+        let matches = names(expand(&format!("{}/**/*.rs", root), true), &root);
+        assert_eq!(
+            matches,
+            [
+                "build.rs",
+                "src/cmd/cd.rs",
+                "src/cmd/inner/deep.rs",
+                "src/lib.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_double_star_is_a_single_level_without_globstar() {
+        let (_dir, root) = nested();
+        let matches = names(expand(&format!("{}/**/*.rs", root), false), &root);
+        assert_eq!(matches, ["src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_trailing_double_star_lists_everything_below() {
+        let (_dir, root) = nested();
+        let matches = names(expand(&format!("{}/src/**", root), true), &root);
+        assert_eq!(
+            matches,
+            [
+                "src/cmd",
+                "src/cmd/cd.rs",
+                "src/cmd/inner",
+                "src/cmd/inner/deep.rs",
+                "src/cmd/notes.txt",
+                "src/lib.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_double_star_does_not_follow_symlink_loops() {
+        let (dir, root) = nested();
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("src/loop")).unwrap();
+
+        let matches = names(expand(&format!("{}/src/**/*.rs", root), true), &root);
+        assert_eq!(
+            matches,
+            ["src/cmd/cd.rs", "src/cmd/inner/deep.rs", "src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_parser_honors_globstar_option() {
+        let (_dir, root) = nested();
+        let registry = CommandRegistry::default();
+        let input = format!("echo {}/**/deep.rs", root);
+        let args = |registry: &CommandRegistry| expand_first(&input, registry).unwrap().args;
+
+        assert_eq!(args(&registry), [format!("{}/**/deep.rs", root)]);
+
+        registry.set_option(ShellOption::Globstar, true);
+        assert_eq!(args(&registry), [format!("{}/src/cmd/inner/deep.rs", root)]);
+    }
+
+    #[test]
+    fn test_noglob_leaves_patterns_literal() {
+        let (_dir, root) = setup();
+        let registry = CommandRegistry::default();
+        let input = format!("echo {}/*.txt", root);
+        let args = |registry: &CommandRegistry| expand_first(&input, registry).unwrap().args;
+
+        assert_eq!(names(args(&registry), &root), ["alpha.txt", "zeta.txt"]);
+
+        registry.set_option(ShellOption::Noglob, true);
+        assert_eq!(args(&registry), [format!("{}/*.txt", root)]);
+
+        registry.set_option(ShellOption::Noglob, false);
+        assert_eq!(args(&registry).len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod glob_redirect_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+    use codecrafters_shell::parser::Redirect;
+    use std::path::PathBuf;
+
+    fn redirect_path(input: &str) -> PathBuf {
+        match &expand_first(input, &env_lookup).unwrap().redirects[0] {
+            Redirect::File { path, .. } => path.clone(),
+            other => panic!("expected a file redirect, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_redirect_target_with_one_match_is_expanded() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            redirect_path(&format!("echo hi > {}/z*.txt", root)),
+            PathBuf::from(format!("{}/zeta.txt", root))
+        );
+    }
+
+    #[test]
+    fn test_unmatched_redirect_target_stays_literal() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            redirect_path(&format!("echo hi >> {}/*.rs", root)),
+            PathBuf::from(format!("{}/*.rs", root))
+        );
+    }
+
+    #[test]
+    fn test_quoted_redirect_target_is_not_globbed() {
+        let (_dir, root) = setup();
+        assert_eq!(
+            redirect_path(&format!("echo hi > '{}/*.txt'", root)),
+            PathBuf::from(format!("{}/*.txt", root))
+        );
+    }
+
+    #[test]
+    fn test_redirect_target_with_several_matches_is_ambiguous() {
+        let (_dir, root) = setup();
+        let err = expand_first(&format!("echo hi > {}/*.txt", root), &env_lookup).unwrap_err();
+        assert!(matches!(err, ShellError::AmbiguousRedirect { .. }));
+        assert_eq!(
+            err.to_string(),
+            format!("{}/*.txt: ambiguous redirect", root)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use codecrafters_shell::commands::CommandRegistry;
+use common::EnvGuard;
+use std::fs;
+use tempfile::TempDir;
+
+/// Runs `test` with `$HISTSIZE` set to `value`. It caps every registry in
+/// the process, which is why these tests have a binary of their own.
+fn with_histsize(value: Option<&str>, test: impl FnOnce()) {
+    let mut env = EnvGuard::lock();
+    env.set_var("HISTSIZE", value);
+    test();
+}
+
+#[cfg(test)]
+mod history_size_tests {
+    use super::*;
+
+    #[test]
+    fn test_histsize_keeps_most_recent_entries() {
+        with_histsize(Some("2"), || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("echo one");
+            registry.add_history_entry("echo two");
+            registry.add_history_entry("echo three");
+
+            assert_eq!(registry.get_history(), vec!["echo two", "echo three"]);
+        });
+    }
+
+    #[test]
+    fn test_histsize_zero_stores_nothing() {
+        with_histsize(Some("0"), || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("echo one");
+
+            assert!(registry.get_history().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_unset_histsize_uses_default() {
+        with_histsize(None, || {
+            let registry = CommandRegistry::new();
+            for i in 0..510 {
+                registry.add_history_entry(&format!("echo {}", i));
+            }
+
+            let history = registry.get_history();
+            assert_eq!(history.len(), 500);
+            assert_eq!(history[0], "echo 10");
+        });
+    }
+
+    #[test]
+    fn test_negative_histsize_keeps_everything() {
+        with_histsize(Some("-1"), || {
+            let registry = CommandRegistry::new();
+            for i in 0..510 {
+                registry.add_history_entry(&format!("echo {}", i));
+            }
+
+            let history = registry.get_history();
+            assert_eq!(history.len(), 510);
+            assert_eq!(history[0], "echo 0");
+        });
+    }
+
+    #[test]
+    fn test_invalid_histsize_uses_default() {
+        with_histsize(Some("lots"), || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("echo one");
+            registry.add_history_entry("echo two");
+            registry.add_history_entry("echo three");
+
+            assert_eq!(registry.get_history().len(), 3);
+        });
+    }
+
+    #[test]
+    fn test_trimming_keeps_append_cursor_in_place() {
+        with_histsize(Some("2"), || {
+            let temp_dir = TempDir::new().unwrap();
+            let first = temp_dir.path().join("first.txt");
+            let second = temp_dir.path().join("second.txt");
+
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("echo one");
+            registry.add_history_entry("echo two");
+            registry.write_history_to_file(&first, true, false).unwrap();
+
+            registry.add_history_entry("echo three");
+            registry
+                .write_history_to_file(&second, true, false)
+                .unwrap();
+
+            assert_eq!(fs::read_to_string(&second).unwrap(), "echo three\n");
+        });
+    }
+}
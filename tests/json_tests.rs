@@ -0,0 +1,46 @@
+use codecrafters_shell::json::{JsonObject, push_string};
+
+#[cfg(test)]
+mod json_string_tests {
+    use super::*;
+
+    fn quote(value: &str) -> String {
+        let mut out = String::new();
+        push_string(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn test_plain_string_is_quoted() {
+        assert_eq!(quote("ls"), "\"ls\"");
+    }
+
+    #[test]
+    fn test_special_characters_are_escaped() {
+        assert_eq!(quote("a\"b\\c\nd\te"), "\"a\\\"b\\\\c\\nd\\te\"");
+    }
+
+    #[test]
+    fn test_control_characters_use_unicode_escapes() {
+        assert_eq!(quote("\u{1b}"), "\"\\u001b\"");
+    }
+}
+
+#[cfg(test)]
+mod json_object_tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_object() {
+        assert_eq!(JsonObject::new().finish(), "{}");
+    }
+
+    #[test]
+    fn test_fields_keep_insertion_order() {
+        let object = JsonObject::new()
+            .string("name", "ls")
+            .number("id", 3)
+            .finish();
+        assert_eq!(object, "{\"name\":\"ls\",\"id\":3}");
+    }
+}
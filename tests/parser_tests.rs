@@ -1,6 +1,29 @@
 use std::path::PathBuf;
 
-use codecrafters_shell::parser::{parse_input, tokenize_input};
+use codecrafters_shell::error::ShellResult;
+use codecrafters_shell::parser::{
+    Connector, ParsedCommand, Redirect, RedirectMode, VariableLookup, parse_input, tokenize_input,
+};
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Parses `input` and expands each pipeline through `lookup`, as the
+/// executor does just before running it.
+fn parse_expanded_with(
+    input: &str,
+    lookup: &dyn VariableLookup,
+) -> ShellResult<Vec<Vec<ParsedCommand>>> {
+    parse_input(input)?
+        .iter()
+        .map(|pipeline| pipeline.expand(lookup))
+        .collect()
+}
+
+fn parse_expanded(input: &str) -> ShellResult<Vec<Vec<ParsedCommand>>> {
+    parse_expanded_with(input, &env_lookup)
+}
 
 #[cfg(test)]
 mod tokenize_tests {
@@ -141,7 +164,19 @@ mod tokenize_tests {
     #[test]
     fn test_tokenize_empty_quotes() {
         let result = tokenize_input(r#"echo """#);
-        assert_eq!(result, vec!["echo"]);
+        assert_eq!(result, vec!["echo", ""]);
+    }
+
+    #[test]
+    fn test_tokenize_quoted_empties_are_kept() {
+        let result = tokenize_input(r#"printf "[%s]" "" x '' "$TOKENIZE_UNSET_VAR""#);
+        assert_eq!(result, vec!["printf", "[%s]", "", "x", "", ""]);
+    }
+
+    #[test]
+    fn test_tokenize_unquoted_empty_expansion_is_dropped() {
+        let result = tokenize_input("echo $TOKENIZE_UNSET_VAR x");
+        assert_eq!(result, vec!["echo", "x"]);
     }
 
     #[test]
@@ -155,25 +190,248 @@ mod tokenize_tests {
         let result = tokenize_input(r"echo hello\|world");
         assert_eq!(result, vec!["echo", "hello|world"]);
     }
+
+    #[test]
+    fn test_tokenize_owned_multibyte_input() {
+        let input = format!("echo {} \"{}\"", "héllo", "wörld ✓");
+        let result = tokenize_input(&input);
+        drop(input);
+        assert_eq!(result, vec!["echo", "héllo", "wörld ✓"]);
+    }
+
+    #[test]
+    fn test_tokenize_double_quote_line_continuation() {
+        let result = tokenize_input("echo \"hello \\\nworld\"");
+        assert_eq!(result, vec!["echo", "hello world"]);
+    }
+
+    #[test]
+    fn test_tokenize_double_quote_keeps_plain_newline() {
+        let result = tokenize_input("echo \"hello\nworld\"");
+        assert_eq!(result, vec!["echo", "hello\nworld"]);
+    }
+
+    #[test]
+    fn test_tokenize_unquoted_line_continuation() {
+        let result = tokenize_input("echo hel\\\nlo world");
+        assert_eq!(result, vec!["echo", "hello", "world"]);
+    }
+
+    #[test]
+    fn test_tokenize_single_quote_keeps_backslash_newline() {
+        let result = tokenize_input("echo 'a\\\nb'");
+        assert_eq!(result, vec!["echo", "a\\\nb"]);
+    }
+
+    #[test]
+    fn test_tokenize_comment_discards_rest_of_line() {
+        let result = tokenize_input("echo hi # bye");
+        assert_eq!(result, vec!["echo", "hi"]);
+    }
+
+    #[test]
+    fn test_tokenize_comment_only_line() {
+        let result = tokenize_input("# just a comment");
+        assert_eq!(result, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_tokenize_quoted_hash_is_literal() {
+        let result = tokenize_input("echo 'a # b'");
+        assert_eq!(result, vec!["echo", "a # b"]);
+    }
+
+    #[test]
+    fn test_tokenize_hash_inside_word_is_literal() {
+        let result = tokenize_input("echo a#b");
+        assert_eq!(result, vec!["echo", "a#b"]);
+    }
+
+    #[test]
+    fn test_tokenize_escaped_hash_is_literal() {
+        let result = tokenize_input(r"echo \#b");
+        assert_eq!(result, vec!["echo", "#b"]);
+    }
+
+    #[test]
+    fn test_tokenize_comment_after_operator() {
+        let result = tokenize_input("echo a;# echo b");
+        assert_eq!(result, vec!["echo", "a", ";"]);
+    }
+}
+
+#[cfg(test)]
+mod variable_expansion_tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_expand_unquoted_variable() {
+        let home = env::var("HOME").unwrap();
+        let result = tokenize_input("echo $HOME");
+        assert_eq!(result, vec!["echo".to_string(), home]);
+    }
+
+    #[test]
+    fn test_expand_variable_in_double_quotes() {
+        let path = env::var("PATH").unwrap();
+        let result = tokenize_input(r#"echo "path: $PATH""#);
+        assert_eq!(result, vec!["echo".to_string(), format!("path: {}", path)]);
+    }
+
+    #[test]
+    fn test_single_quotes_keep_variable_literal() {
+        let result = tokenize_input("echo '$HOME'");
+        assert_eq!(result, vec!["echo", "$HOME"]);
+    }
+
+    #[test]
+    fn test_expand_braced_variable() {
+        let home = env::var("HOME").unwrap();
+        let result = tokenize_input("echo ${HOME}x");
+        assert_eq!(result, vec!["echo".to_string(), format!("{}x", home)]);
+    }
+
+    #[test]
+    fn test_unset_variable_expands_to_nothing() {
+        let result = tokenize_input("echo a${UNSET_VARIABLE_XYZ_12345}b $UNSET_VARIABLE_XYZ_12345");
+        assert_eq!(result, vec!["echo", "ab"]);
+    }
+
+    #[test]
+    fn test_lone_dollar_is_literal() {
+        let result = tokenize_input(r#"echo $ "$""#);
+        assert_eq!(result, vec!["echo", "$", "$"]);
+    }
+
+    #[test]
+    fn test_parse_input_expands_braces_and_tilde() {
+        let home = env::var("HOME").unwrap();
+        let pipelines = parse_expanded("echo pre{1,2} ~").unwrap();
+        assert_eq!(pipelines[0][0].args, vec!["pre1", "pre2", &home]);
+    }
+
+    #[test]
+    fn test_escaped_dollar_in_double_quotes() {
+        let result = tokenize_input(r#"echo "\$HOME""#);
+        assert_eq!(result, vec!["echo", "$HOME"]);
+    }
+}
+
+#[cfg(test)]
+mod quote_aware_expansion_tests {
+    use super::parse_expanded_with;
+    use codecrafters_shell::parser::tokenize_input_with;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn with_x(value: &str) -> impl Fn(&str) -> Option<String> + '_ {
+        move |name: &str| (name == "x").then(|| value.to_string())
+    }
+
+    #[test]
+    fn test_quoted_variable_is_not_split() {
+        let result = tokenize_input_with(r#"echo "$x""#, &with_x("a  b"));
+        assert_eq!(result, vec!["echo", "a  b"]);
+    }
+
+    #[test]
+    fn test_unquoted_variable_is_split() {
+        let result = tokenize_input_with("echo $x", &with_x("a  b"));
+        assert_eq!(result, vec!["echo", "a", "b"]);
+    }
+
+    #[test]
+    fn test_split_only_applies_to_the_expansion() {
+        let result = tokenize_input_with(r#"echo 'p q'$x"r s""#, &with_x("a b"));
+        assert_eq!(result, vec!["echo", "p qa", "br s"]);
+    }
+
+    #[test]
+    fn test_quotes_inside_values_are_not_removed() {
+        let result = tokenize_input_with("echo $x", &with_x(r#""a b""#));
+        assert_eq!(result, vec!["echo", "\"a", "b\""]);
+    }
+
+    #[test]
+    fn test_quoted_expansion_is_not_globbed() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("one.txt"), "").unwrap();
+        let pattern = format!("{}/*.txt", dir.path().display());
+        let lookup = with_x(&pattern);
+
+        let pipelines = parse_expanded_with(r#"echo $x "$x""#, &lookup).unwrap();
+        assert_eq!(
+            pipelines[0][0].args,
+            vec![format!("{}/one.txt", dir.path().display()), pattern.clone()]
+        );
+    }
+
+    #[test]
+    fn test_split_words_stay_arguments_not_operators() {
+        let pipelines = parse_expanded_with("echo $x", &with_x("a ; b")).unwrap();
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0][0].args, vec!["a", ";", "b"]);
+    }
+
+    #[test]
+    fn test_expanded_redirect_operator_is_an_argument() {
+        let pipelines = parse_expanded_with("echo $x y", &with_x(">")).unwrap();
+        assert_eq!(pipelines[0][0].args, vec![">", "y"]);
+        assert!(pipelines[0][0].redirects.is_empty());
+    }
+
+    #[test]
+    fn test_expanded_assignment_is_the_command_word() {
+        let pipelines = parse_expanded_with("$x cmd", &with_x("A=1")).unwrap();
+        assert_eq!(pipelines[0][0].command, "A=1");
+        assert_eq!(pipelines[0][0].args, vec!["cmd"]);
+        assert!(pipelines[0][0].env_assignments.is_empty());
+    }
+
+    #[test]
+    fn test_assignment_value_is_expanded_as_one_word() {
+        let pipelines = parse_expanded_with("A=$x cmd", &with_x("a  *")).unwrap();
+        assert_eq!(pipelines[0][0].command, "cmd");
+        assert_eq!(
+            pipelines[0][0].env_assignments,
+            vec![("A".to_string(), "a  *".to_string())]
+        );
+    }
 }
 
 #[cfg(test)]
 mod parse_command_tests {
     use super::*;
 
+    fn parse_commands(input: &str) -> Vec<ParsedCommand> {
+        parse_expanded(input)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn file_redirect(fd: i32, path: &str, mode: RedirectMode) -> Redirect {
+        Redirect::File {
+            fd,
+            path: PathBuf::from(path),
+            mode,
+        }
+    }
+
     #[test]
     fn test_parse_simple_command() {
-        let commands = parse_input("echo hello");
+        let commands = parse_commands("echo hello");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello"]);
-        assert_eq!(commands[0].stdout_redirect, None);
-        assert_eq!(commands[0].stderr_redirect, None);
+        assert!(commands[0].redirects.is_empty());
     }
 
     #[test]
     fn test_parse_command_with_multiple_args() {
-        let commands = parse_input("echo hello world foo");
+        let commands = parse_commands("echo hello world foo");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello", "world", "foo"]);
@@ -181,87 +439,263 @@ mod parse_command_tests {
 
     #[test]
     fn test_parse_stdout_redirect() {
-        let commands = parse_input("echo hello > output.txt");
+        let commands = parse_commands("echo hello > output.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello"]);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Truncate)]
         );
-        assert_eq!(commands[0].stdout_redirect_append, false);
     }
 
     #[test]
     fn test_parse_stdout_redirect_1() {
-        let commands = parse_input("echo hello 1> output.txt");
+        let commands = parse_commands("echo hello 1> output.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Truncate)]
         );
-        assert_eq!(commands[0].stdout_redirect_append, false);
     }
 
     #[test]
     fn test_parse_stdout_append_redirect() {
-        let commands = parse_input("echo hello >> output.txt");
+        let commands = parse_commands("echo hello >> output.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello"]);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Append)]
         );
-        assert_eq!(commands[0].stdout_redirect_append, true);
     }
 
     #[test]
     fn test_parse_stdout_append_redirect_1() {
-        let commands = parse_input("echo hello 1>> output.txt");
+        let commands = parse_commands("echo hello 1>> output.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Append)]
         );
-        assert_eq!(commands[0].stdout_redirect_append, true);
     }
 
     #[test]
     fn test_parse_stderr_redirect() {
-        let commands = parse_input("cat file 2> error.txt");
+        let commands = parse_commands("cat file 2> error.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "cat");
         assert_eq!(commands[0].args, vec!["file"]);
         assert_eq!(
-            commands[0].stderr_redirect,
-            Some(PathBuf::from("error.txt"))
+            commands[0].redirects,
+            vec![file_redirect(2, "error.txt", RedirectMode::Truncate)]
+        );
+    }
+
+    #[test]
+    fn test_parse_force_redirect() {
+        let commands = parse_commands("echo hello >| out.txt 2>| err.txt");
+        assert_eq!(commands[0].args, vec!["hello"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "out.txt", RedirectMode::Clobber),
+                file_redirect(2, "err.txt", RedirectMode::Clobber),
+            ]
         );
-        assert_eq!(commands[0].stderr_redirect_append, false);
     }
 
     #[test]
     fn test_parse_stderr_append_redirect() {
-        let commands = parse_input("cat file 2>> error.txt");
+        let commands = parse_commands("cat file 2>> error.txt");
         assert_eq!(commands.len(), 1);
         assert_eq!(
-            commands[0].stderr_redirect,
-            Some(PathBuf::from("error.txt"))
+            commands[0].redirects,
+            vec![file_redirect(2, "error.txt", RedirectMode::Append)]
+        );
+    }
+
+    #[test]
+    fn test_parse_stdin_redirect() {
+        let commands = parse_commands("cat < in.txt");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command, "cat");
+        assert!(commands[0].args.is_empty());
+        assert_eq!(
+            commands[0].redirects,
+            vec![file_redirect(0, "in.txt", RedirectMode::Read)]
+        );
+    }
+
+    #[test]
+    fn test_redirect_before_command_word() {
+        let pipelines = parse_expanded("2>/dev/null echo hi").unwrap();
+        let command = &pipelines[0][0];
+        assert_eq!(command.command, "echo");
+        assert_eq!(command.args, vec!["hi"]);
+        assert_eq!(
+            command.redirects,
+            vec![file_redirect(2, "/dev/null", RedirectMode::Truncate)]
+        );
+
+        let pipelines = parse_expanded("> out.txt echo hi").unwrap();
+        assert_eq!(pipelines[0][0].command, "echo");
+        assert_eq!(
+            pipelines[0][0].redirects,
+            vec![file_redirect(1, "out.txt", RedirectMode::Truncate)]
+        );
+
+        let pipelines = parse_expanded("<in.txt cat").unwrap();
+        assert_eq!(pipelines[0][0].command, "cat");
+        assert_eq!(
+            pipelines[0][0].redirects,
+            vec![file_redirect(0, "in.txt", RedirectMode::Read)]
+        );
+    }
+
+    #[test]
+    fn test_redirect_without_command_word() {
+        let pipelines = parse_expanded(">out.txt").unwrap();
+        assert!(pipelines[0][0].command.is_empty());
+        assert_eq!(
+            pipelines[0][0].redirects,
+            vec![file_redirect(1, "out.txt", RedirectMode::Truncate)]
+        );
+    }
+
+    #[test]
+    fn test_parse_stdin_redirect_0() {
+        let commands = parse_commands("wc -l 0< in.txt");
+        assert_eq!(commands[0].args, vec!["-l"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![file_redirect(0, "in.txt", RedirectMode::Read)]
+        );
+    }
+
+    #[test]
+    fn test_parse_numeric_fd_redirects() {
+        let commands = parse_commands("cmd 3> x 0< y 4>> z");
+        assert!(commands[0].args.is_empty());
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(3, "x", RedirectMode::Truncate),
+                file_redirect(0, "y", RedirectMode::Read),
+                file_redirect(4, "z", RedirectMode::Append),
+            ]
+        );
+
+        let commands = parse_commands("cmd 3<in 5>&3 >&2");
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(3, "in", RedirectMode::Read),
+                Redirect::Duplicate { fd: 5, target: 3 },
+                Redirect::Duplicate { fd: 1, target: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_redirects_without_spaces() {
+        let commands = parse_commands("echo hi>out 2>>err a3>b");
+        assert_eq!(commands[0].args, vec!["hi", "a3"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "out", RedirectMode::Truncate),
+                file_redirect(2, "err", RedirectMode::Append),
+                file_redirect(1, "b", RedirectMode::Truncate),
+            ]
+        );
+
+        let commands = parse_commands("echo '3>x' \\3>y");
+        assert_eq!(commands[0].args, vec!["3>x", "3"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![file_redirect(1, "y", RedirectMode::Truncate)]
         );
-        assert_eq!(commands[0].stderr_redirect_append, true);
     }
 
     #[test]
     fn test_parse_both_redirects() {
-        let commands = parse_input("cat file > out.txt 2> err.txt");
+        let commands = parse_commands("cat file > out.txt 2> err.txt");
         assert_eq!(commands.len(), 1);
-        assert_eq!(commands[0].stdout_redirect, Some(PathBuf::from("out.txt")));
-        assert_eq!(commands[0].stderr_redirect, Some(PathBuf::from("err.txt")));
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "out.txt", RedirectMode::Truncate),
+                file_redirect(2, "err.txt", RedirectMode::Truncate),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stderr_to_stdout_preserves_order() {
+        let commands = parse_commands("ls /nope 2>&1 > out.txt");
+        assert_eq!(commands[0].args, vec!["/nope"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                Redirect::Duplicate { fd: 2, target: 1 },
+                file_redirect(1, "out.txt", RedirectMode::Truncate),
+            ]
+        );
+
+        let commands = parse_commands("ls /nope > out.txt 2>&1");
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "out.txt", RedirectMode::Truncate),
+                Redirect::Duplicate { fd: 2, target: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_combined_redirects() {
+        let commands = parse_commands("ls /nope &> all.txt");
+        assert_eq!(commands[0].args, vec!["/nope"]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "all.txt", RedirectMode::Truncate),
+                Redirect::Duplicate { fd: 2, target: 1 },
+            ]
+        );
+
+        let commands = parse_commands("ls /nope &>> all.txt");
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(1, "all.txt", RedirectMode::Append),
+                Redirect::Duplicate { fd: 2, target: 1 },
+            ]
+        );
+        assert!(parse_input("ls &>").is_err());
+    }
+
+    #[test]
+    fn test_parse_pipe_both_joins_stderr_after_own_redirects() {
+        let commands = parse_commands("make 2> err.txt |& grep error | wc -l");
+        assert_eq!(commands.len(), 3);
+        assert_eq!(
+            commands[0].redirects,
+            vec![
+                file_redirect(2, "err.txt", RedirectMode::Truncate),
+                Redirect::Duplicate { fd: 2, target: 1 },
+            ]
+        );
+        assert_eq!(commands[1].command, "grep");
+        assert!(commands[1].redirects.is_empty());
+        assert!(commands[2].redirects.is_empty());
     }
 
     #[test]
     fn test_parse_pipeline_two_commands() {
-        let commands = parse_input("echo hello | cat");
+        let commands = parse_commands("echo hello | cat");
         assert_eq!(commands.len(), 2);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello"]);
@@ -271,7 +705,7 @@ mod parse_command_tests {
 
     #[test]
     fn test_parse_pipeline_three_commands() {
-        let commands = parse_input("cat file | grep pattern | wc -l");
+        let commands = parse_commands("cat file | grep pattern | wc -l");
         assert_eq!(commands.len(), 3);
         assert_eq!(commands[0].command, "cat");
         assert_eq!(commands[1].command, "grep");
@@ -282,31 +716,31 @@ mod parse_command_tests {
 
     #[test]
     fn test_parse_pipeline_with_redirect() {
-        let commands = parse_input("cat file | grep pattern > output.txt");
+        let commands = parse_commands("cat file | grep pattern > output.txt");
         assert_eq!(commands.len(), 2);
         assert_eq!(commands[0].command, "cat");
         assert_eq!(commands[1].command, "grep");
         assert_eq!(
-            commands[1].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[1].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Truncate)]
         );
     }
 
     #[test]
     fn test_parse_empty_input() {
-        let commands = parse_input("");
+        let commands = parse_commands("");
         assert_eq!(commands.len(), 0);
     }
 
     #[test]
     fn test_parse_whitespace_only() {
-        let commands = parse_input("   \t  ");
+        let commands = parse_commands("   \t  ");
         assert_eq!(commands.len(), 0);
     }
 
     #[test]
     fn test_parse_command_with_quoted_args() {
-        let commands = parse_input(r#"echo "hello world""#);
+        let commands = parse_commands(r#"echo "hello world""#);
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].command, "echo");
         assert_eq!(commands[0].args, vec!["hello world"]);
@@ -314,22 +748,625 @@ mod parse_command_tests {
 
     #[test]
     fn test_parse_redirect_with_quoted_filename() {
-        let commands = parse_input(r#"echo hello > "output file.txt""#);
+        let commands = parse_commands(r#"echo hello > "output file.txt""#);
         assert_eq!(commands.len(), 1);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output file.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output file.txt", RedirectMode::Truncate)]
         );
     }
 
     #[test]
     fn test_parse_args_after_redirect() {
-        let commands = parse_input("echo hello > output.txt world");
+        let commands = parse_commands("echo hello > output.txt world");
         assert_eq!(commands.len(), 1);
         assert_eq!(commands[0].args, vec!["hello", "world"]);
         assert_eq!(
-            commands[0].stdout_redirect,
-            Some(PathBuf::from("output.txt"))
+            commands[0].redirects,
+            vec![file_redirect(1, "output.txt", RedirectMode::Truncate)]
+        );
+    }
+
+    #[test]
+    fn test_quoted_redirect_operators_are_arguments() {
+        let commands = parse_commands(r"echo '>' x \> y '2>&1' '<<<' z");
+        assert_eq!(
+            commands[0].args,
+            vec![">", "x", ">", "y", "2>&1", "<<<", "z"]
+        );
+        assert!(commands[0].redirects.is_empty());
+        assert_eq!(commands[0].heredoc, None);
+    }
+
+    #[test]
+    fn test_parse_redirect_expands_tilde() {
+        let commands = parse_commands("echo hello > ~/output.txt");
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            commands[0].redirects,
+            vec![Redirect::File {
+                fd: 1,
+                path: PathBuf::from(home).join("output.txt"),
+                mode: RedirectMode::Truncate,
+            }]
+        );
+    }
+
+    fn assignment(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_parse_bare_assignment() {
+        let commands = parse_commands("FOO=bar");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command, "");
+        assert!(commands[0].args.is_empty());
+        assert_eq!(commands[0].env_assignments, vec![assignment("FOO", "bar")]);
+    }
+
+    #[test]
+    fn test_parse_assignments_before_command() {
+        let commands = parse_commands("FOO=bar BAZ= env -i");
+        assert_eq!(commands[0].command, "env");
+        assert_eq!(commands[0].args, vec!["-i"]);
+        assert_eq!(
+            commands[0].env_assignments,
+            vec![assignment("FOO", "bar"), assignment("BAZ", "")]
+        );
+    }
+
+    #[test]
+    fn test_parse_assignment_after_command_is_argument() {
+        let commands = parse_commands("echo FOO=bar");
+        assert_eq!(commands[0].command, "echo");
+        assert_eq!(commands[0].args, vec!["FOO=bar"]);
+        assert!(commands[0].env_assignments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_name_is_not_assignment() {
+        let commands = parse_commands("1FOO=bar");
+        assert_eq!(commands[0].command, "1FOO=bar");
+        assert!(commands[0].env_assignments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_quoted_assignment_is_command_word() {
+        for input in [r#""FOO=bar" cmd"#, "'FOO'=bar cmd", r"FOO\=bar cmd"] {
+            let commands = parse_commands(input);
+            assert_eq!(commands[0].command, "FOO=bar", "{}", input);
+            assert_eq!(commands[0].args, vec!["cmd"]);
+            assert!(commands[0].env_assignments.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_assignment_before_redirect() {
+        let commands = parse_commands("FOO=bar > out.txt");
+        assert_eq!(commands[0].command, "");
+        assert_eq!(commands[0].env_assignments, vec![assignment("FOO", "bar")]);
+        assert_eq!(
+            commands[0].redirects,
+            vec![file_redirect(1, "out.txt", RedirectMode::Truncate)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod positional_args_tests {
+    use codecrafters_shell::commands::CommandRegistry;
+    use codecrafters_shell::parser::tokenize_input_with;
+
+    fn registry_with_args(args: &[&str]) -> CommandRegistry {
+        let registry = CommandRegistry::new();
+        registry.set_positional_args(args.iter().map(|s| s.to_string()).collect());
+        registry
+    }
+
+    #[test]
+    fn test_quoted_at_keeps_each_arg_separate() {
+        let registry = registry_with_args(&["a b", "c", "d"]);
+        let result = tokenize_input_with(r#"echo "$@""#, &registry);
+        assert_eq!(result, vec!["echo", "a b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_quoted_star_joins_args() {
+        let registry = registry_with_args(&["a b", "c", "d"]);
+        let result = tokenize_input_with(r#"echo "$*""#, &registry);
+        assert_eq!(result, vec!["echo", "a b c d"]);
+    }
+
+    #[test]
+    fn test_quoted_at_attaches_prefix_and_suffix() {
+        let registry = registry_with_args(&["one", "two"]);
+        let result = tokenize_input_with(r#"echo "x$@y""#, &registry);
+        assert_eq!(result, vec!["echo", "xone", "twoy"]);
+    }
+
+    #[test]
+    fn test_quoted_at_without_args_expands_to_nothing() {
+        let registry = registry_with_args(&[]);
+        let result = tokenize_input_with(r#"echo "$@""#, &registry);
+        assert_eq!(result, vec!["echo"]);
+    }
+
+    #[test]
+    fn test_positional_count_and_index() {
+        let registry = registry_with_args(&["first", "second"]);
+        let result = tokenize_input_with("echo $# $1 ${2} $3", &registry);
+        assert_eq!(result, vec!["echo", "2", "first", "second"]);
+    }
+}
+
+#[cfg(test)]
+mod command_list_tests {
+    use super::*;
+
+    fn command_names(input: &str) -> Vec<Vec<String>> {
+        parse_expanded(input)
+            .unwrap()
+            .into_iter()
+            .map(|pipeline| pipeline.into_iter().map(|cmd| cmd.command).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_semicolon_separates_pipelines() {
+        let pipelines = parse_expanded("echo a; echo b").unwrap();
+        assert_eq!(pipelines.len(), 2);
+        assert_eq!(pipelines[0][0].args, vec!["a"]);
+        assert_eq!(pipelines[1][0].args, vec!["b"]);
+    }
+
+    #[test]
+    fn test_trailing_semicolon() {
+        assert_eq!(command_names("echo a;"), vec![vec!["echo"]]);
+        assert_eq!(command_names("echo a ; "), vec![vec!["echo"]]);
+    }
+
+    #[test]
+    fn test_semicolon_in_quotes_is_literal() {
+        let pipelines = parse_expanded(r#"echo "a;b" ';'"#).unwrap();
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0][0].args, vec!["a;b", ";"]);
+    }
+
+    #[test]
+    fn test_pipes_within_segments() {
+        assert_eq!(
+            command_names("echo a | cat; ls | wc -l"),
+            vec![vec!["echo", "cat"], vec!["ls", "wc"]]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_semicolon_operator() {
+        let result = tokenize_input("echo a;echo b");
+        assert_eq!(result, vec!["echo", "a", ";", "echo", "b"]);
+    }
+
+    #[test]
+    fn test_tokenize_logical_operators() {
+        let result = tokenize_input("a && b || c | d");
+        assert_eq!(result, vec!["a", "&&", "b", "||", "c", "|", "d"]);
+    }
+
+    #[test]
+    fn test_tokenize_logical_operators_without_spaces() {
+        let result = tokenize_input("a&&b||c|d");
+        assert_eq!(result, vec!["a", "&&", "b", "||", "c", "|", "d"]);
+    }
+
+    #[test]
+    fn test_tokenize_pipe_both_operator() {
+        assert_eq!(tokenize_input("a |& b"), vec!["a", "|&", "b"]);
+        assert_eq!(tokenize_input("a|&b||c"), vec!["a", "|&", "b", "||", "c"]);
+    }
+
+    #[test]
+    fn test_parse_connectors() {
+        let pipelines = parse_input("mkdir foo && cd foo || echo failed | cat; pwd").unwrap();
+        let connectors: Vec<Connector> = pipelines.iter().map(|p| p.connector).collect();
+        assert_eq!(
+            connectors,
+            vec![
+                Connector::Always,
+                Connector::And,
+                Connector::Or,
+                Connector::Always
+            ]
+        );
+        assert_eq!(pipelines[2].expand(&env_lookup).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_quoted_logical_operators_are_literal() {
+        let pipelines = parse_expanded(r#"echo "a && b" '||'"#).unwrap();
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0][0].args, vec!["a && b", "||"]);
+    }
+
+    #[test]
+    fn test_parse_trailing_background() {
+        let pipelines = parse_expanded("sleep 5 &").unwrap();
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0][0].args, vec!["5"]);
+        assert!(pipelines[0][0].background);
+    }
+
+    #[test]
+    fn test_parse_background_separates_pipelines() {
+        let pipelines = parse_input("sleep 1 & echo hi").unwrap();
+        assert_eq!(pipelines.len(), 2);
+        assert!(pipelines[0].expand(&env_lookup).unwrap()[0].background);
+        assert!(!pipelines[1].expand(&env_lookup).unwrap()[0].background);
+        assert_eq!(pipelines[1].connector, Connector::Always);
+    }
+
+    #[test]
+    fn test_ampersand_in_redirect_is_not_background() {
+        let pipelines = parse_expanded("ls 2>&1").unwrap();
+        assert_eq!(pipelines.len(), 1);
+        assert!(!pipelines[0][0].background);
+        assert_eq!(
+            pipelines[0][0].redirects,
+            vec![Redirect::Duplicate { fd: 2, target: 1 }]
+        );
+    }
+}
+
+#[cfg(test)]
+mod syntax_error_tests {
+    use super::*;
+    use codecrafters_shell::error::ShellError;
+
+    fn syntax_error(input: &str) -> String {
+        match parse_input(input) {
+            Err(ShellError::SyntaxError(token)) => token,
+            other => panic!("expected syntax error for {:?}, got {:?}", input, other),
+        }
+    }
+
+    #[test]
+    fn test_bare_redirect_is_syntax_error() {
+        assert_eq!(syntax_error(">"), "newline");
+    }
+
+    #[test]
+    fn test_bare_stderr_redirect_is_syntax_error() {
+        assert_eq!(syntax_error("2>"), "newline");
+    }
+
+    #[test]
+    fn test_quoted_redirect_as_command_word_is_not_syntax_error() {
+        for (input, word) in [(r#"">" x"#, ">"), (r"\> x", ">"), ("'2>' x", "2>")] {
+            let pipelines = parse_expanded(input).unwrap();
+            let command = &pipelines[0][0];
+            assert_eq!(command.command, word, "{:?}", input);
+            assert_eq!(command.args, vec!["x"], "{:?}", input);
+            assert!(command.redirects.is_empty(), "{:?}", input);
+        }
+        assert!(parse_input(r#"echo ">""#).is_ok());
+    }
+
+    #[test]
+    fn test_leading_pipe_is_syntax_error() {
+        assert_eq!(syntax_error("| cat"), "|");
+    }
+
+    #[test]
+    fn test_trailing_pipe_is_syntax_error() {
+        assert_eq!(syntax_error("echo hi |"), "|");
+        assert_eq!(syntax_error("echo hi |&"), "|&");
+        assert_eq!(syntax_error("|& cat"), "|&");
+    }
+
+    #[test]
+    fn test_redirect_without_target_is_syntax_error() {
+        assert_eq!(syntax_error("echo hi >"), "newline");
+    }
+
+    #[test]
+    fn test_syntax_error_message() {
+        let err = parse_input("| cat").unwrap_err();
+        assert_eq!(err.to_string(), "syntax error near unexpected token `|'");
+    }
+
+    #[test]
+    fn test_quoted_pipe_is_not_syntax_error() {
+        assert!(parse_input("echo '|'").is_ok());
+    }
+}
+
+#[cfg(test)]
+mod unterminated_input_tests {
+    use codecrafters_shell::error::ShellError;
+    use codecrafters_shell::parser::{parse_input, tokenize_input, try_tokenize_input};
+
+    fn unterminated(input: &str) -> String {
+        match try_tokenize_input(input) {
+            Err(ShellError::UnexpectedEof(construct)) => construct,
+            other => panic!(
+                "expected unterminated input for {:?}, got {:?}",
+                input, other
+            ),
+        }
+    }
+
+    #[test]
+    fn test_unterminated_single_quote() {
+        assert_eq!(unterminated("echo 'hello"), "in single-quoted string");
+    }
+
+    #[test]
+    fn test_unterminated_double_quote() {
+        assert_eq!(unterminated("echo \"hello"), "in double-quoted string");
+    }
+
+    #[test]
+    fn test_double_quote_ending_in_backslash() {
+        assert_eq!(unterminated("echo \"hello\\"), "in double-quoted string");
+    }
+
+    #[test]
+    fn test_trailing_backslash() {
+        assert_eq!(unterminated("echo hello\\"), "after backslash");
+    }
+
+    #[test]
+    fn test_terminated_input_tokenizes() {
+        assert_eq!(
+            try_tokenize_input("echo 'a' \"b\" c\\ d").unwrap(),
+            vec!["echo", "a", "b", "c d"]
+        );
+    }
+
+    #[test]
+    fn test_parse_input_reports_unterminated_quote() {
+        let error = parse_input("echo \"unterminated").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "syntax error: unexpected end of file in double-quoted string"
+        );
+        assert_eq!(error.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_tokenize_input_stays_lenient() {
+        assert_eq!(tokenize_input("echo 'open"), vec!["echo", "open"]);
+    }
+}
+
+#[cfg(test)]
+mod completeness_tests {
+    use codecrafters_shell::parser::is_complete;
+
+    #[test]
+    fn test_complete_inputs() {
+        for input in [
+            "",
+            "echo hi",
+            "echo 'a b' \"c d\"",
+            "echo \"multi\nline\"",
+            "echo a \\\nb",
+            "echo it\\'s",
+            "echo hi # it's a comment",
+        ] {
+            assert!(is_complete(input), "{:?} should be complete", input);
+        }
+    }
+
+    #[test]
+    fn test_incomplete_inputs() {
+        for input in [
+            "echo 'open",
+            "echo \"open",
+            "echo \"open\nstill open",
+            "echo one \\",
+            "echo \"ends in \\",
+        ] {
+            assert!(!is_complete(input), "{:?} should be incomplete", input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod history_expansion_tests {
+    use codecrafters_shell::error::ShellError;
+    use codecrafters_shell::parser::expand_history;
+
+    fn history() -> Vec<String> {
+        vec![
+            "echo first".to_string(),
+            "ls -la".to_string(),
+            "echo last".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_bang_bang_is_last_command() {
+        let result = expand_history("!!", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("echo last"));
+    }
+
+    #[test]
+    fn test_bang_bang_inside_line() {
+        let result = expand_history("sudo !! | wc -l", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("sudo echo last | wc -l"));
+    }
+
+    #[test]
+    fn test_bang_number_is_history_entry() {
+        let result = expand_history("!2", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("ls -la"));
+    }
+
+    #[test]
+    fn test_bang_negative_number_counts_back() {
+        let result = expand_history("!-3", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("echo first"));
+    }
+
+    #[test]
+    fn test_bang_prefix_finds_latest_match() {
+        let result = expand_history("!ec", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("echo last"));
+    }
+
+    #[test]
+    fn test_out_of_range_event_is_error() {
+        let result = expand_history("!99", &history());
+        assert!(matches!(
+            result,
+            Err(ShellError::EventNotFound { ref event }) if event == "!99"
+        ));
+    }
+
+    #[test]
+    fn test_bang_bang_with_empty_history_is_error() {
+        assert!(expand_history("!!", &[]).is_err());
+    }
+
+    #[test]
+    fn test_line_without_designators_is_unchanged() {
+        assert_eq!(expand_history("echo hi", &history()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_literal_bangs_are_not_expanded() {
+        for line in [
+            "echo 'hi!!'",
+            "echo hi\\!!",
+            "[ ! -f x ]",
+            "echo hi!",
+            "test a != b",
+        ] {
+            assert_eq!(expand_history(line, &history()).unwrap(), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_double_quotes_do_not_stop_expansion() {
+        let result = expand_history("echo \"!!\"", &history()).unwrap();
+        assert_eq!(result.as_deref(), Some("echo \"echo last\""));
+    }
+}
+
+#[cfg(test)]
+mod heredoc_tests {
+    use super::parse_expanded_with;
+    use codecrafters_shell::error::ShellError;
+    use codecrafters_shell::parser::{ParsedCommand, is_complete};
+
+    fn parse(input: &str) -> Vec<ParsedCommand> {
+        let lookup = |name: &str| (name == "x").then(|| "value".to_string());
+        parse_expanded_with(input, &lookup)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn test_heredoc_body_is_expanded() {
+        let commands = parse("cat <<EOF\nhello $x\n'${x}' \\$x\nEOF");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].command, "cat");
+        assert!(commands[0].args.is_empty());
+        assert_eq!(
+            commands[0].heredoc.as_deref(),
+            Some("hello value\n'value' $x\n")
+        );
+    }
+
+    #[test]
+    fn test_quoted_delimiter_suppresses_expansion() {
+        for input in ["cat <<'EOF'\n$x \\$x\nEOF", "cat <<\"EOF\"\n$x \\$x\nEOF"] {
+            assert_eq!(parse(input)[0].heredoc.as_deref(), Some("$x \\$x\n"));
+        }
+        assert_eq!(
+            parse("cat <<\\EOF\n$x\nEOF")[0].heredoc.as_deref(),
+            Some("$x\n")
         );
     }
+
+    #[test]
+    fn test_dash_strips_leading_tabs() {
+        let commands = parse("cat <<-EOF\n\tone\n\t\ttwo  \n\tEOF");
+        assert_eq!(commands[0].heredoc.as_deref(), Some("one\ntwo  \n"));
+    }
+
+    #[test]
+    fn test_heredocs_read_bodies_in_order() {
+        let commands = parse("cat <<A; cat <<B | wc -l\none\nA\ntwo\nB");
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0].heredoc.as_deref(), Some("one\n"));
+        assert_eq!(commands[1].heredoc.as_deref(), Some("two\n"));
+        assert_eq!(commands[2].heredoc, None);
+    }
+
+    #[test]
+    fn test_heredoc_needs_its_delimiter_line() {
+        assert!(!is_complete("cat <<EOF"));
+        assert!(!is_complete("cat <<EOF\nline\nEOFX"));
+        assert!(is_complete("cat <<EOF\nline\nEOF"));
+        assert!(is_complete("cat <<'<<'\n<<"));
+    }
+
+    #[test]
+    fn test_heredoc_without_delimiter_is_syntax_error() {
+        let lookup = |_: &str| None;
+        assert!(matches!(
+            parse_expanded_with("cat <<", &lookup),
+            Err(ShellError::SyntaxError(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod here_string_tests {
+    use super::*;
+
+    #[test]
+    fn test_here_string_tokenizes_apart_from_other_redirects() {
+        assert_eq!(tokenize_input("cat <<< word"), ["cat", "<<<", "word"]);
+        assert_eq!(tokenize_input("cat <<<word"), ["cat", "<<<", "word"]);
+        assert_eq!(tokenize_input("cat < word"), ["cat", "<", "word"]);
+    }
+
+    #[test]
+    fn test_here_string_becomes_input_with_newline() {
+        let commands = &parse_expanded("cat <<< word").unwrap()[0];
+        assert_eq!(commands[0].heredoc.as_deref(), Some("word\n"));
+        assert!(commands[0].args.is_empty());
+        assert!(commands[0].redirects.is_empty());
+
+        let commands = &parse_expanded("cat < word").unwrap()[0];
+        assert_eq!(commands[0].heredoc, None);
+        assert_eq!(
+            commands[0].redirects,
+            [Redirect::File {
+                fd: 0,
+                path: PathBuf::from("word"),
+                mode: RedirectMode::Read,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_here_string_is_expanded_but_not_split_or_globbed() {
+        let lookup = |name: &str| (name == "x").then(|| "a  * b".to_string());
+        let commands = &parse_expanded_with("cat <<< $x", &lookup).unwrap()[0];
+        assert_eq!(commands[0].heredoc.as_deref(), Some("a  * b\n"));
+
+        let commands = &parse_expanded("cat <<< 'two words'").unwrap()[0];
+        assert_eq!(commands[0].heredoc.as_deref(), Some("two words\n"));
+    }
+
+    #[test]
+    fn test_here_string_needs_a_word() {
+        assert!(parse_input("cat <<<").is_err());
+    }
 }
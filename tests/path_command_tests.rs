@@ -0,0 +1,92 @@
+mod common;
+
+use codecrafters_shell::commands::{CommandRegistry, IoContext, ShellExecutor};
+use codecrafters_shell::error::ShellError;
+use codecrafters_shell::parser::parse_input;
+use common::EnvGuard;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Writes a script to `dir/name` that records its argument in `dir/ran`.
+fn install(dir: &Path, name: &str) {
+    let path = dir.join(name);
+    let script = format!("#!/bin/sh\necho \"$1\" > {}\n", dir.join("ran").display());
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn run_type(registry: &CommandRegistry, args: &[&str]) -> String {
+    let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+    let mut output = Vec::new();
+    registry
+        .get_builtin("type")
+        .unwrap()
+        .execute(
+            &args,
+            registry,
+            &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+        )
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[cfg(test)]
+mod path_command_tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_command_by_absolute_path() {
+        let _env = EnvGuard::lock();
+        let dir = TempDir::new().unwrap();
+        install(dir.path(), "tool");
+        let tool = dir.path().join("tool");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        let line = format!("{} absolute", tool.display());
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        assert_eq!(registry.get_last_status(), 0);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("ran")).unwrap(),
+            "absolute\n"
+        );
+    }
+
+    #[test]
+    fn test_runs_command_by_relative_path() {
+        let mut env = EnvGuard::lock();
+        let dir = TempDir::new().unwrap();
+        install(dir.path(), "tool");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry);
+
+        env.set_current_dir(dir.path());
+        executor
+            .run(&parse_input("./tool relative").unwrap())
+            .unwrap();
+        let not_found = executor.run(&parse_input("./missing").unwrap());
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("ran")).unwrap(),
+            "relative\n"
+        );
+        assert!(matches!(not_found, Err(ShellError::CommandNotFound(_))));
+    }
+
+    #[test]
+    fn test_type_resolves_paths_without_path_search() {
+        let mut env = EnvGuard::lock();
+        let dir = TempDir::new().unwrap();
+        install(dir.path(), "tool");
+        let registry = CommandRegistry::default();
+
+        env.set_current_dir(dir.path());
+        assert_eq!(run_type(&registry, &["./tool"]), "./tool is ./tool\n");
+        assert_eq!(run_type(&registry, &["-a", "./tool"]), "./tool is ./tool\n");
+        assert_eq!(run_type(&registry, &["/bin/sh"]), "/bin/sh is /bin/sh\n");
+    }
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use codecrafters_shell::commands::CommandRegistry;
+use common::EnvGuard;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Runs `test` against a registry whose PATH search starts in `bin_dir`
+/// and tries `suffixes`.
+fn with_search(bin_dir: &Path, suffixes: Option<&str>, test: impl FnOnce(&CommandRegistry)) {
+    let mut env = EnvGuard::lock();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        env::var("PATH").unwrap_or_default()
+    );
+    env.set_var("PATH", Some(&path));
+    env.set_var("SHELL_PATH_SUFFIXES", suffixes);
+
+    test(&CommandRegistry::default());
+}
+
+fn install(dir: &Path, name: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[cfg(test)]
+mod path_suffix_tests {
+    use super::*;
+
+    #[test]
+    fn test_suffix_is_not_tried_by_default() {
+        let bin_dir = TempDir::new().unwrap();
+        install(bin_dir.path(), "deploy.sh");
+
+        with_search(bin_dir.path(), None, |registry| {
+            assert_eq!(registry.get_executable_path("deploy"), None);
+            assert!(!registry.get_command_names().contains(&"deploy".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_configured_suffix_finds_file() {
+        let bin_dir = TempDir::new().unwrap();
+        let path = install(bin_dir.path(), "deploy.sh");
+
+        with_search(bin_dir.path(), Some(".py:.sh"), |registry| {
+            assert_eq!(registry.get_executable_path("deploy"), Some(path.clone()));
+            assert_eq!(registry.find_all_executables("deploy"), vec![path.clone()]);
+            assert!(registry.get_command_names().contains(&"deploy".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_exact_name_wins_over_suffix() {
+        let bin_dir = TempDir::new().unwrap();
+        let exact = install(bin_dir.path(), "build");
+        install(bin_dir.path(), "build.sh");
+
+        with_search(bin_dir.path(), Some(".sh"), |registry| {
+            assert_eq!(registry.get_executable_path("build"), Some(exact.clone()));
+        });
+    }
+}
@@ -0,0 +1,72 @@
+use codecrafters_shell::prompt::{prompt_cwd, render_prompt};
+use std::io;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod prompt_cwd_tests {
+    use super::*;
+
+    fn deleted_cwd() -> io::Result<PathBuf> {
+        Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    #[test]
+    fn test_uses_current_dir_when_available() {
+        let cwd = prompt_cwd(Ok(PathBuf::from("/tmp")), Some("/elsewhere".to_string()));
+        assert_eq!(cwd, "/tmp");
+    }
+
+    #[test]
+    fn test_falls_back_to_pwd_when_cwd_fails() {
+        let cwd = prompt_cwd(deleted_cwd(), Some("/tmp/deleted".to_string()));
+        assert_eq!(cwd, "/tmp/deleted");
+    }
+
+    #[test]
+    fn test_falls_back_to_question_mark_without_pwd() {
+        assert_eq!(prompt_cwd(deleted_cwd(), None), "?");
+        assert_eq!(prompt_cwd(deleted_cwd(), Some(String::new())), "?");
+    }
+
+    #[test]
+    fn test_render_with_unknown_cwd() {
+        let cwd = prompt_cwd(deleted_cwd(), None);
+        assert_eq!(render_prompt("\\w \\$ ", &cwd), "? $ ");
+    }
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn test_render_plain_template() {
+        assert_eq!(render_prompt("$ ", "/tmp"), "$ ");
+    }
+
+    #[test]
+    fn test_render_working_directory() {
+        assert_eq!(
+            render_prompt("[\\w]$ ", "/usr/local/bin"),
+            "[/usr/local/bin]$ "
+        );
+    }
+
+    #[test]
+    fn test_render_basename() {
+        assert_eq!(render_prompt("\\W> ", "/usr/local/bin"), "bin> ");
+        assert_eq!(render_prompt("\\W> ", "/"), "/> ");
+    }
+
+    #[test]
+    fn test_render_abbreviates_home() {
+        let home = std::env::var("HOME").unwrap();
+        let cwd = format!("{}/projects", home);
+        assert_eq!(render_prompt("\\w", &cwd), "~/projects");
+    }
+
+    #[test]
+    fn test_render_unknown_escape_is_kept() {
+        assert_eq!(render_prompt("\\q\\\\", "/"), "\\q\\");
+    }
+}
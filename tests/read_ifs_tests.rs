@@ -0,0 +1,71 @@
+mod common;
+
+use codecrafters_shell::commands::{CommandRegistry, IoContext};
+use common::EnvGuard;
+use std::env;
+use std::io::{self, Cursor};
+
+/// Feeds `line` to `read NAMES...` with `$IFS` set to `ifs`, returning the
+/// value of each name afterwards. IFS would change how every other test
+/// splits words, which is why these tests have a binary of their own.
+fn read_with_ifs(ifs: &str, line: &str, names: &[&str]) -> Vec<String> {
+    let mut env = EnvGuard::lock();
+    env.set_var("IFS", Some(ifs));
+
+    let registry = CommandRegistry::default();
+    let read_cmd = registry.get_builtin("read").unwrap();
+    let args: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+    let mut input = Cursor::new(line.as_bytes().to_vec());
+    read_cmd
+        .execute(
+            &args,
+            &registry,
+            &mut IoContext::new(&mut input, &mut io::sink(), &mut io::sink()),
+        )
+        .unwrap();
+
+    names.iter().map(|name| env::var(name).unwrap()).collect()
+}
+
+#[cfg(test)]
+mod read_ifs_tests {
+    use super::*;
+
+    #[test]
+    fn test_read_splits_on_custom_ifs() {
+        let values = read_with_ifs(
+            ":",
+            "root:x:0\n",
+            &["IFS_TEST_USER", "IFS_TEST_PASS", "IFS_TEST_UID"],
+        );
+        assert_eq!(values, ["root", "x", "0"]);
+    }
+
+    #[test]
+    fn test_read_keeps_empty_fields_between_separators() {
+        let values = read_with_ifs(
+            ":",
+            "a::c\n",
+            &["IFS_EMPTY_A", "IFS_EMPTY_B", "IFS_EMPTY_C"],
+        );
+        assert_eq!(values, ["a", "", "c"]);
+    }
+
+    #[test]
+    fn test_read_last_name_keeps_rest_with_separators() {
+        let values = read_with_ifs(":", "a:b:c\n", &["IFS_REST_FIRST", "IFS_REST_LAST"]);
+        assert_eq!(values, ["a", "b:c"]);
+    }
+
+    #[test]
+    fn test_read_whitespace_around_separator_is_trimmed() {
+        let values = read_with_ifs(" ,", "  a , b  \n", &["IFS_MIXED_A", "IFS_MIXED_B"]);
+        assert_eq!(values, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_read_empty_ifs_does_not_split() {
+        let values = read_with_ifs("", " a b \n", &["IFS_NONE_A", "IFS_NONE_B"]);
+        assert_eq!(values, [" a b ", ""]);
+    }
+}
@@ -322,4 +322,397 @@ mod history_tests {
         let content = fs::read_to_string(&history_file).unwrap();
         assert_eq!(content, "new1\nnew2\n");
     }
+
+    #[test]
+    fn test_concurrent_history_writes_are_not_interleaved() {
+        const LINES_PER_WRITER: usize = 500;
+
+        let temp_dir = TempDir::new().unwrap();
+        let history_file = temp_dir.path().join("history.txt");
+
+        let handles: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|writer| {
+                let history_file = history_file.clone();
+                std::thread::spawn(move || {
+                    let registry = CommandRegistry::new();
+                    for i in 0..LINES_PER_WRITER {
+                        registry.add_history_entry(&format!("echo {}-{}", writer, i));
+                    }
+                    for _ in 0..10 {
+                        registry
+                            .write_history_to_file(&history_file, false, false)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let content = fs::read_to_string(&history_file).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), LINES_PER_WRITER);
+
+        let writer = &lines[0][5..6];
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(*line, format!("echo {}-{}", writer, i));
+        }
+    }
+}
+
+#[cfg(test)]
+mod history_control_tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // HISTCONTROL is process-global, so tests that set it run serially
+    static HISTCONTROL_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_histcontrol(value: &str, test: impl FnOnce()) {
+        let _lock = HISTCONTROL_LOCK.lock().unwrap();
+        // SAFETY: guarded by HISTCONTROL_LOCK; no other test reads this variable.
+        unsafe { env::set_var("HISTCONTROL", value) };
+        test();
+        unsafe { env::remove_var("HISTCONTROL") };
+    }
+
+    #[test]
+    fn test_erasedups_keeps_only_latest() {
+        with_histcontrol("erasedups", || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry("pwd");
+            registry.add_history_entry("ls");
+
+            assert_eq!(registry.get_history(), vec!["pwd", "ls"]);
+        });
+    }
+
+    #[test]
+    fn test_erasedups_keeps_append_cursor_in_place() {
+        with_histcontrol("ignorespace:erasedups", || {
+            let temp_dir = TempDir::new().unwrap();
+            let first = temp_dir.path().join("first.txt");
+            let second = temp_dir.path().join("second.txt");
+
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry("pwd");
+            registry.write_history_to_file(&first, true, false).unwrap();
+
+            registry.add_history_entry("ls");
+            registry
+                .write_history_to_file(&second, true, false)
+                .unwrap();
+
+            assert_eq!(fs::read_to_string(&second).unwrap(), "ls\n");
+        });
+    }
+
+    #[test]
+    fn test_ignoredups_skips_consecutive_duplicates() {
+        with_histcontrol("ignoredups", || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry("ls");
+            registry.add_history_entry("pwd");
+            registry.add_history_entry("ls");
+
+            assert_eq!(registry.get_history(), vec!["ls", "pwd", "ls"]);
+        });
+    }
+
+    #[test]
+    fn test_ignorespace_skips_space_prefixed_commands() {
+        with_histcontrol("ignorespace", || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry(" echo secret");
+
+            assert_eq!(registry.get_history(), vec!["ls"]);
+        });
+    }
+
+    #[test]
+    fn test_ignoreboth_sets_both_options() {
+        with_histcontrol("ignoreboth", || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry("ls");
+            registry.add_history_entry(" pwd");
+
+            assert_eq!(registry.get_history(), vec!["ls"]);
+        });
+    }
+
+    #[test]
+    fn test_everything_kept_when_histcontrol_unset() {
+        let _lock = HISTCONTROL_LOCK.lock().unwrap();
+        // SAFETY: guarded by HISTCONTROL_LOCK; no other test reads this variable.
+        unsafe { env::remove_var("HISTCONTROL") };
+
+        let registry = CommandRegistry::new();
+        registry.add_history_entry("ls");
+        registry.add_history_entry("ls");
+        registry.add_history_entry(" pwd");
+
+        assert_eq!(registry.get_history(), vec!["ls", "ls", " pwd"]);
+    }
+
+    #[test]
+    fn test_duplicates_kept_without_histcontrol() {
+        with_histcontrol("", || {
+            let registry = CommandRegistry::new();
+            registry.add_history_entry("ls");
+            registry.add_history_entry("ls");
+
+            assert_eq!(registry.get_history(), vec!["ls", "ls"]);
+        });
+    }
+}
+
+#[cfg(test)]
+mod job_reaping_tests {
+    use super::*;
+    use std::process::Command;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_finished_background_job_is_reaped() {
+        let registry = CommandRegistry::new();
+        let child = Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        registry.add_job(child, "true");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut reaped = Vec::new();
+        while reaped.is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            reaped = registry.reap_jobs().unwrap();
+        }
+
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].command, "true");
+        assert_eq!(registry.get_job_count(), 0);
+
+        // A reaped child no longer exists, not even as a zombie.
+        let alive = unsafe { libc::kill(pid, 0) } == 0;
+        assert!(!alive);
+    }
+
+    #[test]
+    fn test_running_job_is_not_reaped() {
+        let registry = CommandRegistry::new();
+        let child = Command::new("sleep").arg("5").spawn().unwrap();
+        let pid = child.id() as i32;
+        registry.add_job(child, "sleep 5");
+
+        assert!(registry.reap_jobs().unwrap().is_empty());
+        assert_eq!(registry.get_job_count(), 1);
+
+        unsafe { libc::kill(pid, libc::SIGKILL) };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while registry.get_job_count() > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            registry.reap_jobs().unwrap();
+        }
+        assert_eq!(registry.get_job_count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod custom_builtin_tests {
+    use super::*;
+    use codecrafters_shell::commands::{Command, IoContext, ShellExecutor, ShellStatus};
+    use codecrafters_shell::error::ShellResult;
+    use codecrafters_shell::parser::parse_input;
+    use std::io;
+
+    struct GreetCommand;
+
+    impl Command for GreetCommand {
+        fn execute(
+            &self,
+            args: &[String],
+            _: &CommandRegistry,
+            ctx: &mut IoContext,
+        ) -> ShellResult<ShellStatus> {
+            writeln!(ctx.output, "hello, {}", args.join(" "))?;
+            Ok(ShellStatus::Continue)
+        }
+
+        fn get_name(&self) -> &str {
+            "greet"
+        }
+
+        fn get_synopsis(&self) -> &str {
+            "greet [name ...]"
+        }
+    }
+
+    fn registry_with_greet() -> CommandRegistry {
+        let mut registry = CommandRegistry::default();
+        registry.register_builtin(Box::new(GreetCommand));
+        registry
+    }
+
+    #[test]
+    fn test_custom_builtin_is_listed() {
+        let registry = registry_with_greet();
+        assert!(registry.get_builtin("greet").is_some());
+        assert!(registry.get_command_names().contains(&"greet".to_string()));
+    }
+
+    #[test]
+    fn test_custom_builtin_is_reported_by_type() {
+        let registry = registry_with_greet();
+        let type_cmd = registry.get_builtin("type").unwrap();
+        let mut output = Vec::new();
+        type_cmd
+            .execute(
+                &["greet".to_string()],
+                &registry,
+                &mut IoContext::new(&mut io::empty(), &mut output, &mut io::sink()),
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "greet is a shell builtin\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_builtin_runs_through_executor() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_file = temp_dir.path().join("greet.txt");
+
+        let registry = registry_with_greet();
+        let executor = ShellExecutor::new(&registry);
+        let commands =
+            parse_input(&format!("greet world > {}", output_file.to_str().unwrap())).unwrap();
+        executor.run(&commands).unwrap();
+
+        assert_eq!(fs::read_to_string(&output_file).unwrap(), "hello, world\n");
+    }
+}
+
+#[cfg(test)]
+mod history_option_tests {
+    use super::*;
+    use codecrafters_shell::options::ShellOption;
+
+    #[test]
+    fn test_history_recording_can_be_disabled() {
+        let registry = CommandRegistry::default();
+        registry.add_history_entry("echo before");
+
+        registry.set_option(ShellOption::History, false);
+        registry.add_history_entry("echo secret");
+
+        registry.set_option(ShellOption::History, true);
+        registry.add_history_entry("echo after");
+
+        assert_eq!(registry.get_history(), vec!["echo before", "echo after"]);
+    }
+}
+
+#[cfg(test)]
+mod find_all_executables_tests {
+    use super::*;
+
+    #[test]
+    fn test_find_all_executables_matches_first_lookup() {
+        let registry = CommandRegistry::default();
+        let all = registry.find_all_executables("sh");
+
+        assert!(!all.is_empty());
+        assert_eq!(Some(all[0].clone()), registry.get_executable_path("sh"));
+    }
+
+    #[test]
+    fn test_find_all_executables_missing() {
+        let registry = CommandRegistry::default();
+        assert!(
+            registry
+                .find_all_executables("nonexistent_command_xyz")
+                .is_empty()
+        );
+    }
+}
+
+#[cfg(test)]
+mod history_clear_tests {
+    use super::*;
+
+    #[test]
+    fn test_clear_resets_append_cursor() {
+        let temp_dir = TempDir::new().unwrap();
+        let histfile = temp_dir.path().join("history");
+
+        let registry = CommandRegistry::default();
+        registry.add_history_entry("echo one");
+        registry
+            .write_history_to_file(&histfile, true, false)
+            .unwrap();
+
+        registry.clear_history();
+        registry.add_history_entry("echo two");
+        registry
+            .write_history_to_file(&histfile, true, false)
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&histfile).unwrap(),
+            "echo one\necho two\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod describe_tests {
+    use super::*;
+
+    #[test]
+    fn test_every_builtin_describes_itself() {
+        let registry = CommandRegistry::default();
+        let infos = registry.describe_builtins();
+        assert!(!infos.is_empty());
+
+        for info in &infos {
+            let command = registry.get_builtin(&info.name).unwrap();
+            assert_eq!(info.name, command.get_name());
+            assert!(!info.synopsis.is_empty(), "{} has no synopsis", info.name);
+            assert_eq!(*info, command.describe());
+        }
+    }
+
+    #[test]
+    fn test_descriptions_are_sorted_by_name() {
+        let registry = CommandRegistry::default();
+        let names: Vec<String> = registry
+            .describe_builtins()
+            .into_iter()
+            .map(|info| info.name)
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn test_posix_flag_separates_extensions() {
+        let registry = CommandRegistry::default();
+        let posix = |name: &str| registry.get_builtin(name).unwrap().describe().posix;
+
+        assert!(posix("cd"));
+        assert!(posix("."));
+        assert!(posix("["));
+        assert!(!posix("source"));
+        assert!(!posix("shopt"));
+        assert!(!posix("history"));
+    }
 }
@@ -0,0 +1,317 @@
+use codecrafters_shell::shell::Shell;
+use rustyline::completion::Candidate;
+use std::fs;
+use tempfile::TempDir;
+
+fn replacements(shell: &Shell, line: &str) -> (usize, Vec<String>) {
+    let (start, candidates) = shell.candidates(line, line.len());
+    let replacements = candidates
+        .iter()
+        .map(|candidate| candidate.replacement().to_string())
+        .collect();
+    (start, replacements)
+}
+
+fn displays(shell: &Shell, line: &str) -> Vec<String> {
+    let (_, candidates) = shell.candidates(line, line.len());
+    candidates
+        .iter()
+        .map(|candidate| candidate.display().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod completion_tests {
+    use super::*;
+
+    fn shell() -> Shell<'static> {
+        Shell::new(vec![
+            "echo".to_string(),
+            "exit".to_string(),
+            "export".to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_first_word_completes_commands() {
+        assert_eq!(displays(&shell(), "ex"), ["exit", "export"]);
+    }
+
+    #[test]
+    fn test_single_match_completes_with_space() {
+        assert_eq!(
+            replacements(&shell(), "exp"),
+            (0, vec!["export ".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_multiple_matches_complete_common_prefix() {
+        assert_eq!(replacements(&shell(), "e"), (0, vec!["e".to_string(); 3]));
+        assert_eq!(replacements(&shell(), "ex"), (0, vec!["ex".to_string(); 2]));
+    }
+
+    #[test]
+    fn test_common_prefix_extends_the_word() {
+        let shell = Shell::new(vec!["history".to_string(), "histfile".to_string()]);
+        assert_eq!(replacements(&shell, "h"), (0, vec!["hist".to_string(); 2]));
+    }
+
+    #[test]
+    fn test_empty_line_has_no_candidates() {
+        assert_eq!(replacements(&shell(), ""), (0, Vec::new()));
+    }
+
+    #[test]
+    fn test_word_after_pipe_completes_commands() {
+        assert_eq!(
+            replacements(&shell(), "ls | ec"),
+            (5, vec!["echo ".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_second_word_completes_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("exit_notes.txt"), "").unwrap();
+        fs::create_dir(temp_dir.path().join("exports")).unwrap();
+        fs::write(temp_dir.path().join("other.txt"), "").unwrap();
+
+        let dir = format!("{}/", temp_dir.path().to_str().unwrap());
+        let line = format!("cat {}ex", dir);
+
+        assert_eq!(displays(&shell(), &line), ["exit_notes.txt", "exports"]);
+        assert_eq!(
+            replacements(&shell(), &format!("{}i", line)),
+            (4, vec![format!("{}exit_notes.txt ", dir)])
+        );
+    }
+
+    #[test]
+    fn test_second_word_does_not_offer_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let line = format!("cat {}/ex", temp_dir.path().to_str().unwrap());
+
+        let (_, candidates) = replacements(&shell(), &line);
+        assert!(candidates.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod cd_completion_tests {
+    use super::*;
+    use std::env;
+
+    fn shell() -> Shell<'static> {
+        Shell::new(vec!["cd".to_string()])
+    }
+
+    #[test]
+    fn test_cd_offers_cdpath_directories() {
+        let cdpath = TempDir::new().unwrap();
+        fs::create_dir(cdpath.path().join("project_alpha")).unwrap();
+        fs::create_dir(cdpath.path().join("project_beta")).unwrap();
+        fs::write(cdpath.path().join("project_notes.txt"), "").unwrap();
+
+        // SAFETY: no other test in this binary reads CDPATH.
+        unsafe { env::set_var("CDPATH", cdpath.path()) };
+        let result = displays(&shell(), "cd project_");
+        let single = replacements(&shell(), "cd project_b");
+        unsafe { env::remove_var("CDPATH") };
+
+        assert_eq!(result, ["project_alpha", "project_beta"]);
+        assert_eq!(single, (3, vec!["project_beta/".to_string()]));
+    }
+
+    #[test]
+    fn test_cd_offers_only_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("setup.sh"), "").unwrap();
+
+        let dir = format!("{}/", temp_dir.path().to_str().unwrap());
+        let line = format!("cd {}s", dir);
+
+        assert_eq!(
+            replacements(&shell(), &line),
+            (3, vec![format!("{}src/", dir)])
+        );
+    }
+
+    #[test]
+    fn test_cd_argument_to_other_command_completes_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("cd_notes.txt"), "").unwrap();
+
+        let dir = format!("{}/", temp_dir.path().to_str().unwrap());
+        let line = format!("echo cd {}cd", dir);
+
+        assert_eq!(
+            replacements(&shell(), &line),
+            (8, vec![format!("{}cd_notes.txt ", dir)])
+        );
+    }
+}
+
+#[cfg(test)]
+mod path_completion_tests {
+    use super::*;
+    use std::env;
+
+    fn shell() -> Shell<'static> {
+        Shell::new(vec!["cat".to_string(), "cd".to_string()])
+    }
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("src/mod.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("src/.hidden.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("setup.sh"), "").unwrap();
+        temp_dir
+    }
+
+    /// Completes `line` with `$HOME` pointing at `home`.
+    fn replacements_with_home(home: &TempDir, line: &str) -> (usize, Vec<String>) {
+        // SAFETY: no other test in this binary reads HOME.
+        unsafe { env::set_var("HOME", home.path()) };
+        replacements(&shell(), line)
+    }
+
+    #[test]
+    fn test_argument_completes_nested_path() {
+        let temp_dir = project();
+        let dir = format!("{}/src/", temp_dir.path().to_str().unwrap());
+        let line = format!("cat {}ma", dir);
+
+        assert_eq!(
+            replacements(&shell(), &line),
+            (4, vec![format!("{}main.rs ", dir)])
+        );
+    }
+
+    #[test]
+    fn test_directories_get_slash_and_files_get_space() {
+        let temp_dir = project();
+        let dir = format!("{}/", temp_dir.path().to_str().unwrap());
+
+        assert_eq!(
+            replacements(&shell(), &format!("cat {}se", dir)),
+            (4, vec![format!("{}setup.sh ", dir)])
+        );
+        assert_eq!(
+            replacements(&shell(), &format!("cat {}sr", dir)),
+            (4, vec![format!("{}src/", dir)])
+        );
+    }
+
+    #[test]
+    fn test_hidden_files_need_a_leading_dot() {
+        let temp_dir = project();
+        let dir = format!("{}/src/", temp_dir.path().to_str().unwrap());
+
+        let all = displays(&shell(), &format!("cat {}", dir));
+        assert_eq!(all, ["main.rs", "mod.rs"]);
+
+        let (_, hidden) = replacements(&shell(), &format!("cat {}.", dir));
+        assert_eq!(hidden, vec![format!("{}.hidden.rs ", dir)]);
+    }
+
+    #[test]
+    fn test_tilde_prefix_is_expanded_for_lookup_but_kept() {
+        let home = project();
+
+        assert_eq!(
+            replacements_with_home(&home, "cat ~/src/ma"),
+            (4, vec!["~/src/main.rs ".to_string()])
+        );
+        assert_eq!(
+            replacements_with_home(&home, "cd ~/s"),
+            (3, vec!["~/src/".to_string()])
+        );
+    }
+}
+
+#[cfg(test)]
+mod case_insensitive_completion_tests {
+    use super::*;
+
+    fn commands() -> Vec<String> {
+        vec!["echo".to_string(), "exit".to_string(), "Export".to_string()]
+    }
+
+    #[test]
+    fn test_default_is_case_sensitive() {
+        let shell = Shell::new(commands());
+        assert!(displays(&shell, "EC").is_empty());
+        assert_eq!(displays(&shell, "ex"), ["exit"]);
+    }
+
+    #[test]
+    fn test_case_insensitive_matches_any_case() {
+        let shell = Shell::new(commands()).with_case_insensitive(true);
+        assert_eq!(replacements(&shell, "EC"), (0, vec!["echo ".to_string()]));
+        assert_eq!(displays(&shell, "ex"), ["exit", "Export"]);
+    }
+
+    #[test]
+    fn test_case_insensitive_file_completion() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("README.md"), "").unwrap();
+        fs::write(temp_dir.path().join("main.rs"), "").unwrap();
+        let dir = format!("{}/", temp_dir.path().to_str().unwrap());
+        let line = format!("cat {}read", dir);
+
+        assert!(displays(&Shell::new(commands()), &line).is_empty());
+        assert_eq!(
+            replacements(&Shell::new(commands()).with_case_insensitive(true), &line),
+            (4, vec![format!("{}README.md ", dir)])
+        );
+    }
+}
+
+#[cfg(test)]
+mod registry_completion_tests {
+    use super::*;
+    use codecrafters_shell::commands::CommandRegistry;
+    use std::env;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_executable_added_after_startup_is_completed() {
+        let bin_dir = TempDir::new().unwrap();
+        let original_path = env::var("PATH").unwrap_or_default();
+        // SAFETY: no other test in this binary reads PATH.
+        unsafe {
+            env::set_var(
+                "PATH",
+                format!("{}:{}", bin_dir.path().display(), original_path),
+            )
+        };
+
+        let registry = CommandRegistry::default();
+        let shell = Shell::from_registry(&registry);
+        let before = displays(&shell, "zz_late_tool");
+
+        let tool = bin_dir.path().join("zz_late_tool");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        let after = replacements(&shell, "zz_late");
+
+        unsafe { env::set_var("PATH", original_path) };
+
+        assert!(before.is_empty());
+        assert_eq!(after, (0, vec!["zz_late_tool ".to_string()]));
+    }
+
+    #[test]
+    fn test_alias_defined_after_startup_is_completed() {
+        let registry = CommandRegistry::default();
+        let shell = Shell::from_registry(&registry);
+        assert!(displays(&shell, "zz_alias").is_empty());
+
+        registry.set_alias("zz_alias_ll", "ls -l");
+        assert_eq!(displays(&shell, "zz_alias"), ["zz_alias_ll"]);
+    }
+}
@@ -0,0 +1,175 @@
+use codecrafters_shell::commands::{CommandRegistry, ShellExecutor};
+use codecrafters_shell::parser::parse_input;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+// These tests send SIGINT to process groups, so they live in their own
+// binary away from tests that spawn children in the test process's group.
+
+/// Polls until `path` holds a pid, as written by the command under test.
+fn read_pid(path: &std::path::Path) -> libc::pid_t {
+    loop {
+        if let Ok(contents) = fs::read_to_string(path)
+            && let Ok(pid) = contents.trim().parse()
+        {
+            return pid;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod job_control_tests {
+    use super::*;
+
+    #[test]
+    fn test_foreground_command_runs_in_its_own_process_group() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("pid");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_job_control(true);
+
+        let signaller = {
+            let pid_file = pid_file.clone();
+            thread::spawn(move || {
+                let pid = read_pid(&pid_file);
+                // SAFETY: kill takes no pointers.
+                unsafe { libc::kill(-pid, libc::SIGINT) }
+            })
+        };
+
+        let line = format!("sh -c 'echo $$ > {}; exec sleep 5'", pid_file.display());
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+
+        // Signalling the child's group only works if it leads one, and the
+        // interrupt ends it without reaching this process
+        assert_eq!(signaller.join().unwrap(), 0);
+        assert_eq!(registry.get_last_status(), 130);
+
+        executor.run(&parse_input("true").unwrap()).unwrap();
+        assert_eq!(registry.get_last_status(), 0);
+    }
+
+    #[test]
+    fn test_background_pipeline_shares_one_group() {
+        let temp_dir = TempDir::new().unwrap();
+        let groups = temp_dir.path().join("groups");
+        let registry = CommandRegistry::default();
+        let executor = ShellExecutor::new(&registry).with_job_control(true);
+
+        let line = format!(
+            "sh -c 'ps -o pgid= $$' | sh -c 'cat; ps -o pgid= $$' > {} &",
+            groups.display()
+        );
+        executor.run(&parse_input(&line).unwrap()).unwrap();
+        registry.wait_for_all_jobs().unwrap();
+
+        let contents = fs::read_to_string(&groups).unwrap();
+        let groups: Vec<&str> = contents.split_whitespace().collect();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0], groups[1]);
+        // SAFETY: getpgrp cannot fail.
+        assert_ne!(groups[0], unsafe { libc::getpgrp() }.to_string());
+    }
+}
+
+#[cfg(test)]
+mod interrupt_tests {
+    use super::*;
+
+    #[test]
+    fn test_ctrl_c_ends_foreground_command_but_not_the_shell() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("pid");
+
+        // Its own group stands in for the terminal's foreground group, which
+        // receives Ctrl-C when the shell is not doing job control
+        let mut shell = Command::new(env!("CARGO_BIN_EXE_codecrafters-shell"))
+            .process_group(0)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut stdin = shell.stdin.take().unwrap();
+        writeln!(
+            stdin,
+            "sh -c 'echo $$ > {}; exec sleep 5'",
+            pid_file.display()
+        )
+        .unwrap();
+
+        read_pid(&pid_file);
+        // SAFETY: kill takes no pointers.
+        assert_eq!(
+            unsafe { libc::kill(-(shell.id() as libc::pid_t), libc::SIGINT) },
+            0
+        );
+
+        writeln!(stdin, "echo survived $?").unwrap();
+        drop(stdin);
+
+        let stdout = BufReader::new(shell.stdout.take().unwrap());
+        let output: Vec<String> = stdout.lines().map(Result::unwrap).collect();
+        let status = shell.wait().unwrap();
+
+        assert_eq!(status.signal(), None);
+        assert!(
+            output.iter().any(|line| line.ends_with("survived 130")),
+            "{:?}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_ctrl_c_breaks_off_wait_but_leaves_the_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("pid");
+
+        let mut shell = Command::new(env!("CARGO_BIN_EXE_codecrafters-shell"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut stdin = shell.stdin.take().unwrap();
+        writeln!(
+            stdin,
+            "sh -c 'echo $$ > {}; exec sleep 5' &",
+            pid_file.display()
+        )
+        .unwrap();
+        writeln!(stdin, "wait; echo waited $?").unwrap();
+        writeln!(stdin, "jobs").unwrap();
+        drop(stdin);
+        let job = read_pid(&pid_file);
+
+        // The shell only takes the interrupt once it is waiting, so keep
+        // sending it until the wait gives up
+        let mut stdout = BufReader::new(shell.stdout.take().unwrap()).lines();
+        let shell_pid = shell.id() as libc::pid_t;
+        let signaller = thread::spawn(move || {
+            // SAFETY: kill takes no pointers.
+            while unsafe { libc::kill(shell_pid, libc::SIGINT) } == 0 {
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+        let waited = stdout
+            .find(|line| line.as_ref().unwrap().contains("waited"))
+            .unwrap()
+            .unwrap();
+        let jobs = stdout.next().unwrap().unwrap();
+
+        // SAFETY: kill takes no pointers.
+        unsafe { libc::kill(job, libc::SIGKILL) };
+        shell.wait().unwrap();
+        signaller.join().unwrap();
+        assert!(waited.ends_with("waited 130"), "{:?}", waited);
+        assert!(jobs.contains("Running"), "{:?}", jobs);
+    }
+}
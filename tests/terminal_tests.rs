@@ -0,0 +1,30 @@
+use codecrafters_shell::terminal::truncate;
+
+#[cfg(test)]
+mod truncate_tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_unchanged() {
+        assert_eq!(truncate("echo hello", 20), "echo hello");
+        assert_eq!(truncate("echo hello", 10), "echo hello");
+    }
+
+    #[test]
+    fn test_long_text_ends_with_ellipsis() {
+        assert_eq!(truncate("echo hello world", 10), "echo hell…");
+        assert_eq!(truncate("echo hello world", 10).chars().count(), 10);
+    }
+
+    #[test]
+    fn test_counts_characters_not_bytes() {
+        assert_eq!(truncate("échoéécho", 5), "écho…");
+        assert_eq!(truncate("ééé", 3), "ééé");
+    }
+
+    #[test]
+    fn test_tiny_widths() {
+        assert_eq!(truncate("echo", 1), "…");
+        assert_eq!(truncate("echo", 0), "");
+    }
+}